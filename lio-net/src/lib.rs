@@ -1,7 +1,8 @@
 use std::io::{self};
-use std::net::{SocketAddr, ToSocketAddrs};
+use std::net::{Shutdown, SocketAddr, ToSocketAddrs};
 use std::os::fd::{FromRawFd, RawFd};
 
+use lio::SocketAddress;
 use socket2::{Domain, Protocol, Type};
 
 pub struct Fd(RawFd);
@@ -51,6 +52,9 @@ impl TcpListener {
     let (rawfd, addr) = lio::accept(self.0.0.0).await?;
 
     let socket = Socket::from(unsafe { Fd::from_raw_fd(rawfd) });
+    let addr = addr.as_inet().ok_or_else(|| {
+      io::Error::new(io::ErrorKind::InvalidData, "peer address is not inet")
+    })?;
 
     Ok((TcpStream(socket), addr))
   }
@@ -92,7 +96,10 @@ impl TcpStream {
     self.0.recv(vec)
   }
 
-  pub fn shutdown(&self, how: i32) -> impl Future<Output = io::Result<()>> {
+  pub fn shutdown(
+    &self,
+    how: Shutdown,
+  ) -> impl Future<Output = io::Result<()>> {
     self.0.shutdown(how)
   }
 }
@@ -119,7 +126,7 @@ impl Socket {
     lio::listen(self.0.0, 128).await
   }
 
-  pub async fn accept(&self) -> io::Result<(Socket, SocketAddr)> {
+  pub async fn accept(&self) -> io::Result<(Socket, SocketAddress)> {
     let (raw_fd, addr) = lio::accept(self.0.0).await?;
     let fd = unsafe { Fd::from_raw_fd(raw_fd) };
 
@@ -137,7 +144,7 @@ impl Socket {
   pub async fn send(&self, vec: Vec<u8>) -> lio::BufResult<i32, Vec<u8>> {
     lio::send(self.0.0, vec, None).await
   }
-  pub async fn shutdown(&self, how: i32) -> io::Result<()> {
+  pub async fn shutdown(&self, how: Shutdown) -> io::Result<()> {
     lio::shutdown(self.0.0, how).await
   }
 }
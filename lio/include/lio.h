@@ -0,0 +1,877 @@
+/* DO NOT MODIFY THIS MANUALLY! This file was autogenerated to  match lio's API. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+#include <sys/socket.h>
+#include <sys/types.h>
+
+/**
+ * Refers to a submitted operation, for [`lio_cancel`]. 0 if it can't be
+ * cancelled, because it completed right away or runs on a thread of its own.
+ */
+typedef uint64_t LioHandle;
+
+/**
+ * A completed tagged operation, filled in by [`lio_poll`].
+ */
+typedef struct LioCompletion {
+  /**
+   * Tag the operation was submitted with.
+   */
+  uint64_t tag;
+  /**
+   * Same as the `result` the callback would get.
+   */
+  int32_t result;
+  /**
+   * The buffer handed back, which the caller must free, or null. For
+   * `lio_accept_tagged`, the peer address as a `struct sockaddr_storage`.
+   */
+  uint8_t *buf;
+  /**
+   * Length of `buf`.
+   */
+  uintptr_t len;
+} LioCompletion;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Shut down part of a full-duplex connection.
+ *
+ * # Parameters
+ * - `fd`: Socket file descriptor
+ * - `how`: How to shutdown (SHUT_RD=0, SHUT_WR=1, SHUT_RDWR=2)
+ * - `userdata`: Passed back to `callback` untouched
+ * - `callback(userdata, result)`: Called when complete
+ *   - `result`: 0 on success, or negative errno on error (EINVAL for an unknown `how`)
+ */
+LioHandle lio_shutdown(int fd,
+                       int32_t how,
+                       void *userdata,
+                       void (*callback)(void*, int32_t));
+
+/**
+ * Create a symbolic link.
+ *
+ * # Parameters
+ * - `new_dir_fd`: Directory `linkpath` is relative to, or `AT_FDCWD`
+ * - `target`: Nul-terminated path the link points to
+ * - `linkpath`: Nul-terminated path of the new link
+ * - `userdata`: Passed back to `callback` untouched
+ * - `callback(userdata, result)`: Called when complete
+ *   - `result`: 0 on success, or negative errno on error
+ */
+LioHandle lio_symlinkat(int new_dir_fd,
+                        const char *target,
+                        const char *linkpath,
+                        void *userdata,
+                        void (*callback)(void*, int32_t));
+
+/**
+ * Create a hard link.
+ *
+ * # Parameters
+ * - `old_dir_fd`: Directory `old_path` is relative to, or `AT_FDCWD`
+ * - `old_path`: Nul-terminated path of the existing file
+ * - `new_dir_fd`: Directory `new_path` is relative to, or `AT_FDCWD`
+ * - `new_path`: Nul-terminated path of the new link
+ * - `userdata`: Passed back to `callback` untouched
+ * - `callback(userdata, result)`: Called when complete
+ *   - `result`: 0 on success, or negative errno on error
+ */
+LioHandle lio_linkat(int old_dir_fd,
+                     const char *old_path,
+                     int new_dir_fd,
+                     const char *new_path,
+                     void *userdata,
+                     void (*callback)(void*, int32_t));
+
+/**
+ * Rename a file.
+ *
+ * # Parameters
+ * - `old_dir_fd`: Directory `old_path` is relative to, or `AT_FDCWD`
+ * - `old_path`: Nul-terminated path of the existing file
+ * - `new_dir_fd`: Directory `new_path` is relative to, or `AT_FDCWD`
+ * - `new_path`: Nul-terminated new path
+ * - `userdata`: Passed back to `callback` untouched
+ * - `callback(userdata, result)`: Called when complete
+ *   - `result`: 0 on success, or negative errno on error
+ */
+LioHandle lio_renameat(int old_dir_fd,
+                       const char *old_path,
+                       int new_dir_fd,
+                       const char *new_path,
+                       void *userdata,
+                       void (*callback)(void*, int32_t));
+
+/**
+ * Remove a file.
+ *
+ * # Parameters
+ * - `dir_fd`: Directory `pathname` is relative to, or `AT_FDCWD`
+ * - `pathname`: Nul-terminated path of the file
+ * - `userdata`: Passed back to `callback` untouched
+ * - `callback(userdata, result)`: Called when complete
+ *   - `result`: 0 on success, or negative errno on error
+ */
+LioHandle lio_unlinkat(int dir_fd,
+                       const char *pathname,
+                       void *userdata,
+                       void (*callback)(void*, int32_t));
+
+/**
+ * Synchronize a file's in-core state with storage device.
+ *
+ * # Parameters
+ * - `fd`: File descriptor
+ * - `userdata`: Passed back to `callback` untouched
+ * - `callback(userdata, result)`: Called when complete
+ *   - `result`: 0 on success, or negative errno on error
+ */
+LioHandle lio_fsync(int fd,
+                    void *userdata,
+                    void (*callback)(void*, int32_t));
+
+/**
+ * Synchronize a file's data with storage device, without metadata that isn't
+ * needed to read it back.
+ *
+ * # Parameters
+ * - `fd`: File descriptor
+ * - `userdata`: Passed back to `callback` untouched
+ * - `callback(userdata, result)`: Called when complete
+ *   - `result`: 0 on success, or negative errno on error
+ */
+LioHandle lio_fdatasync(int fd,
+                        void *userdata,
+                        void (*callback)(void*, int32_t));
+
+#if defined(__linux__)
+/**
+ * Synchronize a byte range of a file with storage device (Linux only).
+ *
+ * # Parameters
+ * - `fd`: File descriptor
+ * - `offset`: Start of the range
+ * - `nbytes`: Length of the range, or 0 for up to the end of the file
+ * - `flags`: SYNC_FILE_RANGE_* flags
+ * - `userdata`: Passed back to `callback` untouched
+ * - `callback(userdata, result)`: Called when complete
+ *   - `result`: 0 on success, or negative errno on error
+ */
+LioHandle lio_sync_file_range(int fd,
+                              uint64_t offset,
+                              uint32_t nbytes,
+                              uint32_t flags,
+                              void *userdata,
+                              void (*callback)(void*, int32_t));
+#endif
+
+/**
+ * Write data to a file descriptor.
+ *
+ * Ownership of `buf` transfers to lio and returns via callback. See module docs for details.
+ *
+ * # Parameters
+ * - `fd`: File descriptor
+ * - `buf`: malloc-allocated buffer containing data to write
+ * - `buf_len`: Buffer length in bytes
+ * - `offset`: File offset, or -1 for current position
+ * - `userdata`: Passed back to `callback` untouched
+ * - `callback(userdata, result, buf, len)`: Called when complete
+ *   - `result`: Bytes written, or negative errno on error
+ *   - `buf`: Original buffer pointer (must free)
+ *   - `len`: Original buffer length
+ */
+LioHandle lio_write(int fd,
+                    uint8_t *buf,
+                    uintptr_t buf_len,
+                    int64_t offset,
+                    void *userdata,
+                    void (*callback)(void*, int32_t, uint8_t*, uintptr_t));
+
+/**
+ * Read data from a file descriptor.
+ *
+ * Ownership of `buf` transfers to lio and returns via callback. See module docs for details.
+ *
+ * # Parameters
+ * - `fd`: File descriptor
+ * - `buf`: malloc-allocated buffer to read into
+ * - `buf_len`: Buffer length in bytes
+ * - `offset`: File offset, or -1 for current position
+ * - `userdata`: Passed back to `callback` untouched
+ * - `callback(userdata, result, buf, len)`: Called when complete
+ *   - `result`: Bytes read (check this, not `len`), 0 on EOF, or negative errno on error
+ *   - `buf`: Original buffer pointer containing data (must free)
+ *   - `len`: Original buffer length
+ */
+LioHandle lio_read(int fd,
+                   uint8_t *buf,
+                   uintptr_t buf_len,
+                   int64_t offset,
+                   void *userdata,
+                   void (*callback)(void*, int32_t, uint8_t*, uintptr_t));
+
+/**
+ * Truncate a file to a specified length.
+ *
+ * # Parameters
+ * - `fd`: File descriptor
+ * - `len`: New file length in bytes
+ * - `userdata`: Passed back to `callback` untouched
+ * - `callback(userdata, result)`: Called when complete
+ *   - `result`: 0 on success, or negative errno on error
+ */
+LioHandle lio_truncate(int fd,
+                       uint64_t len,
+                       void *userdata,
+                       void (*callback)(void*, int32_t));
+
+/**
+ * Create a socket.
+ *
+ * # Parameters
+ * - `domain`: Protocol family (AF_INET=2, AF_INET6=10, etc.)
+ * - `ty`: Socket type (SOCK_STREAM=1, SOCK_DGRAM=2, etc.)
+ * - `proto`: Protocol (IPPROTO_TCP=6, IPPROTO_UDP=17, or 0 for default)
+ * - `userdata`: Passed back to `callback` untouched
+ * - `callback(userdata, result)`: Called when complete
+ *   - `result`: Socket file descriptor on success, or negative errno on error
+ */
+LioHandle lio_socket(int32_t domain,
+                     int32_t ty,
+                     int32_t proto,
+                     void *userdata,
+                     void (*callback)(void*, int32_t));
+
+/**
+ * Bind a socket to an address.
+ *
+ * # Parameters
+ * - `fd`: Socket file descriptor
+ * - `sock`: Pointer to sockaddr structure (sockaddr_in, sockaddr_in6 or sockaddr_un)
+ * - `sock_len`: Pointer to size of sockaddr structure
+ * - `userdata`: Passed back to `callback` untouched
+ * - `callback(userdata, result)`: Called when complete
+ *   - `result`: 0 on success, or negative errno on error (EINVAL for an unsupported address)
+ */
+LioHandle lio_bind(int fd,
+                   const struct sockaddr *sock,
+                   const socklen_t *sock_len,
+                   void *userdata,
+                   void (*callback)(void*, int32_t));
+
+/**
+ * Accept a connection on a socket.
+ *
+ * # Parameters
+ * - `fd`: Listening socket file descriptor
+ * - `userdata`: Passed back to `callback` untouched
+ * - `callback(userdata, result, addr)`: Called when complete
+ *   - `result`: New socket file descriptor on success, or negative errno on error
+ *   - `addr`: Pointer to peer address, a sockaddr_in, sockaddr_in6 or sockaddr_un
+ *     depending on `ss_family` (null on error, caller must free on success)
+ */
+LioHandle lio_accept(int fd,
+                     void *userdata,
+                     void (*callback)(void*, int32_t, const struct sockaddr_storage*));
+
+/**
+ * Listen for connections on a socket.
+ *
+ * # Parameters
+ * - `fd`: Socket file descriptor
+ * - `backlog`: Maximum length of pending connections queue
+ * - `userdata`: Passed back to `callback` untouched
+ * - `callback(userdata, result)`: Called when complete
+ *   - `result`: 0 on success, or negative errno on error
+ */
+LioHandle lio_listen(int fd,
+                     int32_t backlog,
+                     void *userdata,
+                     void (*callback)(void*, int32_t));
+
+/**
+ * Connect a socket to an address.
+ *
+ * # Parameters
+ * - `fd`: Socket file descriptor
+ * - `sock`: Pointer to sockaddr structure (sockaddr_in, sockaddr_in6 or sockaddr_un)
+ * - `sock_len`: Size of sockaddr structure
+ * - `userdata`: Passed back to `callback` untouched
+ * - `callback(userdata, result)`: Called when complete
+ *   - `result`: 0 on success, or negative errno on error (EINVAL for an unsupported address)
+ */
+LioHandle lio_connect(int fd,
+                      const struct sockaddr *sock,
+                      socklen_t sock_len,
+                      void *userdata,
+                      void (*callback)(void*, int32_t));
+
+/**
+ * Send data to a socket.
+ *
+ * Ownership of `buf` transfers to lio and returns via callback. See module docs for details.
+ *
+ * # Parameters
+ * - `fd`: Socket file descriptor
+ * - `buf`: malloc-allocated buffer containing data to send
+ * - `buf_len`: Buffer length in bytes
+ * - `flags`: Send flags (e.g., MSG_DONTWAIT, MSG_NOSIGNAL)
+ * - `userdata`: Passed back to `callback` untouched
+ * - `callback(userdata, result, buf, len)`: Called when complete
+ *   - `result`: Bytes sent, or negative errno on error
+ *   - `buf`: Original buffer pointer (must free)
+ *   - `len`: Original buffer length
+ */
+LioHandle lio_send(int fd,
+                   uint8_t *buf,
+                   uintptr_t buf_len,
+                   int32_t flags,
+                   void *userdata,
+                   void (*callback)(void*, int32_t, uint8_t*, uintptr_t));
+
+/**
+ * Receive data from a socket.
+ *
+ * Ownership of `buf` transfers to lio and returns via callback. See module docs for details.
+ *
+ * # Parameters
+ * - `fd`: Socket file descriptor
+ * - `buf`: malloc-allocated buffer to receive into
+ * - `buf_len`: Buffer length in bytes
+ * - `flags`: Receive flags (e.g., MSG_PEEK, MSG_WAITALL)
+ * - `userdata`: Passed back to `callback` untouched
+ * - `callback(userdata, result, buf, len)`: Called when complete
+ *   - `result`: Bytes received (check this, not `len`), or negative errno on error
+ *   - `buf`: Original buffer pointer containing data (must free)
+ *   - `len`: Original buffer length
+ */
+LioHandle lio_recv(int fd,
+                   uint8_t *buf,
+                   uintptr_t buf_len,
+                   int32_t flags,
+                   void *userdata,
+                   void (*callback)(void*, int32_t, uint8_t*, uintptr_t));
+
+/**
+ * Write all of a buffer, resubmitting after short writes.
+ *
+ * Same as [`lio_write`], except for the result.
+ *
+ * # Parameters
+ * - `userdata`: Passed back to `callback` untouched
+ * - `callback(userdata, result, buf, len)`: Called when all of `buf` is written, or on error
+ *   - `result`: `buf_len` on success, bytes written if a write made no progress,
+ *     or negative errno on error
+ */
+LioHandle lio_write_all(int fd,
+                        uint8_t *buf,
+                        uintptr_t buf_len,
+                        int64_t offset,
+                        void *userdata,
+                        void (*callback)(void*, int32_t, uint8_t*, uintptr_t));
+
+/**
+ * Fill a whole buffer, resubmitting after short reads.
+ *
+ * Same as [`lio_read`], except for the result.
+ *
+ * # Parameters
+ * - `userdata`: Passed back to `callback` untouched
+ * - `callback(userdata, result, buf, len)`: Called when `buf` is full, or on error
+ *   - `result`: `buf_len` on success, bytes read if the file ended first,
+ *     or negative errno on error
+ */
+LioHandle lio_read_exact(int fd,
+                         uint8_t *buf,
+                         uintptr_t buf_len,
+                         int64_t offset,
+                         void *userdata,
+                         void (*callback)(void*, int32_t, uint8_t*, uintptr_t));
+
+/**
+ * Send all of a buffer, resubmitting after short sends.
+ *
+ * Same as [`lio_send`] without flags, except for the result.
+ *
+ * # Parameters
+ * - `userdata`: Passed back to `callback` untouched
+ * - `callback(userdata, result, buf, len)`: Called when all of `buf` is sent, or on error
+ *   - `result`: `buf_len` on success, or negative errno on error
+ */
+LioHandle lio_send_all(int fd,
+                       uint8_t *buf,
+                       uintptr_t buf_len,
+                       void *userdata,
+                       void (*callback)(void*, int32_t, uint8_t*, uintptr_t));
+
+/**
+ * Fill a whole buffer from a socket, resubmitting after short receives.
+ *
+ * Same as [`lio_recv`] without flags, except for the result.
+ *
+ * # Parameters
+ * - `userdata`: Passed back to `callback` untouched
+ * - `callback(userdata, result, buf, len)`: Called when `buf` is full, or on error
+ *   - `result`: `buf_len` on success, bytes received if the peer shut down
+ *     first, or negative errno on error
+ */
+LioHandle lio_recv_exact(int fd,
+                         uint8_t *buf,
+                         uintptr_t buf_len,
+                         void *userdata,
+                         void (*callback)(void*, int32_t, uint8_t*, uintptr_t));
+
+/**
+ * Close a file descriptor.
+ *
+ * # Parameters
+ * - `fd`: File descriptor to close
+ * - `userdata`: Passed back to `callback` untouched
+ * - `callback(userdata, result)`: Called when complete
+ *   - `result`: 0 on success, or negative errno on error
+ */
+LioHandle lio_close(int fd,
+                    void *userdata,
+                    void (*callback)(void*, int32_t));
+
+/**
+ * Open a file.
+ *
+ * # Parameters
+ * - `dir_fd`: Directory `pathname` is relative to, or `AT_FDCWD`
+ * - `pathname`: Nul-terminated path of the file
+ * - `flags`: Open flags (e.g., O_RDONLY, O_CREAT)
+ * - `mode`: Permissions of a newly created file, ignored without O_CREAT or O_TMPFILE
+ * - `userdata`: Passed back to `callback` untouched
+ * - `callback(userdata, result)`: Called when complete
+ *   - `result`: File descriptor on success, or negative errno on error
+ */
+LioHandle lio_openat(int dir_fd,
+                     const char *pathname,
+                     int32_t flags,
+                     mode_t mode,
+                     void *userdata,
+                     void (*callback)(void*, int32_t));
+
+#if defined(__linux__)
+/**
+ * Open a file, with control over path resolution (Linux only).
+ *
+ * # Parameters
+ * - `dir_fd`: Directory `pathname` is relative to, or `AT_FDCWD`
+ * - `pathname`: Nul-terminated path of the file
+ * - `flags`: Open flags (e.g., O_RDONLY, O_CREAT)
+ * - `mode`: Permissions of a newly created file, only allowed with O_CREAT or O_TMPFILE
+ * - `resolve`: RESOLVE_* flags (e.g., RESOLVE_BENEATH), or 0
+ * - `userdata`: Passed back to `callback` untouched
+ * - `callback(userdata, result)`: Called when complete
+ *   - `result`: File descriptor on success, or negative errno on error
+ */
+LioHandle lio_openat2(int dir_fd,
+                      const char *pathname,
+                      int32_t flags,
+                      mode_t mode,
+                      uint64_t resolve,
+                      void *userdata,
+                      void (*callback)(void*, int32_t));
+#endif
+
+#if defined(__linux__)
+/**
+ * Duplicate pipe content without consuming it (Linux only).
+ *
+ * # Parameters
+ * - `fd_in`: Pipe to read from
+ * - `fd_out`: Pipe to write to
+ * - `size`: Maximum number of bytes to duplicate
+ * - `userdata`: Passed back to `callback` untouched
+ * - `callback(userdata, result)`: Called when complete
+ *   - `result`: Bytes duplicated, or negative errno on error
+ */
+LioHandle lio_tee(int fd_in,
+                  int fd_out,
+                  uint32_t size,
+                  void *userdata,
+                  void (*callback)(void*, int32_t));
+#endif
+
+#if defined(__linux__)
+/**
+ * Wait for a duration (Linux only).
+ *
+ * # Parameters
+ * - `duration_ms`: Milliseconds to wait
+ * - `userdata`: Passed back to `callback` untouched
+ * - `callback(userdata, result)`: Called when complete
+ *   - `result`: 0 once the duration has passed, or negative errno on error
+ */
+LioHandle lio_timeout(uint64_t duration_ms,
+                      void *userdata,
+                      void (*callback)(void*, int32_t));
+#endif
+
+#if defined(__linux__)
+/**
+ * Wait until a futex word is woken (Linux only).
+ *
+ * Completes immediately if `*futex` isn't `expected`, so re-check the value in a loop.
+ *
+ * # Parameters
+ * - `futex`: 4-byte aligned futex word, must stay valid until `callback` is called
+ * - `expected`: Value `*futex` is expected to hold
+ * - `userdata`: Passed back to `callback` untouched
+ * - `callback(userdata, result)`: Called when complete
+ *   - `result`: 0 on wake-up or if `*futex` isn't `expected`, or negative errno on error
+ */
+LioHandle lio_futex_wait(const uint32_t *futex,
+                         uint32_t expected,
+                         void *userdata,
+                         void (*callback)(void*, int32_t));
+#endif
+
+#if defined(__linux__)
+/**
+ * Wake waiters of a futex word (Linux only).
+ *
+ * # Parameters
+ * - `futex`: 4-byte aligned futex word, must stay valid until `callback` is called
+ * - `count`: Maximum number of waiters to wake
+ * - `userdata`: Passed back to `callback` untouched
+ * - `callback(userdata, result)`: Called when complete
+ *   - `result`: Number of waiters woken, or negative errno on error
+ */
+LioHandle lio_futex_wake(const uint32_t *futex,
+                         uint32_t count,
+                         void *userdata,
+                         void (*callback)(void*, int32_t));
+#endif
+
+/**
+ * Wait for a child process to exit, and reap it.
+ *
+ * # Parameters
+ * - `pid`: Process id of a child of this process
+ * - `userdata`: Passed back to `callback` untouched
+ * - `callback(userdata, result)`: Called when complete
+ *   - `result`: Wait status for WIFEXITED/WEXITSTATUS and friends, or negative errno on error
+ */
+LioHandle lio_waitid(uint32_t pid,
+                     void *userdata,
+                     void (*callback)(void*, int32_t));
+
+/**
+ * Cancel an operation.
+ *
+ * Its callback is still called exactly once, with `-ECANCELED` if it was
+ * cancelled in time, and otherwise with its usual result.
+ *
+ * # Parameters
+ * - `handle`: Returned by the operation's submission
+ *
+ * # Returns
+ * 0 if cancellation was requested, or `-ENOENT` if the operation already
+ * completed or can't be cancelled.
+ */
+int32_t lio_cancel(LioHandle handle);
+
+/**
+ * Start the lio runtime, and a background thread running the callbacks.
+ *
+ * Must be called once before any other function.
+ */
+void lio_init(void);
+
+/**
+ * Shutdown the lio runtime and wait for all pending operations to complete.
+ *
+ * This function blocks until all pending I/O operations finish and their callbacks are called.
+ * After calling this, no new operations should be submitted.
+ */
+void lio_exit(void);
+
+/**
+ * Start the lio runtime without a background thread. Completions, and the
+ * callbacks of non-tagged operations, then only happen inside [`lio_poll`].
+ *
+ * Use instead of `lio_init`.
+ */
+void lio_init_polled(void);
+
+/**
+ * Wait for tagged operations to complete.
+ *
+ * Without a background thread, see [`lio_init_polled`], this also drives lio,
+ * and runs the callbacks of non-tagged operations on the calling thread.
+ *
+ * # Parameters
+ * - `completions`: Array with room for `max` completions
+ * - `max`: Maximum number of completions to return
+ * - `timeout_ms`: Milliseconds to wait for a first completion, 0 to not wait,
+ *   or -1 to wait indefinitely
+ *
+ * # Returns
+ * Number of completions written to `completions`, 0 on timeout, or negative
+ * errno on error.
+ */
+int32_t lio_poll(LioCompletion *completions,
+                 uintptr_t max,
+                 int32_t timeout_ms);
+
+/**
+ * Same as [`lio_shutdown`], but the completion is queued for [`lio_poll`] under `tag`.
+ */
+LioHandle lio_shutdown_tagged(int fd,
+                              int32_t how,
+                              uint64_t tag);
+
+/**
+ * Same as [`lio_symlinkat`], but the completion is queued for [`lio_poll`] under `tag`.
+ */
+LioHandle lio_symlinkat_tagged(int new_dir_fd,
+                               const char *target,
+                               const char *linkpath,
+                               uint64_t tag);
+
+/**
+ * Same as [`lio_linkat`], but the completion is queued for [`lio_poll`] under `tag`.
+ */
+LioHandle lio_linkat_tagged(int old_dir_fd,
+                            const char *old_path,
+                            int new_dir_fd,
+                            const char *new_path,
+                            uint64_t tag);
+
+/**
+ * Same as [`lio_renameat`], but the completion is queued for [`lio_poll`] under `tag`.
+ */
+LioHandle lio_renameat_tagged(int old_dir_fd,
+                              const char *old_path,
+                              int new_dir_fd,
+                              const char *new_path,
+                              uint64_t tag);
+
+/**
+ * Same as [`lio_unlinkat`], but the completion is queued for [`lio_poll`] under `tag`.
+ */
+LioHandle lio_unlinkat_tagged(int dir_fd,
+                              const char *pathname,
+                              uint64_t tag);
+
+/**
+ * Same as [`lio_fsync`], but the completion is queued for [`lio_poll`] under `tag`.
+ */
+LioHandle lio_fsync_tagged(int fd,
+                           uint64_t tag);
+
+/**
+ * Same as [`lio_fdatasync`], but the completion is queued for [`lio_poll`] under `tag`.
+ */
+LioHandle lio_fdatasync_tagged(int fd,
+                               uint64_t tag);
+
+#if defined(__linux__)
+/**
+ * Same as [`lio_sync_file_range`], but the completion is queued for [`lio_poll`] under `tag`.
+ */
+LioHandle lio_sync_file_range_tagged(int fd,
+                                     uint64_t offset,
+                                     uint32_t nbytes,
+                                     uint32_t flags,
+                                     uint64_t tag);
+#endif
+
+/**
+ * Same as [`lio_write`], but the completion is queued for [`lio_poll`] under `tag`.
+ */
+LioHandle lio_write_tagged(int fd,
+                           uint8_t *buf,
+                           uintptr_t buf_len,
+                           int64_t offset,
+                           uint64_t tag);
+
+/**
+ * Same as [`lio_read`], but the completion is queued for [`lio_poll`] under `tag`.
+ */
+LioHandle lio_read_tagged(int fd,
+                          uint8_t *buf,
+                          uintptr_t buf_len,
+                          int64_t offset,
+                          uint64_t tag);
+
+/**
+ * Same as [`lio_truncate`], but the completion is queued for [`lio_poll`] under `tag`.
+ */
+LioHandle lio_truncate_tagged(int fd,
+                              uint64_t len,
+                              uint64_t tag);
+
+/**
+ * Same as [`lio_socket`], but the completion is queued for [`lio_poll`] under `tag`.
+ */
+LioHandle lio_socket_tagged(int32_t domain,
+                            int32_t ty,
+                            int32_t proto,
+                            uint64_t tag);
+
+/**
+ * Same as [`lio_bind`], but the completion is queued for [`lio_poll`] under `tag`.
+ */
+LioHandle lio_bind_tagged(int fd,
+                          const struct sockaddr *sock,
+                          const socklen_t *sock_len,
+                          uint64_t tag);
+
+/**
+ * Same as [`lio_accept`], but the completion is queued for [`lio_poll`] under `tag`.
+ */
+LioHandle lio_accept_tagged(int fd,
+                            uint64_t tag);
+
+/**
+ * Same as [`lio_listen`], but the completion is queued for [`lio_poll`] under `tag`.
+ */
+LioHandle lio_listen_tagged(int fd,
+                            int32_t backlog,
+                            uint64_t tag);
+
+/**
+ * Same as [`lio_connect`], but the completion is queued for [`lio_poll`] under `tag`.
+ */
+LioHandle lio_connect_tagged(int fd,
+                             const struct sockaddr *sock,
+                             socklen_t sock_len,
+                             uint64_t tag);
+
+/**
+ * Same as [`lio_send`], but the completion is queued for [`lio_poll`] under `tag`.
+ */
+LioHandle lio_send_tagged(int fd,
+                          uint8_t *buf,
+                          uintptr_t buf_len,
+                          int32_t flags,
+                          uint64_t tag);
+
+/**
+ * Same as [`lio_recv`], but the completion is queued for [`lio_poll`] under `tag`.
+ */
+LioHandle lio_recv_tagged(int fd,
+                          uint8_t *buf,
+                          uintptr_t buf_len,
+                          int32_t flags,
+                          uint64_t tag);
+
+/**
+ * Same as [`lio_write_all`], but the completion is queued for [`lio_poll`] under `tag`.
+ */
+LioHandle lio_write_all_tagged(int fd,
+                               uint8_t *buf,
+                               uintptr_t buf_len,
+                               int64_t offset,
+                               uint64_t tag);
+
+/**
+ * Same as [`lio_read_exact`], but the completion is queued for [`lio_poll`] under `tag`.
+ */
+LioHandle lio_read_exact_tagged(int fd,
+                                uint8_t *buf,
+                                uintptr_t buf_len,
+                                int64_t offset,
+                                uint64_t tag);
+
+/**
+ * Same as [`lio_send_all`], but the completion is queued for [`lio_poll`] under `tag`.
+ */
+LioHandle lio_send_all_tagged(int fd,
+                              uint8_t *buf,
+                              uintptr_t buf_len,
+                              uint64_t tag);
+
+/**
+ * Same as [`lio_recv_exact`], but the completion is queued for [`lio_poll`] under `tag`.
+ */
+LioHandle lio_recv_exact_tagged(int fd,
+                                uint8_t *buf,
+                                uintptr_t buf_len,
+                                uint64_t tag);
+
+/**
+ * Same as [`lio_close`], but the completion is queued for [`lio_poll`] under `tag`.
+ */
+LioHandle lio_close_tagged(int fd,
+                           uint64_t tag);
+
+/**
+ * Same as [`lio_openat`], but the completion is queued for [`lio_poll`] under `tag`.
+ */
+LioHandle lio_openat_tagged(int dir_fd,
+                            const char *pathname,
+                            int32_t flags,
+                            mode_t mode,
+                            uint64_t tag);
+
+#if defined(__linux__)
+/**
+ * Same as [`lio_openat2`], but the completion is queued for [`lio_poll`] under `tag`.
+ */
+LioHandle lio_openat2_tagged(int dir_fd,
+                             const char *pathname,
+                             int32_t flags,
+                             mode_t mode,
+                             uint64_t resolve,
+                             uint64_t tag);
+#endif
+
+#if defined(__linux__)
+/**
+ * Same as [`lio_tee`], but the completion is queued for [`lio_poll`] under `tag`.
+ */
+LioHandle lio_tee_tagged(int fd_in,
+                         int fd_out,
+                         uint32_t size,
+                         uint64_t tag);
+#endif
+
+#if defined(__linux__)
+/**
+ * Same as [`lio_timeout`], but the completion is queued for [`lio_poll`] under `tag`.
+ */
+LioHandle lio_timeout_tagged(uint64_t duration_ms,
+                             uint64_t tag);
+#endif
+
+#if defined(__linux__)
+/**
+ * Same as [`lio_futex_wait`], but the completion is queued for [`lio_poll`] under `tag`.
+ */
+LioHandle lio_futex_wait_tagged(const uint32_t *futex,
+                                uint32_t expected,
+                                uint64_t tag);
+#endif
+
+#if defined(__linux__)
+/**
+ * Same as [`lio_futex_wake`], but the completion is queued for [`lio_poll`] under `tag`.
+ */
+LioHandle lio_futex_wake_tagged(const uint32_t *futex,
+                                uint32_t count,
+                                uint64_t tag);
+#endif
+
+/**
+ * Same as [`lio_waitid`], but the completion is queued for [`lio_poll`] under `tag`.
+ */
+LioHandle lio_waitid_tagged(uint32_t pid,
+                            uint64_t tag);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
@@ -0,0 +1,309 @@
+//! Socket addresses of every family lio supports, see [`SocketAddress`].
+
+use std::{
+  ffi::OsStr,
+  fmt,
+  hash::{Hash, Hasher},
+  io, mem,
+  net::{SocketAddr, SocketAddrV4, SocketAddrV6},
+  os::unix::ffi::OsStrExt,
+  path::Path,
+  ptr,
+};
+
+use crate::op::net_utils::{
+  libc_socketaddr_into_std, std_socketaddr_into_libc,
+};
+
+/// Address of an IPv4, IPv6 or Unix domain socket, taken by
+/// [`bind`](crate::bind) and [`connect`](crate::connect) and returned by
+/// [`accept`](crate::accept).
+///
+/// # Examples
+///
+/// ```rust
+/// use lio::{SocketAddress, UnixAddress};
+///
+/// let inet: SocketAddress = "127.0.0.1:8080".parse::<std::net::SocketAddr>().unwrap().into();
+/// assert!(inet.as_inet().is_some());
+///
+/// let unix: SocketAddress = UnixAddress::from_pathname("/run/app.sock").unwrap().into();
+/// assert_eq!(unix.as_unix().unwrap().as_pathname(), Some("/run/app.sock".as_ref()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SocketAddress {
+  /// `AF_INET` or `AF_INET6`.
+  Inet(SocketAddr),
+  /// `AF_UNIX`.
+  Unix(UnixAddress),
+}
+
+impl SocketAddress {
+  /// The address family, `AF_INET`, `AF_INET6` or `AF_UNIX`.
+  pub fn family(&self) -> libc::c_int {
+    match self {
+      SocketAddress::Inet(SocketAddr::V4(_)) => libc::AF_INET,
+      SocketAddress::Inet(SocketAddr::V6(_)) => libc::AF_INET6,
+      SocketAddress::Unix(_) => libc::AF_UNIX,
+    }
+  }
+
+  pub fn as_inet(&self) -> Option<SocketAddr> {
+    match self {
+      SocketAddress::Inet(addr) => Some(*addr),
+      SocketAddress::Unix(_) => None,
+    }
+  }
+
+  pub fn as_unix(&self) -> Option<&UnixAddress> {
+    match self {
+      SocketAddress::Inet(_) => None,
+      SocketAddress::Unix(addr) => Some(addr),
+    }
+  }
+
+  /// The address as passed to `bind(2)` or `connect(2)`.
+  pub(crate) fn to_libc(&self) -> (libc::sockaddr_storage, libc::socklen_t) {
+    match self {
+      SocketAddress::Inet(addr) => {
+        let len = match addr {
+          SocketAddr::V4(_) => mem::size_of::<libc::sockaddr_in>(),
+          SocketAddr::V6(_) => mem::size_of::<libc::sockaddr_in6>(),
+        };
+        (std_socketaddr_into_libc(*addr), len as libc::socklen_t)
+      }
+      SocketAddress::Unix(addr) => {
+        let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+        // SAFETY: sockaddr_storage is large enough for any address.
+        unsafe {
+          ptr::copy_nonoverlapping(
+            &addr.addr,
+            (&mut storage as *mut libc::sockaddr_storage).cast(),
+            1,
+          )
+        };
+        (storage, addr.len)
+      }
+    }
+  }
+
+  /// Reads an address returned by the kernel, for example from `accept(2)`,
+  /// where `len` is the length the kernel reported.
+  pub(crate) fn from_libc(
+    storage: &libc::sockaddr_storage,
+    len: libc::socklen_t,
+  ) -> io::Result<SocketAddress> {
+    match storage.ss_family as libc::c_int {
+      libc::AF_UNIX => {
+        let mut addr = UnixAddress::unnamed();
+        let len = (len as usize).min(mem::size_of::<libc::sockaddr_un>());
+        // SAFETY: Both are valid for `len` bytes.
+        unsafe {
+          ptr::copy_nonoverlapping(
+            (storage as *const libc::sockaddr_storage).cast::<u8>(),
+            (&mut addr.addr as *mut libc::sockaddr_un).cast::<u8>(),
+            len,
+          )
+        };
+        addr.len = len.max(SUN_PATH_OFFSET) as libc::socklen_t;
+        Ok(SocketAddress::Unix(addr))
+      }
+      _ => libc_socketaddr_into_std(storage).map(SocketAddress::Inet),
+    }
+  }
+
+  /// Reads an address passed in by a caller, `len` bytes at `addr`.
+  ///
+  /// # Safety
+  ///
+  /// `addr` must be valid for reading `len` bytes.
+  #[cfg(feature = "unstable_ffi")]
+  pub(crate) unsafe fn from_raw(
+    addr: *const libc::sockaddr,
+    len: libc::socklen_t,
+  ) -> io::Result<SocketAddress> {
+    let einval = || io::Error::from_raw_os_error(libc::EINVAL);
+    if addr.is_null()
+      || (len as usize) < mem::size_of::<libc::sa_family_t>()
+      || len as usize > mem::size_of::<libc::sockaddr_storage>()
+    {
+      return Err(einval());
+    }
+
+    let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+    // SAFETY: Upheld by the caller, and `len` fits in `storage`.
+    unsafe {
+      ptr::copy_nonoverlapping(
+        addr.cast::<u8>(),
+        (&mut storage as *mut libc::sockaddr_storage).cast::<u8>(),
+        len as usize,
+      )
+    };
+
+    let min_len = match storage.ss_family as libc::c_int {
+      libc::AF_INET => mem::size_of::<libc::sockaddr_in>(),
+      libc::AF_INET6 => mem::size_of::<libc::sockaddr_in6>(),
+      _ => 0,
+    };
+    if (len as usize) < min_len {
+      return Err(einval());
+    }
+    SocketAddress::from_libc(&storage, len)
+  }
+}
+
+impl From<SocketAddr> for SocketAddress {
+  fn from(addr: SocketAddr) -> Self {
+    SocketAddress::Inet(addr)
+  }
+}
+
+impl From<SocketAddrV4> for SocketAddress {
+  fn from(addr: SocketAddrV4) -> Self {
+    SocketAddress::Inet(addr.into())
+  }
+}
+
+impl From<SocketAddrV6> for SocketAddress {
+  fn from(addr: SocketAddrV6) -> Self {
+    SocketAddress::Inet(addr.into())
+  }
+}
+
+impl From<UnixAddress> for SocketAddress {
+  fn from(addr: UnixAddress) -> Self {
+    SocketAddress::Unix(addr)
+  }
+}
+
+const SUN_PATH_OFFSET: usize = mem::offset_of!(libc::sockaddr_un, sun_path);
+
+/// Address of a Unix domain socket: a path in the filesystem, a name in the
+/// abstract namespace (Linux only), or unnamed, like the peer of a client
+/// that never called `bind`.
+#[derive(Clone)]
+pub struct UnixAddress {
+  addr: libc::sockaddr_un,
+  len: libc::socklen_t,
+}
+
+impl UnixAddress {
+  /// An address bound to `path`. Fails with `InvalidInput` if `path` contains
+  /// a NUL byte or doesn't fit in `sun_path`.
+  pub fn from_pathname(path: impl AsRef<Path>) -> io::Result<UnixAddress> {
+    let bytes = path.as_ref().as_os_str().as_bytes();
+    if bytes.contains(&0) {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "unix socket path contains a NUL byte",
+      ));
+    }
+    // Room for the terminating NUL.
+    UnixAddress::from_sun_path(bytes, 1)
+  }
+
+  /// An address in the abstract namespace, which isn't visible in the
+  /// filesystem and disappears with the last socket bound to it.
+  #[cfg(any(target_os = "linux", target_os = "android"))]
+  #[cfg_attr(docsrs, doc(cfg(any(target_os = "linux", target_os = "android"))))]
+  pub fn from_abstract_name(name: impl AsRef<[u8]>) -> io::Result<UnixAddress> {
+    let name = name.as_ref();
+    let mut bytes = Vec::with_capacity(name.len() + 1);
+    bytes.push(0);
+    bytes.extend_from_slice(name);
+    UnixAddress::from_sun_path(&bytes, 0)
+  }
+
+  /// An address without a name.
+  pub fn unnamed() -> UnixAddress {
+    let mut addr: libc::sockaddr_un = unsafe { mem::zeroed() };
+    addr.sun_family = libc::AF_UNIX as libc::sa_family_t;
+    UnixAddress { addr, len: SUN_PATH_OFFSET as libc::socklen_t }
+  }
+
+  fn from_sun_path(bytes: &[u8], nul: usize) -> io::Result<UnixAddress> {
+    let mut this = UnixAddress::unnamed();
+    if bytes.len() + nul > this.addr.sun_path.len() {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "unix socket path is too long",
+      ));
+    }
+    for (dst, src) in this.addr.sun_path.iter_mut().zip(bytes) {
+      *dst = *src as libc::c_char;
+    }
+    let len = SUN_PATH_OFFSET + bytes.len() + nul;
+    #[cfg(any(
+      target_os = "macos",
+      target_os = "ios",
+      target_os = "freebsd",
+      target_os = "openbsd",
+      target_os = "netbsd",
+      target_os = "dragonfly"
+    ))]
+    {
+      this.addr.sun_len = len as u8;
+    }
+    this.len = len as libc::socklen_t;
+    Ok(this)
+  }
+
+  /// The used part of `sun_path`, without the terminating NUL of paths.
+  fn sun_path(&self) -> &[u8] {
+    let len = self.len as usize - SUN_PATH_OFFSET;
+    // SAFETY: c_char and u8 have the same layout, and `len` is in bounds.
+    let bytes = unsafe {
+      std::slice::from_raw_parts(self.addr.sun_path.as_ptr().cast::<u8>(), len)
+    };
+    match bytes.first() {
+      Some(0) | None => bytes,
+      Some(_) => bytes.split(|&b| b == 0).next().unwrap_or(bytes),
+    }
+  }
+
+  pub fn as_pathname(&self) -> Option<&Path> {
+    match self.sun_path() {
+      [] | [0, ..] => None,
+      path => Some(Path::new(OsStr::from_bytes(path))),
+    }
+  }
+
+  #[cfg(any(target_os = "linux", target_os = "android"))]
+  #[cfg_attr(docsrs, doc(cfg(any(target_os = "linux", target_os = "android"))))]
+  pub fn as_abstract_name(&self) -> Option<&[u8]> {
+    match self.sun_path() {
+      [0, name @ ..] => Some(name),
+      _ => None,
+    }
+  }
+
+  pub fn is_unnamed(&self) -> bool {
+    self.sun_path().is_empty()
+  }
+}
+
+impl PartialEq for UnixAddress {
+  fn eq(&self, other: &Self) -> bool {
+    self.sun_path() == other.sun_path()
+  }
+}
+
+impl Eq for UnixAddress {}
+
+impl Hash for UnixAddress {
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    self.sun_path().hash(state)
+  }
+}
+
+impl fmt::Debug for UnixAddress {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self.sun_path() {
+      [] => write!(f, "(unnamed)"),
+      [0, name @ ..] => {
+        write!(f, "\"{}\" (abstract)", name.escape_ascii())
+      }
+      path => write!(f, "{:?} (pathname)", Path::new(OsStr::from_bytes(path))),
+    }
+  }
+}
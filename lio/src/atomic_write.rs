@@ -0,0 +1,118 @@
+use std::{
+  ffi::{CString, OsStr, OsString},
+  io,
+  os::{fd::RawFd, unix::ffi::OsStringExt},
+  path::Path,
+  sync::atomic::{AtomicU64, Ordering},
+};
+
+use crate::{Driver, OpenFlags, op::OpenAt};
+
+/// Atomically replaces the contents of the file at `path` with `buf`.
+///
+/// Readers of `path` either see the old contents or all of `buf`, never a mix,
+/// even if the process or machine crashes half-way. Built entirely from lio operations:
+///
+/// 1. Open a temporary file next to `path` (same directory, so same filesystem).
+/// 2. Write all of `buf` to it, resubmitting after short writes.
+/// 3. [`fdatasync`](crate::fdatasync) the temporary file, so the data is on disk before it's visible.
+/// 4. [`renameat`](crate::renameat) it over `path`.
+/// 5. [`fsync`](crate::fsync) the directory, so the rename itself is durable.
+///
+/// The file is created with mode `0o644` (before the process umask). If any step
+/// fails, the temporary file is removed and the error is returned.
+///
+/// # Examples
+///
+/// ```rust
+/// async fn atomic_write_example() -> std::io::Result<()> {
+///     lio::atomic_write("/tmp/config.toml", b"key = \"value\"\n".to_vec()).await?;
+///     Ok(())
+/// }
+/// ```
+pub async fn atomic_write(
+  path: impl AsRef<Path>,
+  buf: Vec<u8>,
+) -> io::Result<()> {
+  let path = path.as_ref();
+  let file_name = path.file_name().ok_or_else(|| {
+    io::Error::new(io::ErrorKind::InvalidInput, "path has no file name")
+  })?;
+  let dir = match path.parent() {
+    Some(dir) if !dir.as_os_str().is_empty() => dir,
+    _ => Path::new("."),
+  };
+
+  let dir_fd = Driver::submit(OpenAt::new(
+    libc::AT_FDCWD,
+    CString::new(dir.as_os_str().to_os_string().into_vec())?,
+    OpenFlags::RDONLY | OpenFlags::DIRECTORY | OpenFlags::CLOEXEC,
+  ))
+  .await?;
+
+  let tmp_name = temp_name(file_name.to_os_string());
+  let result = replace(dir_fd, &tmp_name, file_name, buf).await;
+
+  let close_result = crate::close(dir_fd).await;
+  result.and(close_result)
+}
+
+async fn replace(
+  dir_fd: RawFd,
+  tmp_name: &OsString,
+  file_name: &OsStr,
+  buf: Vec<u8>,
+) -> io::Result<()> {
+  let tmp_fd = Driver::submit(OpenAt::with_mode(
+    dir_fd,
+    CString::new(tmp_name.clone().into_vec())?,
+    OpenFlags::WRONLY | OpenFlags::CREAT | OpenFlags::EXCL | OpenFlags::CLOEXEC,
+    0o644,
+  ))
+  .await?;
+
+  let written = write_and_sync(tmp_fd, buf).await;
+  let closed = crate::close(tmp_fd).await;
+
+  let result = match written.and(closed) {
+    Ok(()) => crate::renameat(dir_fd, tmp_name, dir_fd, file_name)?.await,
+    Err(err) => Err(err),
+  };
+
+  if let Err(err) = result {
+    // Best effort, the original error is more interesting than this one.
+    let _ = crate::unlinkat(dir_fd, tmp_name)?.await;
+    return Err(err);
+  }
+
+  crate::fsync(dir_fd).await
+}
+
+async fn write_and_sync(fd: RawFd, mut buf: Vec<u8>) -> io::Result<()> {
+  let mut offset = 0i64;
+  while !buf.is_empty() {
+    let (res, mut rest) = crate::write(fd, buf, offset).await;
+    let written = res? as usize;
+    if written == 0 {
+      return Err(io::ErrorKind::WriteZero.into());
+    }
+    rest.drain(..written);
+    offset += written as i64;
+    buf = rest;
+  }
+
+  crate::fdatasync(fd).await
+}
+
+fn temp_name(mut file_name: OsString) -> OsString {
+  static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+  let mut name = OsString::from(".");
+  file_name.push(format!(
+    ".lio-tmp.{}.{}",
+    std::process::id(),
+    COUNTER.fetch_add(1, Ordering::Relaxed)
+  ));
+  name.push(file_name);
+  name
+}
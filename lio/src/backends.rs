@@ -1,16 +1,51 @@
-use crate::{OperationProgress, driver::OpStore, op::Operation};
+use crate::{OperationProgress, RingStats, driver::OpStore, op::Operation};
 
 #[cfg(linux)]
 mod io_uring;
 mod polling;
+#[cfg(feature = "sim")]
+mod sim;
 #[cfg(linux)]
 pub use io_uring::*;
 pub use polling::*;
+#[cfg(feature = "sim")]
+pub use sim::{CompletionOrder, SimState, Simulated};
+#[cfg(feature = "sim")]
+pub(crate) use sim::{bytes, fill};
+
+/// When operations are handed to the kernel, set with
+/// [`set_submit_policy`](crate::set_submit_policy).
+///
+/// Only io_uring submits in batches, other backends always submit immediately.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SubmitPolicy {
+  /// One `io_uring_enter` per operation.
+  #[default]
+  Immediate,
+  /// Operations are queued and submitted together on the next
+  /// [`tick`](crate::tick). The background thread only submits while it
+  /// isn't waiting for completions, so only use this when ticking manually.
+  OnTick,
+  /// Operations are queued until this many are waiting, and on each tick.
+  Threshold(usize),
+}
 
 pub trait IoBackend {
   fn tick(&self, store: &OpStore, can_wait: bool);
   fn submit<O>(&self, op: O, store: &OpStore) -> OperationProgress<O>
   where
     O: Operation + Sized;
+  /// Wakes up a [`IoBackend::tick`] waiting for completions.
   fn notify(&self);
+  /// Tries to cancel operation `id`. Cancelled operations complete with `ECANCELED`
+  /// through [`IoBackend::tick`], or immediately if possible.
+  fn cancel(&self, id: u64, store: &OpStore);
+  /// Hands operations deferred by the [`SubmitPolicy`] or a [`batch`](crate::batch)
+  /// to the kernel.
+  fn flush(&self) {}
+  fn set_submit_policy(&self, _policy: SubmitPolicy) {}
+  /// Submission and completion queue occupancy, if the backend has any.
+  fn ring_stats(&self) -> Option<RingStats> {
+    None
+  }
 }
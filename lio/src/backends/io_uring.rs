@@ -1,7 +1,16 @@
+use crate::{
+  OperationProgress, backends::IoBackend, driver::OpStore, op,
+  op_registration::ExtractedOpNotification,
+};
+use parking_lot::Mutex;
+
 pub struct IoUring {
   inner: io_uring::IoUring,
   probe: io_uring::Probe,
   submission_guard: Mutex<()>,
+  policy: Mutex<crate::SubmitPolicy>,
+  /// Entries pushed but not yet handed to the kernel.
+  unsubmitted: std::sync::atomic::AtomicUsize,
 }
 
 impl IoUring {
@@ -13,10 +22,61 @@ impl IoUring {
       (io_uring, probe)
     };
 
-    Self { inner: io_uring, probe, submission_guard: Mutex::new(()) }
+    Self {
+      inner: io_uring,
+      probe,
+      submission_guard: Mutex::new(()),
+      policy: Mutex::new(crate::SubmitPolicy::Immediate),
+      unsubmitted: std::sync::atomic::AtomicUsize::new(0),
+    }
+  }
+  /// Pushes `entry`, and submits it unless the [`SubmitPolicy`](crate::SubmitPolicy)
+  /// or a [`batch`](crate::batch) defers it.
+  fn push_entry(&self, entry: &io_uring::squeue::Entry) {
+    use std::sync::atomic::Ordering;
+
+    // SAFETY: because of references rules, a "fake" lock has to be implemented here, but because
+    // of it, this is safe.
+    let _g = self.submission_guard.lock();
+    unsafe {
+      let mut sub = self.inner.submission_shared();
+      if sub.push(entry).is_err() {
+        // Full of deferred entries, make room.
+        sub.sync();
+        drop(sub);
+        self.submit_unsubmitted();
+        sub = self.inner.submission_shared();
+        sub.push(entry).expect("submission queue full after submitting");
+      }
+      sub.sync();
+      drop(sub);
+    }
+    // Counted under the guard, so it can't go out of sync with the queue.
+    let queued = self.unsubmitted.fetch_add(1, Ordering::AcqRel) + 1;
+    drop(_g);
+    #[cfg(feature = "tracing")]
+    tracing::trace!(user_data = entry.get_user_data(), "sqe pushed");
+
+    let submit = !crate::driver::batching()
+      && match *self.policy.lock() {
+        crate::SubmitPolicy::Immediate => true,
+        crate::SubmitPolicy::OnTick => false,
+        crate::SubmitPolicy::Threshold(threshold) => queued >= threshold,
+      };
+    if submit {
+      self.submit_unsubmitted();
+    }
+  }
+  fn submit_unsubmitted(&self) {
+    let _count = self.unsubmitted.swap(0, std::sync::atomic::Ordering::AcqRel);
+    #[cfg(feature = "tracing")]
+    if _count > 1 {
+      tracing::trace!(count = _count, "submitting batch");
+    }
+    self.inner.submit().unwrap();
   }
   pub fn from_i32_to_io_result(res: i32) -> std::io::Result<i32> {
-    if res < 0 { Err(std::io::Error::from_raw_os_error(res)) } else { Ok(res) }
+    if res < 0 { Err(std::io::Error::from_raw_os_error(-res)) } else { Ok(res) }
   }
 }
 
@@ -25,40 +85,81 @@ impl IoBackend for IoUring {
   where
     T: op::Operation,
   {
-    if T::entry_supported(&self.probe) {
-      let operation_id = store.next_id();
-      let mut op = Box::new(op);
-      let entry = op.create_entry().user_data(operation_id);
-
-      // Insert the operation into wakers first
-      store.insert(operation_id, op);
-
-      // Then submit to io_uring
-      // SAFETY: because of references rules, a "fake" lock has to be implemented here, but because
-      // of it, this is safe.
-      let _g = self.submission_guard.lock();
-      unsafe {
-        let mut sub = self.inner.submission_shared();
-        // FIXME
-        sub.push(&entry).expect("unwrapping for now");
-        sub.sync();
-        drop(sub);
-      }
-      drop(_g);
+    let supported = T::entry_supported(&self.probe);
+    #[cfg(feature = "fault_injection")]
+    let supported = supported && !crate::fault::take_unsupported();
 
-      self.inner.submit().unwrap();
-      OperationProgress::<T>::new_uring(operation_id)
-    } else {
-      todo!();
-      // OperationProgress::<T>::new_blocking(op)
-    }
+    let mut op = Box::new(op);
+    let entry =
+      if supported { Some(op.create_entry()) } else { op.fallback_entry() };
+    let Some(entry) = entry else {
+      #[cfg(feature = "tracing")]
+      tracing::debug!("opcode not supported, falling back to blocking");
+      return OperationProgress::<T>::new_blocking(*op);
+    };
+
+    let operation_id = store.next_id();
+    let entry = entry.user_data(operation_id);
+
+    // Insert the operation into wakers first
+    store.insert(operation_id, op);
+
+    // Then submit to io_uring
+    self.push_entry(&entry);
+    OperationProgress::<T>::new_uring(operation_id)
   }
 
   fn notify(&self) {
-    todo!();
+    self.push_entry(
+      &io_uring::opcode::Nop::new()
+        .build()
+        .user_data(crate::driver::INTERNAL_ID),
+    );
+    self.flush();
+  }
+
+  fn flush(&self) {
+    if self.unsubmitted.load(std::sync::atomic::Ordering::Acquire) > 0 {
+      self.submit_unsubmitted();
+    }
+  }
+
+  fn set_submit_policy(&self, policy: crate::SubmitPolicy) {
+    *self.policy.lock() = policy;
+    self.flush();
+  }
+
+  fn ring_stats(&self) -> Option<crate::RingStats> {
+    let _g = self.submission_guard.lock();
+    // SAFETY: Only reads the queue heads and tails, which are atomics.
+    let (sq_len, sq_capacity) = unsafe {
+      let sub = self.inner.submission_shared();
+      (sub.len(), sub.capacity())
+    };
+    drop(_g);
+    let cq = unsafe { self.inner.completion_shared() };
+
+    Some(crate::RingStats {
+      sq_len,
+      sq_capacity,
+      cq_len: cq.len(),
+      cq_capacity: cq.capacity(),
+      cq_overflow: cq.overflow(),
+    })
+  }
+
+  fn cancel(&self, id: u64, _store: &OpStore) {
+    self.push_entry(
+      &io_uring::opcode::AsyncCancel::new(id)
+        .build()
+        .user_data(crate::driver::INTERNAL_ID),
+    );
+    self.flush();
   }
 
   fn tick(&self, store: &OpStore, can_wait: bool) {
+    // Also submits everything deferred.
+    self.unsubmitted.store(0, std::sync::atomic::Ordering::Release);
     self.inner.submit_and_wait(if can_wait { 1 } else { 0 }).unwrap();
 
     // SAFETY: lio guarrantees that only one tick impl is running at any time.
@@ -67,7 +168,7 @@ impl IoBackend for IoUring {
 
       let operation_id = io_entry.user_data();
 
-      // If the operation id is not registered (e.g., wake-up NOP), skip.
+      // If the operation id is not registered (e.g., wake-up NOP or cancellations), skip.
       let Some(set_done_result) = store.get_mut(operation_id, |entry| {
         entry.set_done(Self::from_i32_to_io_result(io_entry.result()))
       }) else {
@@ -81,8 +182,7 @@ impl IoBackend for IoUring {
           #[cfg(feature = "high")]
           ExtractedOpNotification::Waker(waker) => waker.wake(),
           ExtractedOpNotification::Callback(callback) => {
-            store.get_mut(operation_id, |entry| callback.call(entry));
-            assert!(store.remove(operation_id));
+            store.run_callback(operation_id, callback);
           }
         },
       }
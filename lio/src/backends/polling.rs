@@ -49,6 +49,29 @@ impl IoBackend for Polling {
   fn notify(&self) {
     self.inner.notify().unwrap();
   }
+  fn cancel(&self, id: u64, store: &OpStore) {
+    // Operations without an fd entry already completed.
+    let Some(fd) = self.fd_map.lock().remove(&id) else {
+      return;
+    };
+    let _ = self.delete_interest(fd);
+
+    let set_done_result = store
+      .get_mut(id, |reg| {
+        reg.set_done(Err(io::Error::from_raw_os_error(libc::ECANCELED)))
+      })
+      .expect("Cannot find matching operation");
+    match set_done_result {
+      None => {}
+      Some(value) => match value {
+        #[cfg(feature = "high")]
+        ExtractedOpNotification::Waker(waker) => waker.wake(),
+        ExtractedOpNotification::Callback(callback) => {
+          store.run_callback(id, callback);
+        }
+      },
+    }
+  }
   fn tick(&self, store: &OpStore, can_wait: bool) {
     let events =
       self.interest_wait(can_wait).expect("background thread failed");
@@ -97,8 +120,7 @@ impl IoBackend for Polling {
               #[cfg(feature = "high")]
               ExtractedOpNotification::Waker(waker) => waker.wake(),
               ExtractedOpNotification::Callback(callback) => {
-                store.get_mut(operation_id, |entry| callback.call(entry));
-                assert!(store.remove(operation_id));
+                store.run_callback(operation_id, callback);
               }
             },
           }
@@ -0,0 +1,886 @@
+use std::{
+  collections::{BTreeSet, HashMap, VecDeque},
+  ffi::CStr,
+  io,
+  net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+  os::{fd::RawFd, unix::ffi::OsStrExt},
+  path::{Component, Path, PathBuf},
+  task::Poll,
+  time::Duration,
+};
+
+use parking_lot::{Condvar, Mutex};
+
+use crate::{
+  IoBuf, IoBufMut, OperationProgress, backends::IoBackend, driver::OpStore,
+  op::Operation, op_registration::ExtractedOpNotification,
+};
+
+/// First port handed out when binding to port 0.
+const EPHEMERAL_PORT: u16 = 49152;
+
+/// Order in which operations that complete during the same [`tick`](crate::tick)
+/// are reported.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CompletionOrder {
+  /// The order they were submitted in.
+  #[default]
+  Submission,
+  /// The reverse of the order they were submitted in.
+  Reversed,
+  /// A pseudo-random order, which is the same for every run with the same seed.
+  Shuffled { seed: u64 },
+}
+
+/// Backend that runs operations against an in-memory filesystem and loopback
+/// network instead of the kernel, selected with [`init_simulated`](crate::init_simulated).
+///
+/// Nothing touches the real filesystem or network, and operations complete in a
+/// deterministic order, controlled with [`Simulated::completion_order`].
+///
+/// - Files live in memory. Only `/` and `/tmp` exist up front, more can be added
+///   with [`Simulated::with_dir`] and [`Simulated::with_file`].
+/// - TCP sockets can only reach listeners in the same simulation. Any address works.
+/// - Time is virtual: when no operation can make progress, the clock jumps to the
+///   next pending [`timeout`](crate::timeout).
+/// - Operations without a simulation, like `waitid` or `futex_wait`, fail with `ENOSYS`.
+///
+/// # Examples
+///
+/// ```rust
+/// use lio::{CompletionOrder, Simulated};
+///
+/// let sim = Simulated::new()
+///   .with_file("/etc/config", b"answer = 42".to_vec())
+///   .completion_order(CompletionOrder::Shuffled { seed: 7 });
+/// lio::init_simulated(sim);
+/// # lio::exit();
+/// ```
+pub struct Simulated {
+  state: Mutex<SimState>,
+  /// Operations that haven't completed yet, in submission order.
+  queue: Mutex<Vec<u64>>,
+  order: CompletionOrder,
+  rng: Mutex<u64>,
+  /// Set when a tick waiting for completions should look again.
+  woken: Mutex<bool>,
+  wakeup: Condvar,
+}
+
+impl Default for Simulated {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl Simulated {
+  pub fn new() -> Self {
+    Self {
+      state: Mutex::new(SimState::new()),
+      queue: Mutex::new(Vec::new()),
+      order: CompletionOrder::Submission,
+      rng: Mutex::new(0),
+      woken: Mutex::new(false),
+      wakeup: Condvar::new(),
+    }
+  }
+
+  pub fn completion_order(mut self, order: CompletionOrder) -> Self {
+    if let CompletionOrder::Shuffled { seed } = order {
+      // Xorshift gets stuck on zero.
+      *self.rng.get_mut() = seed | 1;
+    }
+    self.order = order;
+    self
+  }
+
+  /// Creates an empty directory, and any missing parents.
+  ///
+  /// # Panics
+  ///
+  /// If `path` isn't absolute.
+  pub fn with_dir(mut self, path: impl AsRef<Path>) -> Self {
+    let path = absolute(path.as_ref());
+    self.state.get_mut().add_dir(&path);
+    self
+  }
+
+  /// Creates a file, and any missing parent directories.
+  ///
+  /// # Panics
+  ///
+  /// If `path` isn't absolute.
+  pub fn with_file(
+    mut self,
+    path: impl AsRef<Path>,
+    contents: Vec<u8>,
+  ) -> Self {
+    let path = absolute(path.as_ref());
+    let state = self.state.get_mut();
+    if let Some(parent) = path.parent() {
+      state.add_dir(parent);
+    }
+    state.inodes.push(contents);
+    state.files.insert(path, state.inodes.len() - 1);
+    self
+  }
+
+  fn wake(&self) {
+    *self.woken.lock() = true;
+    self.wakeup.notify_one();
+  }
+
+  fn next_random(&self) -> u64 {
+    let mut rng = self.rng.lock();
+    *rng ^= *rng << 13;
+    *rng ^= *rng >> 7;
+    *rng ^= *rng << 17;
+    *rng
+  }
+
+  /// Runs queued operations until none can make progress, and returns the ones
+  /// that completed.
+  fn run_queue(&self, store: &OpStore) -> Vec<(u64, io::Result<i32>)> {
+    let mut state = self.state.lock();
+    let mut done = Vec::new();
+
+    loop {
+      let queued = self.queue.lock().clone();
+      let mut progressed = false;
+
+      for id in queued {
+        let outcome = store.get_mut(id, |reg| reg.simulate(&mut state));
+        let res = match outcome {
+          Some(Poll::Pending) => continue,
+          Some(Poll::Ready(res)) => res,
+          // Already completed, for example by Simulated::cancel.
+          None => {
+            self.queue.lock().retain(|queued| *queued != id);
+            continue;
+          }
+        };
+        self.queue.lock().retain(|queued| *queued != id);
+        done.push((id, res));
+        progressed = true;
+      }
+
+      if progressed {
+        continue;
+      }
+      // Everything is waiting, so let virtual time pass.
+      if !done.is_empty() || !state.advance_clock() {
+        break;
+      }
+    }
+
+    done
+  }
+
+  fn complete(store: &OpStore, id: u64, res: io::Result<i32>) {
+    let Some(set_done_result) = store.get_mut(id, |reg| reg.set_done(res))
+    else {
+      return;
+    };
+
+    match set_done_result {
+      None => {}
+      Some(value) => match value {
+        #[cfg(feature = "high")]
+        ExtractedOpNotification::Waker(waker) => waker.wake(),
+        ExtractedOpNotification::Callback(callback) => {
+          store.run_callback(id, callback);
+        }
+      },
+    }
+  }
+}
+
+impl IoBackend for Simulated {
+  fn submit<O>(&self, op: O, store: &OpStore) -> OperationProgress<O>
+  where
+    O: Operation + Sized,
+  {
+    let id = store.next_id();
+    store.insert(id, Box::new(op));
+    self.queue.lock().push(id);
+    self.wake();
+    OperationProgress::new_simulated(id)
+  }
+
+  fn notify(&self) {
+    self.wake();
+  }
+
+  fn cancel(&self, id: u64, store: &OpStore) {
+    let mut queue = self.queue.lock();
+    let Some(index) = queue.iter().position(|queued| *queued == id) else {
+      return;
+    };
+    queue.remove(index);
+    drop(queue);
+
+    Self::complete(
+      store,
+      id,
+      Err(io::Error::from_raw_os_error(libc::ECANCELED)),
+    );
+  }
+
+  fn tick(&self, store: &OpStore, can_wait: bool) {
+    let mut done = self.run_queue(store);
+
+    while can_wait && done.is_empty() {
+      let mut woken = self.woken.lock();
+      if !*woken {
+        self.wakeup.wait(&mut woken);
+      }
+      *woken = false;
+      drop(woken);
+      done = self.run_queue(store);
+    }
+
+    match self.order {
+      CompletionOrder::Submission => {}
+      CompletionOrder::Reversed => done.reverse(),
+      CompletionOrder::Shuffled { .. } => {
+        for i in (1..done.len()).rev() {
+          let j = (self.next_random() % (i as u64 + 1)) as usize;
+          done.swap(i, j);
+        }
+      }
+    }
+
+    for (id, res) in done {
+      Self::complete(store, id, res);
+    }
+  }
+}
+
+fn absolute(path: &Path) -> PathBuf {
+  assert!(path.is_absolute(), "simulated paths must be absolute: {path:?}");
+  normalize(Path::new("/"), path)
+}
+
+/// Joins `path` onto `base`, resolving `.` and `..` lexically.
+fn normalize(base: &Path, path: &Path) -> PathBuf {
+  let mut out = base.to_path_buf();
+  for component in path.components() {
+    match component {
+      Component::RootDir => out = PathBuf::from("/"),
+      Component::ParentDir => {
+        out.pop();
+      }
+      Component::Normal(name) => out.push(name),
+      Component::CurDir | Component::Prefix(_) => {}
+    }
+  }
+  out
+}
+
+fn errno(code: i32) -> io::Error {
+  io::Error::from_raw_os_error(code)
+}
+
+/// Copies `data` into the spare capacity of `buf`, after its initialized
+/// bytes, and returns how much was copied.
+pub(crate) fn fill(buf: &mut impl IoBufMut, data: &[u8]) -> i32 {
+  let init = buf.bytes_init();
+  let len = data.len().min(buf.bytes_total() - init);
+  // SAFETY: `buf` has room for `bytes_total` bytes.
+  unsafe {
+    let spare = buf.stable_mut_ptr().add(init);
+    std::ptr::copy_nonoverlapping(data.as_ptr(), spare, len)
+  };
+  len as i32
+}
+
+/// Initialized bytes of `buf`.
+pub(crate) fn bytes(buf: &impl IoBuf) -> &[u8] {
+  // SAFETY: The first `bytes_init` bytes are initialized.
+  unsafe { std::slice::from_raw_parts(buf.stable_ptr(), buf.bytes_init()) }
+}
+
+/// State of the simulated filesystem and network, which operations act on.
+pub struct SimState {
+  /// File contents, indexed by inode number. Never shrinks.
+  inodes: Vec<Vec<u8>>,
+  files: HashMap<PathBuf, usize>,
+  dirs: BTreeSet<PathBuf>,
+  fds: HashMap<RawFd, Entry>,
+  /// File descriptors are never reused.
+  next_fd: RawFd,
+  next_port: u16,
+  now: Duration,
+  timers: BTreeSet<Duration>,
+}
+
+enum Entry {
+  File { inode: usize, pos: u64, readable: bool, writable: bool, append: bool },
+  Dir(PathBuf),
+  Socket(Socket),
+}
+
+struct Socket {
+  local: Option<SocketAddr>,
+  state: SocketState,
+}
+
+enum SocketState {
+  Idle,
+  /// Accepted connections waiting for `accept`, with the peer's address.
+  Listening(VecDeque<(RawFd, SocketAddr)>),
+  Connected(Stream),
+}
+
+struct Stream {
+  peer: RawFd,
+  rx: VecDeque<u8>,
+  /// The peer shut down writing.
+  eof: bool,
+  read_shut: bool,
+  write_shut: bool,
+}
+
+impl SimState {
+  fn new() -> Self {
+    let mut state = Self {
+      inodes: Vec::new(),
+      files: HashMap::new(),
+      dirs: BTreeSet::new(),
+      fds: HashMap::new(),
+      next_fd: 3,
+      next_port: EPHEMERAL_PORT,
+      now: Duration::ZERO,
+      timers: BTreeSet::new(),
+    };
+    state.add_dir(Path::new("/tmp"));
+    state
+  }
+
+  fn add_dir(&mut self, path: &Path) {
+    for dir in path.ancestors() {
+      self.dirs.insert(dir.to_path_buf());
+    }
+  }
+
+  fn new_fd(&mut self, entry: Entry) -> RawFd {
+    let fd = self.next_fd;
+    self.next_fd += 1;
+    self.fds.insert(fd, entry);
+    fd
+  }
+
+  fn resolve(&self, dir_fd: RawFd, path: &CStr) -> io::Result<PathBuf> {
+    let path = Path::new(std::ffi::OsStr::from_bytes(path.to_bytes()));
+    if path.as_os_str().is_empty() {
+      return Err(errno(libc::ENOENT));
+    }
+    if path.is_absolute() {
+      return Ok(normalize(Path::new("/"), path));
+    }
+
+    let base = if dir_fd == libc::AT_FDCWD {
+      PathBuf::from("/")
+    } else {
+      match self.fds.get(&dir_fd) {
+        Some(Entry::Dir(dir)) => dir.clone(),
+        Some(_) => return Err(errno(libc::ENOTDIR)),
+        None => return Err(errno(libc::EBADF)),
+      }
+    };
+    Ok(normalize(&base, path))
+  }
+
+  fn parent_exists(&self, path: &Path) -> io::Result<()> {
+    match path.parent() {
+      Some(parent) if !self.dirs.contains(parent) => Err(errno(libc::ENOENT)),
+      _ => Ok(()),
+    }
+  }
+
+  pub(crate) fn now(&self) -> Duration {
+    self.now
+  }
+
+  /// Completes with `ETIME` once the virtual clock reaches `deadline`, like io_uring timeouts.
+  pub(crate) fn sleep_until(
+    &mut self,
+    deadline: Duration,
+  ) -> Poll<io::Result<i32>> {
+    if self.now >= deadline {
+      Poll::Ready(Err(errno(libc::ETIME)))
+    } else {
+      self.timers.insert(deadline);
+      Poll::Pending
+    }
+  }
+
+  /// Moves the clock to the earliest timer. Returns false if there are none.
+  fn advance_clock(&mut self) -> bool {
+    match self.timers.pop_first() {
+      Some(deadline) => {
+        self.now = self.now.max(deadline);
+        true
+      }
+      None => false,
+    }
+  }
+
+  pub(crate) fn open(
+    &mut self,
+    dir_fd: RawFd,
+    path: &CStr,
+    flags: i32,
+  ) -> io::Result<i32> {
+    let path = self.resolve(dir_fd, path)?;
+    let access = flags & libc::O_ACCMODE;
+    let readable = access != libc::O_WRONLY;
+    let writable = access != libc::O_RDONLY;
+
+    if self.dirs.contains(&path) {
+      if writable {
+        return Err(errno(libc::EISDIR));
+      }
+      return Ok(self.new_fd(Entry::Dir(path)));
+    }
+    if flags & libc::O_DIRECTORY != 0 {
+      let code = if self.files.contains_key(&path) {
+        libc::ENOTDIR
+      } else {
+        libc::ENOENT
+      };
+      return Err(errno(code));
+    }
+
+    let inode = match self.files.get(&path) {
+      Some(_) if flags & libc::O_CREAT != 0 && flags & libc::O_EXCL != 0 => {
+        return Err(errno(libc::EEXIST));
+      }
+      Some(&inode) => {
+        if flags & libc::O_TRUNC != 0 && writable {
+          self.inodes[inode].clear();
+        }
+        inode
+      }
+      None if flags & libc::O_CREAT == 0 => return Err(errno(libc::ENOENT)),
+      None => {
+        self.parent_exists(&path)?;
+        self.inodes.push(Vec::new());
+        self.files.insert(path, self.inodes.len() - 1);
+        self.inodes.len() - 1
+      }
+    };
+
+    let append = flags & libc::O_APPEND != 0;
+    Ok(self.new_fd(Entry::File { inode, pos: 0, readable, writable, append }))
+  }
+
+  pub(crate) fn close(&mut self, fd: RawFd) -> io::Result<i32> {
+    match self.fds.remove(&fd).ok_or(errno(libc::EBADF))? {
+      Entry::Socket(Socket {
+        state: SocketState::Connected(stream), ..
+      }) => {
+        if let Some(peer) = self.stream_mut(stream.peer) {
+          peer.eof = true;
+        }
+      }
+      Entry::Socket(Socket {
+        state: SocketState::Listening(backlog), ..
+      }) => {
+        for (accepted, _) in backlog {
+          let _ = self.close(accepted);
+        }
+      }
+      _ => {}
+    }
+    Ok(0)
+  }
+
+  /// Reads up to `len` bytes. A negative offset uses and advances the file position.
+  pub(crate) fn read(
+    &mut self,
+    fd: RawFd,
+    offset: i64,
+    len: usize,
+  ) -> Poll<io::Result<Vec<u8>>> {
+    let (inode, pos) = match self.fds.get_mut(&fd) {
+      None => return Poll::Ready(Err(errno(libc::EBADF))),
+      Some(Entry::Dir(_)) => return Poll::Ready(Err(errno(libc::EISDIR))),
+      Some(Entry::Socket(_)) => return self.recv(fd, len),
+      Some(Entry::File { readable: false, .. }) => {
+        return Poll::Ready(Err(errno(libc::EBADF)));
+      }
+      Some(Entry::File { inode, pos, .. }) => (*inode, pos),
+    };
+
+    let start = if offset < 0 { *pos } else { offset as u64 };
+    let data = &self.inodes[inode];
+    let start = (start as usize).min(data.len());
+    let end = start.saturating_add(len).min(data.len());
+    if offset < 0 {
+      *pos = end as u64;
+    }
+    Poll::Ready(Ok(data[start..end].to_vec()))
+  }
+
+  /// Writes `data`. A negative offset uses and advances the file position.
+  pub(crate) fn write(
+    &mut self,
+    fd: RawFd,
+    offset: i64,
+    data: &[u8],
+  ) -> Poll<io::Result<i32>> {
+    let (inode, pos, append) = match self.fds.get_mut(&fd) {
+      None => return Poll::Ready(Err(errno(libc::EBADF))),
+      Some(Entry::Dir(_)) => return Poll::Ready(Err(errno(libc::EISDIR))),
+      Some(Entry::Socket(_)) => return self.send(fd, data),
+      Some(Entry::File { writable: false, .. }) => {
+        return Poll::Ready(Err(errno(libc::EBADF)));
+      }
+      Some(Entry::File { inode, pos, append, .. }) => (*inode, pos, *append),
+    };
+
+    let file = &mut self.inodes[inode];
+    let start = match (append, offset) {
+      (true, _) => file.len(),
+      (false, offset) if offset < 0 => *pos as usize,
+      (false, offset) => offset as usize,
+    };
+    let end = start + data.len();
+    if file.len() < end {
+      file.resize(end, 0);
+    }
+    file[start..end].copy_from_slice(data);
+    if append || offset < 0 {
+      *pos = end as u64;
+    }
+    Poll::Ready(Ok(data.len() as i32))
+  }
+
+  /// Checks that `fd` is open, for operations like `fsync` that have nothing to do.
+  pub(crate) fn check_fd(&self, fd: RawFd) -> io::Result<i32> {
+    match self.fds.contains_key(&fd) {
+      true => Ok(0),
+      false => Err(errno(libc::EBADF)),
+    }
+  }
+
+  pub(crate) fn truncate(&mut self, fd: RawFd, size: u64) -> io::Result<i32> {
+    match self.fds.get(&fd) {
+      None => Err(errno(libc::EBADF)),
+      Some(Entry::File { inode, writable: true, .. }) => {
+        self.inodes[*inode].resize(size as usize, 0);
+        Ok(0)
+      }
+      Some(_) => Err(errno(libc::EINVAL)),
+    }
+  }
+
+  pub(crate) fn unlink(
+    &mut self,
+    dir_fd: RawFd,
+    path: &CStr,
+  ) -> io::Result<i32> {
+    let path = self.resolve(dir_fd, path)?;
+    if self.files.remove(&path).is_some() {
+      Ok(0)
+    } else if self.dirs.contains(&path) {
+      Err(errno(libc::EISDIR))
+    } else {
+      Err(errno(libc::ENOENT))
+    }
+  }
+
+  pub(crate) fn rename(
+    &mut self,
+    old_dir_fd: RawFd,
+    old_path: &CStr,
+    new_dir_fd: RawFd,
+    new_path: &CStr,
+  ) -> io::Result<i32> {
+    let old_path = self.resolve(old_dir_fd, old_path)?;
+    let new_path = self.resolve(new_dir_fd, new_path)?;
+    self.parent_exists(&new_path)?;
+    if self.dirs.contains(&new_path) {
+      return Err(errno(libc::EISDIR));
+    }
+
+    let inode = self.files.remove(&old_path).ok_or(errno(libc::ENOENT))?;
+    self.files.insert(new_path, inode);
+    Ok(0)
+  }
+
+  pub(crate) fn link(
+    &mut self,
+    old_dir_fd: RawFd,
+    old_path: &CStr,
+    new_dir_fd: RawFd,
+    new_path: &CStr,
+  ) -> io::Result<i32> {
+    let old_path = self.resolve(old_dir_fd, old_path)?;
+    let new_path = self.resolve(new_dir_fd, new_path)?;
+    self.parent_exists(&new_path)?;
+    if self.files.contains_key(&new_path) || self.dirs.contains(&new_path) {
+      return Err(errno(libc::EEXIST));
+    }
+
+    let inode = *self.files.get(&old_path).ok_or(errno(libc::ENOENT))?;
+    self.files.insert(new_path, inode);
+    Ok(0)
+  }
+
+  pub(crate) fn socket(&mut self, domain: i32, ty: i32) -> io::Result<i32> {
+    if domain != libc::AF_INET && domain != libc::AF_INET6 {
+      return Err(errno(libc::EAFNOSUPPORT));
+    }
+    if ty & !(libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC) != libc::SOCK_STREAM {
+      return Err(errno(libc::EPROTONOSUPPORT));
+    }
+    Ok(
+      self.new_fd(Entry::Socket(Socket {
+        local: None,
+        state: SocketState::Idle,
+      })),
+    )
+  }
+
+  fn socket_mut(&mut self, fd: RawFd) -> io::Result<&mut Socket> {
+    match self.fds.get_mut(&fd) {
+      Some(Entry::Socket(socket)) => Ok(socket),
+      Some(_) => Err(errno(libc::ENOTSOCK)),
+      None => Err(errno(libc::EBADF)),
+    }
+  }
+
+  fn stream_mut(&mut self, fd: RawFd) -> Option<&mut Stream> {
+    match self.socket_mut(fd) {
+      Ok(Socket { state: SocketState::Connected(stream), .. }) => Some(stream),
+      _ => None,
+    }
+  }
+
+  fn addr_in_use(&self, addr: SocketAddr) -> bool {
+    self.fds.values().any(|entry| match entry {
+      Entry::Socket(Socket { local: Some(local), .. }) => {
+        local.port() == addr.port()
+          && (local.ip() == addr.ip()
+            || local.ip().is_unspecified()
+            || addr.ip().is_unspecified())
+      }
+      _ => false,
+    })
+  }
+
+  fn ephemeral(&mut self, ip: IpAddr) -> SocketAddr {
+    loop {
+      let addr = SocketAddr::new(ip, self.next_port);
+      self.next_port = self.next_port.checked_add(1).unwrap_or(EPHEMERAL_PORT);
+      if !self.addr_in_use(addr) {
+        return addr;
+      }
+    }
+  }
+
+  pub(crate) fn bind(
+    &mut self,
+    fd: RawFd,
+    addr: SocketAddr,
+  ) -> io::Result<i32> {
+    if self.socket_mut(fd)?.local.is_some() {
+      return Err(errno(libc::EINVAL));
+    }
+    let addr = match addr.port() {
+      0 => self.ephemeral(addr.ip()),
+      _ if self.addr_in_use(addr) => return Err(errno(libc::EADDRINUSE)),
+      _ => addr,
+    };
+    self.socket_mut(fd)?.local = Some(addr);
+    Ok(0)
+  }
+
+  pub(crate) fn listen(&mut self, fd: RawFd) -> io::Result<i32> {
+    if self.socket_mut(fd)?.local.is_none() {
+      let addr = self.ephemeral(Ipv4Addr::UNSPECIFIED.into());
+      self.socket_mut(fd)?.local = Some(addr);
+    }
+    let socket = self.socket_mut(fd)?;
+    match socket.state {
+      SocketState::Idle => {
+        socket.state = SocketState::Listening(VecDeque::new())
+      }
+      SocketState::Listening(_) => {}
+      SocketState::Connected(_) => return Err(errno(libc::EINVAL)),
+    }
+    Ok(0)
+  }
+
+  fn listener(&self, addr: SocketAddr) -> Option<(RawFd, SocketAddr)> {
+    self.fds.iter().find_map(|(fd, entry)| match entry {
+      Entry::Socket(Socket {
+        local: Some(local),
+        state: SocketState::Listening(_),
+      }) if local.port() == addr.port()
+        && (local.ip() == addr.ip() || local.ip().is_unspecified()) =>
+      {
+        Some((*fd, *local))
+      }
+      _ => None,
+    })
+  }
+
+  /// Connects straight away if something listens on `addr`.
+  pub(crate) fn connect(
+    &mut self,
+    fd: RawFd,
+    addr: SocketAddr,
+  ) -> io::Result<i32> {
+    let socket = self.socket_mut(fd)?;
+    match socket.state {
+      SocketState::Idle => {}
+      SocketState::Connected(_) => return Err(errno(libc::EISCONN)),
+      SocketState::Listening(_) => return Err(errno(libc::EINVAL)),
+    }
+    let local = socket.local;
+    let (listener, _) = self.listener(addr).ok_or(errno(libc::ECONNREFUSED))?;
+
+    let local = match local {
+      Some(local) => local,
+      None => {
+        let loopback = match addr {
+          SocketAddr::V4(_) => IpAddr::V4(Ipv4Addr::LOCALHOST),
+          SocketAddr::V6(_) => IpAddr::V6(Ipv6Addr::LOCALHOST),
+        };
+        self.ephemeral(loopback)
+      }
+    };
+
+    let accepted = self.new_fd(Entry::Socket(Socket {
+      local: Some(addr),
+      state: SocketState::Connected(Stream::new(fd)),
+    }));
+    let socket = self.socket_mut(fd)?;
+    socket.local = Some(local);
+    socket.state = SocketState::Connected(Stream::new(accepted));
+
+    match self.socket_mut(listener)?.state {
+      SocketState::Listening(ref mut backlog) => {
+        backlog.push_back((accepted, local))
+      }
+      _ => unreachable!("found by Self::listener"),
+    }
+    Ok(0)
+  }
+
+  pub(crate) fn accept(
+    &mut self,
+    fd: RawFd,
+  ) -> Poll<io::Result<(RawFd, SocketAddr)>> {
+    match self.socket_mut(fd) {
+      Err(err) => Poll::Ready(Err(err)),
+      Ok(Socket { state: SocketState::Listening(backlog), .. }) => {
+        match backlog.pop_front() {
+          Some(accepted) => Poll::Ready(Ok(accepted)),
+          None => Poll::Pending,
+        }
+      }
+      Ok(_) => Poll::Ready(Err(errno(libc::EINVAL))),
+    }
+  }
+
+  pub(crate) fn send(
+    &mut self,
+    fd: RawFd,
+    data: &[u8],
+  ) -> Poll<io::Result<i32>> {
+    let stream = match self.socket_mut(fd) {
+      Err(err) => return Poll::Ready(Err(err)),
+      Ok(Socket { state: SocketState::Connected(stream), .. }) => stream,
+      Ok(_) => return Poll::Ready(Err(errno(libc::ENOTCONN))),
+    };
+    if stream.write_shut {
+      return Poll::Ready(Err(errno(libc::EPIPE)));
+    }
+
+    let peer = stream.peer;
+    match self.stream_mut(peer) {
+      Some(peer) => {
+        if !peer.read_shut {
+          peer.rx.extend(data);
+        }
+        Poll::Ready(Ok(data.len() as i32))
+      }
+      None => Poll::Ready(Err(errno(libc::EPIPE))),
+    }
+  }
+
+  pub(crate) fn recv(
+    &mut self,
+    fd: RawFd,
+    len: usize,
+  ) -> Poll<io::Result<Vec<u8>>> {
+    let stream = match self.socket_mut(fd) {
+      Err(err) => return Poll::Ready(Err(err)),
+      Ok(Socket { state: SocketState::Connected(stream), .. }) => stream,
+      Ok(_) => return Poll::Ready(Err(errno(libc::ENOTCONN))),
+    };
+
+    if !stream.rx.is_empty() || len == 0 {
+      let len = len.min(stream.rx.len());
+      return Poll::Ready(Ok(stream.rx.drain(..len).collect()));
+    }
+    if stream.eof || stream.read_shut {
+      return Poll::Ready(Ok(Vec::new()));
+    }
+    Poll::Pending
+  }
+
+  /// Unbound sockets report `0.0.0.0:0`.
+  pub(crate) fn local_addr(&mut self, fd: RawFd) -> io::Result<SocketAddr> {
+    Ok(self.socket_mut(fd)?.local.unwrap_or((Ipv4Addr::UNSPECIFIED, 0).into()))
+  }
+
+  pub(crate) fn peer_addr(&mut self, fd: RawFd) -> io::Result<SocketAddr> {
+    let peer = match self.socket_mut(fd)? {
+      Socket { state: SocketState::Connected(stream), .. } => stream.peer,
+      _ => return Err(errno(libc::ENOTCONN)),
+    };
+    match self.socket_mut(peer) {
+      Ok(Socket { local: Some(addr), .. }) => Ok(*addr),
+      _ => Err(errno(libc::ENOTCONN)),
+    }
+  }
+
+  pub(crate) fn shutdown(&mut self, fd: RawFd, how: i32) -> io::Result<i32> {
+    let stream = match self.socket_mut(fd)? {
+      Socket { state: SocketState::Connected(stream), .. } => stream,
+      _ => return Err(errno(libc::ENOTCONN)),
+    };
+
+    let (read, write) = match how {
+      libc::SHUT_RD => (true, false),
+      libc::SHUT_WR => (false, true),
+      libc::SHUT_RDWR => (true, true),
+      _ => return Err(errno(libc::EINVAL)),
+    };
+    stream.read_shut |= read;
+    stream.write_shut |= write;
+
+    let peer = stream.peer;
+    if write && let Some(peer) = self.stream_mut(peer) {
+      peer.eof = true;
+    }
+    Ok(0)
+  }
+}
+
+impl Stream {
+  fn new(peer: RawFd) -> Self {
+    Self {
+      peer,
+      rx: VecDeque::new(),
+      eof: false,
+      read_shut: false,
+      write_shut: false,
+    }
+  }
+}
@@ -0,0 +1,277 @@
+//! Owned buffers that can be handed to the kernel.
+//!
+//! Operations like [`read`](crate::read) and [`write`](crate::write) take
+//! ownership of the buffer for as long as the kernel might access it, and hand
+//! it back in the [`BufResult`](crate::BufResult). Any type implementing
+//! [`IoBuf`] (or [`IoBufMut`] for reads) can be used, not just `Vec<u8>`.
+
+use std::ops::{Bound, RangeBounds};
+
+/// A buffer the kernel can read from.
+///
+/// # Safety
+///
+/// The pointer returned by [`IoBuf::stable_ptr`] must stay valid, and point to
+/// the same memory, even if the buffer is moved. `bytes_init` bytes from it
+/// must be initialized, and `bytes_total` must be allocated.
+pub unsafe trait IoBuf: Unpin + Send + 'static {
+  /// Pointer to the start of the buffer.
+  fn stable_ptr(&self) -> *const u8;
+
+  /// Number of initialized bytes. This is what gets written.
+  fn bytes_init(&self) -> usize;
+
+  /// Total size of the buffer, including uninitialized capacity.
+  fn bytes_total(&self) -> usize;
+
+  /// Restricts the buffer to `range`, without copying.
+  ///
+  /// The range is relative to the start of the buffer, and can extend into
+  /// uninitialized capacity.
+  ///
+  /// # Panics
+  ///
+  /// If the range is out of bounds of [`IoBuf::bytes_total`].
+  ///
+  /// # Examples
+  ///
+  /// ```rust
+  /// use lio::IoBuf;
+  ///
+  /// let buf = b"Hello, World!".to_vec();
+  /// let slice = buf.slice(7..12);
+  /// assert_eq!(&slice[..], b"World");
+  /// ```
+  fn slice(self, range: impl RangeBounds<usize>) -> Slice<Self>
+  where
+    Self: Sized,
+  {
+    let begin = match range.start_bound() {
+      Bound::Included(&n) => n,
+      Bound::Excluded(&n) => n + 1,
+      Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+      Bound::Included(&n) => n + 1,
+      Bound::Excluded(&n) => n,
+      Bound::Unbounded => self.bytes_total(),
+    };
+
+    assert!(begin <= end, "slice index starts at {begin} but ends at {end}");
+    assert!(
+      end <= self.bytes_total(),
+      "range end {end} out of bounds for buffer of size {}",
+      self.bytes_total()
+    );
+
+    Slice { buf: self, begin, end }
+  }
+}
+
+/// A buffer the kernel can write into.
+///
+/// Reads fill the spare capacity, from [`IoBuf::bytes_init`] up to
+/// [`IoBuf::bytes_total`], and then mark what they filled as initialized. So a
+/// `Vec` is appended to, and needs spare capacity, like from
+/// [`Vec::with_capacity`]. Fixed-size buffers are always fully initialized,
+/// which leaves nothing to read into, so they only implement [`IoBuf`].
+///
+/// # Safety
+///
+/// Same as [`IoBuf`], and [`IoBufMut::stable_mut_ptr`] must point to the same
+/// memory as [`IoBuf::stable_ptr`].
+pub unsafe trait IoBufMut: IoBuf {
+  /// Mutable pointer to the start of the buffer.
+  fn stable_mut_ptr(&mut self) -> *mut u8;
+
+  /// Marks the first `pos` bytes as initialized. Never shrinks the buffer.
+  ///
+  /// # Safety
+  ///
+  /// The first `pos` bytes must have been initialized.
+  unsafe fn set_init(&mut self, pos: usize);
+}
+
+unsafe impl IoBuf for Vec<u8> {
+  fn stable_ptr(&self) -> *const u8 {
+    self.as_ptr()
+  }
+
+  fn bytes_init(&self) -> usize {
+    self.len()
+  }
+
+  fn bytes_total(&self) -> usize {
+    self.capacity()
+  }
+}
+
+unsafe impl IoBufMut for Vec<u8> {
+  fn stable_mut_ptr(&mut self) -> *mut u8 {
+    self.as_mut_ptr()
+  }
+
+  unsafe fn set_init(&mut self, pos: usize) {
+    if self.len() < pos {
+      unsafe { self.set_len(pos) };
+    }
+  }
+}
+
+unsafe impl IoBuf for Box<[u8]> {
+  fn stable_ptr(&self) -> *const u8 {
+    self.as_ptr()
+  }
+
+  fn bytes_init(&self) -> usize {
+    self.len()
+  }
+
+  fn bytes_total(&self) -> usize {
+    self.len()
+  }
+}
+
+// Arrays are only buffers when boxed, since the pointer must survive moves.
+unsafe impl<const N: usize> IoBuf for Box<[u8; N]> {
+  fn stable_ptr(&self) -> *const u8 {
+    self.as_ptr()
+  }
+
+  fn bytes_init(&self) -> usize {
+    N
+  }
+
+  fn bytes_total(&self) -> usize {
+    N
+  }
+}
+
+unsafe impl IoBuf for &'static [u8] {
+  fn stable_ptr(&self) -> *const u8 {
+    self.as_ptr()
+  }
+
+  fn bytes_init(&self) -> usize {
+    self.len()
+  }
+
+  fn bytes_total(&self) -> usize {
+    self.len()
+  }
+}
+
+#[cfg(feature = "bytes")]
+unsafe impl IoBuf for bytes::Bytes {
+  fn stable_ptr(&self) -> *const u8 {
+    self.as_ptr()
+  }
+
+  fn bytes_init(&self) -> usize {
+    self.len()
+  }
+
+  fn bytes_total(&self) -> usize {
+    self.len()
+  }
+}
+
+#[cfg(feature = "bytes")]
+unsafe impl IoBuf for bytes::BytesMut {
+  fn stable_ptr(&self) -> *const u8 {
+    self.as_ptr()
+  }
+
+  fn bytes_init(&self) -> usize {
+    self.len()
+  }
+
+  fn bytes_total(&self) -> usize {
+    self.capacity()
+  }
+}
+
+#[cfg(feature = "bytes")]
+unsafe impl IoBufMut for bytes::BytesMut {
+  fn stable_mut_ptr(&mut self) -> *mut u8 {
+    self.as_mut_ptr()
+  }
+
+  unsafe fn set_init(&mut self, pos: usize) {
+    if self.len() < pos {
+      unsafe { self.set_len(pos) };
+    }
+  }
+}
+
+/// A view into part of a buffer, created with [`IoBuf::slice`].
+///
+/// Operations only see the viewed range, and the whole buffer can be taken back
+/// with [`Slice::into_inner`].
+pub struct Slice<T> {
+  buf: T,
+  begin: usize,
+  end: usize,
+}
+
+impl<T> Slice<T> {
+  /// Offset into the underlying buffer where this view starts.
+  pub fn begin(&self) -> usize {
+    self.begin
+  }
+
+  /// Offset into the underlying buffer where this view ends.
+  pub fn end(&self) -> usize {
+    self.end
+  }
+
+  pub fn get_ref(&self) -> &T {
+    &self.buf
+  }
+
+  pub fn get_mut(&mut self) -> &mut T {
+    &mut self.buf
+  }
+
+  /// Returns the whole underlying buffer.
+  pub fn into_inner(self) -> T {
+    self.buf
+  }
+}
+
+unsafe impl<T: IoBuf> IoBuf for Slice<T> {
+  fn stable_ptr(&self) -> *const u8 {
+    unsafe { self.buf.stable_ptr().add(self.begin) }
+  }
+
+  fn bytes_init(&self) -> usize {
+    self.buf.bytes_init().clamp(self.begin, self.end) - self.begin
+  }
+
+  fn bytes_total(&self) -> usize {
+    self.end - self.begin
+  }
+}
+
+unsafe impl<T: IoBufMut> IoBufMut for Slice<T> {
+  fn stable_mut_ptr(&mut self) -> *mut u8 {
+    unsafe { self.buf.stable_mut_ptr().add(self.begin) }
+  }
+
+  unsafe fn set_init(&mut self, pos: usize) {
+    // Bytes between the initialized part and `begin` are still uninitialized,
+    // so the underlying buffer can only grow if there is no such gap.
+    if self.buf.bytes_init() >= self.begin {
+      unsafe { self.buf.set_init(self.begin + pos) }
+    }
+  }
+}
+
+/// The initialized bytes of the view.
+impl<T: IoBuf> std::ops::Deref for Slice<T> {
+  type Target = [u8];
+
+  fn deref(&self) -> &[u8] {
+    unsafe { std::slice::from_raw_parts(self.stable_ptr(), self.bytes_init()) }
+  }
+}
@@ -0,0 +1,164 @@
+//! Where [`when_done`](crate::OperationProgress::when_done) callbacks run, see
+//! [`CallbackDispatch`].
+
+use std::{
+  fmt, mem,
+  sync::{Arc, mpsc},
+  thread,
+};
+
+use parking_lot::{Mutex, RwLock};
+
+/// A callback handed to a [`CallbackDispatch::Executor`] to run.
+pub type CallbackJob = Box<dyn FnOnce() + Send>;
+
+/// Where callbacks run, set with
+/// [`set_callback_dispatch`](crate::set_callback_dispatch).
+///
+/// Callbacks passed to [`when_done`](crate::OperationProgress::when_done) run
+/// once their operation completes. Inline, a slow callback holds up every
+/// completion after it.
+///
+/// # Examples
+///
+/// ```rust
+/// use lio::CallbackDispatch;
+///
+/// lio::init();
+/// lio::set_callback_dispatch(CallbackDispatch::ThreadPool(4));
+///
+/// // Or run them on threads of your own.
+/// let (sender, receiver) = std::sync::mpsc::channel::<lio::CallbackJob>();
+/// let sender = std::sync::Mutex::new(sender);
+/// lio::set_callback_dispatch(CallbackDispatch::executor(move |callback| {
+///     sender.lock().unwrap().send(callback).unwrap();
+/// }));
+/// # drop(receiver);
+/// # lio::exit();
+/// ```
+#[derive(Clone, Default)]
+pub enum CallbackDispatch {
+  /// On the thread that completes the operation: the lio background thread,
+  /// or the one calling [`tick`](crate::tick).
+  #[default]
+  Inline,
+  /// On this many threads owned by lio. [`exit`](crate::exit) waits for the
+  /// callbacks queued on them.
+  ThreadPool(usize),
+  /// Handed to this function, which runs them wherever it likes. Callbacks
+  /// handed over during [`exit`](crate::exit) may run after it returns.
+  Executor(Arc<dyn Fn(CallbackJob) + Send + Sync>),
+}
+
+impl CallbackDispatch {
+  /// Shorthand for [`CallbackDispatch::Executor`].
+  pub fn executor(
+    executor: impl Fn(CallbackJob) + Send + Sync + 'static,
+  ) -> Self {
+    CallbackDispatch::Executor(Arc::new(executor))
+  }
+}
+
+impl fmt::Debug for CallbackDispatch {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      CallbackDispatch::Inline => f.write_str("Inline"),
+      CallbackDispatch::ThreadPool(threads) => {
+        f.debug_tuple("ThreadPool").field(threads).finish()
+      }
+      CallbackDispatch::Executor(_) => f.write_str("Executor(..)"),
+    }
+  }
+}
+
+enum Mode {
+  Inline,
+  Pool(mpsc::Sender<CallbackJob>),
+  Executor(Arc<dyn Fn(CallbackJob) + Send + Sync>),
+}
+
+/// Runs callbacks as set by [`CallbackDispatch`].
+pub(crate) struct Dispatcher {
+  mode: RwLock<Mode>,
+  /// Threads of the current pool, and of pools replaced since, which stop once
+  /// their queue is empty.
+  threads: Mutex<Vec<thread::JoinHandle<()>>>,
+}
+
+impl Dispatcher {
+  pub(crate) fn new() -> Self {
+    Self { mode: RwLock::new(Mode::Inline), threads: Mutex::new(Vec::new()) }
+  }
+
+  pub(crate) fn set(&self, dispatch: CallbackDispatch) {
+    let mode = match dispatch {
+      CallbackDispatch::Inline => Mode::Inline,
+      CallbackDispatch::ThreadPool(threads) => Mode::Pool(self.spawn(threads)),
+      CallbackDispatch::Executor(executor) => Mode::Executor(executor),
+    };
+    // Dropping the old pool's sender lets its threads stop.
+    drop(mem::replace(&mut *self.mode.write(), mode));
+  }
+
+  fn spawn(&self, threads: usize) -> mpsc::Sender<CallbackJob> {
+    let (sender, receiver) = mpsc::channel::<CallbackJob>();
+    let receiver = Arc::new(Mutex::new(receiver));
+
+    let mut handles = self.threads.lock();
+    for _ in 0..threads.max(1) {
+      let receiver = receiver.clone();
+      let handle = thread::Builder::new()
+        .name("lio-callback".into())
+        .spawn(move || {
+          loop {
+            // The receiver is unlocked before running the job.
+            let Ok(job) = receiver.lock().recv() else { break };
+            job();
+          }
+        })
+        .expect("failed to launch a callback thread");
+      handles.push(handle);
+    }
+    sender
+  }
+
+  pub(crate) fn run(&self, job: impl FnOnce() + Send + 'static) {
+    let mode = self.mode.read();
+    match &*mode {
+      Mode::Inline => {
+        drop(mode);
+        job()
+      }
+      Mode::Pool(sender) => {
+        if let Err(mpsc::SendError(job)) = sender.send(Box::new(job)) {
+          drop(mode);
+          job()
+        }
+      }
+      Mode::Executor(executor) => {
+        // Not called under the lock, so it may change the dispatch itself.
+        let executor = executor.clone();
+        drop(mode);
+        executor(Box::new(job))
+      }
+    }
+  }
+
+  /// If this is one of the pool's threads.
+  pub(crate) fn on_pool_thread(&self) -> bool {
+    let current = thread::current().id();
+    self.threads.lock().iter().any(|handle| handle.thread().id() == current)
+  }
+
+  /// Goes back to running callbacks inline, and waits for the pool threads to
+  /// run what's queued on them.
+  pub(crate) fn shutdown(&self) {
+    self.set(CallbackDispatch::Inline);
+    let threads = mem::take(&mut *self.threads.lock());
+    for handle in threads {
+      if handle.join().is_err() {
+        panic!("lio callback thread panicked");
+      }
+    }
+  }
+}
@@ -1,33 +1,48 @@
 use crate::OperationProgress;
 use crate::backends::{self, IoBackend};
+use crate::dispatch::{CallbackDispatch, Dispatcher};
 use crate::op::Operation;
 #[cfg(feature = "high")]
 use crate::op_registration::TryExtractOutcome;
+use crate::stats::{self, RingStats};
 
 use parking_lot::Mutex;
 #[cfg(feature = "high")]
 use std::task::Waker;
 use std::{
+  cell::Cell,
   collections::HashMap,
+  io,
+  ops::Deref,
   ptr::NonNull,
   sync::{
     OnceLock,
-    atomic::{AtomicPtr, AtomicU64, Ordering},
+    atomic::{AtomicBool, AtomicPtr, AtomicU64, AtomicUsize, Ordering},
     mpsc,
   },
   thread,
+  time::{Duration, Instant},
 };
 
 use crate::op;
-use crate::op_registration::OpRegistration;
+use crate::op_registration::{OpCallback, OpRegistration};
+#[cfg(feature = "fault_injection")]
+use crate::{
+  fault::{Fault, Hold},
+  op_registration::ExtractedOpNotification,
+};
 
 pub struct OpStore {
   store: Mutex<HashMap<u64, OpRegistration>>,
+  dispatch: Dispatcher,
 }
 
 impl OpStore {
   fn new() -> OpStore {
-    Self { store: Mutex::new(HashMap::with_capacity(512)) }
+    Self {
+      store: Mutex::new(HashMap::with_capacity(512)),
+      dispatch: Dispatcher::new(),
+    }
   }
   pub fn next_id(&self) -> u64 {
     static NEXT: OnceLock<AtomicU64> = OnceLock::new();
@@ -37,6 +52,14 @@ impl OpStore {
     let mut _lock = self.store.lock();
     _lock.remove(&id).is_some()
   }
+  /// Removes the registration and calls its callback where the
+  /// [`CallbackDispatch`](crate::CallbackDispatch) says. The store isn't locked
+  /// while the callback runs, so it can submit new operations.
+  pub fn run_callback(&self, id: u64, callback: OpCallback) {
+    let mut reg =
+      self.store.lock().remove(&id).expect("Cannot find matching operation");
+    self.dispatch.run(move || callback.call(&mut reg));
+  }
   pub fn get_mut<F, R>(&self, id: u64, mut _f: F) -> Option<R>
   where
     F: FnOnce(&mut OpRegistration) -> R,
@@ -45,10 +68,71 @@ impl OpStore {
     let reg = _lock.get_mut(&id)?;
     Some(_f(reg))
   }
+  /// Ids of operations that the kernel hasn't completed yet.
+  pub fn pending(&self) -> Vec<u64> {
+    let _lock = self.store.lock();
+    _lock
+      .iter()
+      .filter(|(_, reg)| reg.is_pending())
+      .map(|(id, _)| *id)
+      .collect()
+  }
+  pub fn len(&self) -> usize {
+    self.store.lock().len()
+  }
+  pub fn has_pending(&self) -> bool {
+    self.store.lock().values().any(OpRegistration::is_pending)
+  }
+  /// Removes all pending operations, leaking them since the kernel might still
+  /// write to them. Returns how many were leaked.
+  pub fn leak_pending(&self) -> usize {
+    let mut _lock = self.store.lock();
+    let mut leaked = 0;
+    _lock.retain(|_, reg| {
+      if reg.is_pending() {
+        reg.leak();
+        leaked += 1;
+        false
+      } else {
+        true
+      }
+    });
+    leaked
+  }
+  /// Completes held operations whose injected delay expired.
+  #[cfg(feature = "fault_injection")]
+  pub fn release_held(&self) {
+    let held: Vec<u64> = {
+      let _lock = self.store.lock();
+      _lock.iter().filter(|(_, reg)| reg.is_held()).map(|(id, _)| *id).collect()
+    };
+
+    for id in held {
+      let Some(Some(notification)) =
+        self.get_mut(id, |reg| reg.release()).flatten()
+      else {
+        continue;
+      };
+      match notification {
+        #[cfg(feature = "high")]
+        ExtractedOpNotification::Waker(waker) => waker.wake(),
+        ExtractedOpNotification::Callback(callback) => {
+          self.run_callback(id, callback);
+        }
+      }
+    }
+  }
+  #[cfg(feature = "fault_injection")]
+  pub fn has_held(&self) -> bool {
+    self.store.lock().values().any(OpRegistration::is_held)
+  }
   pub fn insert<O>(&self, id: u64, op: Box<O>)
   where
     O: Operation,
   {
+    #[cfg(feature = "tracing")]
+    tracing::Span::current().record("id", id);
+
     let mut _lock = self.store.lock();
     let result = _lock.insert(id, OpRegistration::new(op));
     assert!(result.is_none());
@@ -64,18 +148,132 @@ pub type Default = backends::IoUring;
 #[cfg(not(linux))]
 pub type Default = backends::Polling;
 
-pub(crate) struct Driver<Io = Default> {
+/// Backend picked at [`Driver::init_with`].
+pub(crate) enum Backend {
+  // Boxed, since the backends differ a lot in size.
+  Native(Box<Default>),
+  #[cfg(feature = "sim")]
+  Simulated(Box<backends::Simulated>),
+}
+
+impl IoBackend for Backend {
+  fn tick(&self, store: &OpStore, can_wait: bool) {
+    match self {
+      Backend::Native(io) => io.tick(store, can_wait),
+      #[cfg(feature = "sim")]
+      Backend::Simulated(io) => io.tick(store, can_wait),
+    }
+  }
+  fn submit<O>(&self, op: O, store: &OpStore) -> OperationProgress<O>
+  where
+    O: Operation + Sized,
+  {
+    match self {
+      Backend::Native(io) => io.submit(op, store),
+      #[cfg(feature = "sim")]
+      Backend::Simulated(io) => io.submit(op, store),
+    }
+  }
+  fn notify(&self) {
+    match self {
+      Backend::Native(io) => io.notify(),
+      #[cfg(feature = "sim")]
+      Backend::Simulated(io) => io.notify(),
+    }
+  }
+  fn cancel(&self, id: u64, store: &OpStore) {
+    match self {
+      Backend::Native(io) => io.cancel(id, store),
+      #[cfg(feature = "sim")]
+      Backend::Simulated(io) => io.cancel(id, store),
+    }
+  }
+  fn flush(&self) {
+    match self {
+      Backend::Native(io) => io.flush(),
+      #[cfg(feature = "sim")]
+      Backend::Simulated(io) => io.flush(),
+    }
+  }
+  fn set_submit_policy(&self, policy: backends::SubmitPolicy) {
+    match self {
+      Backend::Native(io) => io.set_submit_policy(policy),
+      #[cfg(feature = "sim")]
+      Backend::Simulated(io) => io.set_submit_policy(policy),
+    }
+  }
+  fn ring_stats(&self) -> Option<RingStats> {
+    match self {
+      Backend::Native(io) => io.ring_stats(),
+      #[cfg(feature = "sim")]
+      Backend::Simulated(io) => io.ring_stats(),
+    }
+  }
+}
+
+thread_local! {
+  /// Nesting depth of `batch` scopes on this thread.
+  static BATCH_DEPTH: Cell<usize> = const { Cell::new(0) };
+  /// Number of [`DriverRef`]s alive on this thread.
+  static THREAD_REFS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// If operations submitted on this thread are deferred until the end of a `batch` scope.
+pub(crate) fn batching() -> bool {
+  BATCH_DEPTH.get() > 0
+}
+
+/// Reserved `user_data`/key for entries that aren't operations, like wake-ups and cancellations.
+pub(crate) const INTERNAL_ID: u64 = u64::MAX;
+
+/// How long cancelled operations get to complete before they are leaked.
+const CANCEL_GRACE: Duration = Duration::from_secs(1);
+const DRAIN_INTERVAL: Duration = Duration::from_millis(1);
+
+pub(crate) struct Driver<Io = Backend> {
   driver: Io,
   store: OpStore,
-  // Shared shutdown state and background thread handle
-  shutting_down: Mutex<Option<mpsc::Sender<()>>>,
+  // Set once shutdown starts, new operations are then completed with ECANCELED.
+  shutting_down: AtomicBool,
+  // Background thread handle, and the channel used to stop it.
+  worker_stop: Mutex<Option<mpsc::Sender<()>>>,
   background_handle: Mutex<Option<thread::JoinHandle<()>>>,
 }
 
 static DRIVER: AtomicPtr<Driver> = AtomicPtr::new(std::ptr::null_mut());
+/// Number of [`DriverRef`]s alive on any thread. [`Driver::exit`] doesn't free
+/// the driver before it drops to zero.
+static REFS: AtomicUsize = AtomicUsize::new(0);
+
+/// Reference to the running driver, which keeps it from being freed.
+///
+/// Only meant to live for the duration of a call, never across an `await` or
+/// stored. It isn't `Send`, since it's counted per thread to catch
+/// [`Driver::exit`] being called while the driver is in use.
+pub(crate) struct DriverRef(NonNull<Driver>);
+
+impl Deref for DriverRef {
+  type Target = Driver;
+
+  fn deref(&self) -> &Driver {
+    // SAFETY: The driver isn't freed while a DriverRef exists.
+    unsafe { self.0.as_ref() }
+  }
+}
+
+impl Drop for DriverRef {
+  fn drop(&mut self) {
+    THREAD_REFS.set(THREAD_REFS.get() - 1);
+    REFS.fetch_sub(1, Ordering::SeqCst);
+  }
+}
 
 impl Driver {
   pub(crate) fn init() {
+    Self::init_with(Backend::Native(Box::new(Default::new())))
+  }
+
+  pub(crate) fn init_with(backend: Backend) {
     // Check if already initialized
     let current = DRIVER.load(Ordering::Acquire);
     if !current.is_null() {
@@ -83,9 +281,10 @@ impl Driver {
     }
 
     let driver = Driver {
-      driver: Default::new(),
+      driver: backend,
       store: OpStore::new(),
-      shutting_down: Mutex::new(None),
+      shutting_down: AtomicBool::new(false),
+      worker_stop: Mutex::new(None),
       background_handle: Mutex::new(None),
     };
 
@@ -109,28 +308,116 @@ impl Driver {
     }
   }
 
-  pub(crate) fn get() -> &'static Driver {
-    let ptr = DRIVER.load(Ordering::Acquire);
-    if ptr.is_null() {
+  pub(crate) fn get() -> DriverRef {
+    let Some(driver) = Self::try_get() else {
       panic!("Driver not initialized. Call Driver::init() first.");
-    }
-    // SAFETY: The pointer is valid from init() until deallocate()
-    unsafe { &*ptr }
+    };
+    driver
+  }
+
+  pub(crate) fn try_get() -> Option<DriverRef> {
+    // Counted before loading the pointer, so that exit, which clears the
+    // pointer before waiting for REFS to drop to zero, sees this reference.
+    REFS.fetch_add(1, Ordering::SeqCst);
+    let Some(ptr) = NonNull::new(DRIVER.load(Ordering::SeqCst)) else {
+      REFS.fetch_sub(1, Ordering::SeqCst);
+      return None;
+    };
+    THREAD_REFS.set(THREAD_REFS.get() + 1);
+    Some(DriverRef(ptr))
+  }
+
+  /// Whether [`Driver::exit`] has started, after which operations aren't submitted.
+  pub(crate) fn is_shutting_down(&self) -> bool {
+    self.shutting_down.load(Ordering::Acquire)
+  }
+
+  #[cfg(feature = "sim")]
+  pub(crate) fn is_simulated(&self) -> bool {
+    matches!(self.driver, Backend::Simulated(_))
+  }
+
+  /// Number of registered operations, and ring occupancy.
+  pub(crate) fn stats(&self) -> (usize, Option<RingStats>) {
+    (self.store.len(), self.driver.ring_stats())
   }
 
-  pub fn exit() {
-    Driver::get().worker_shutdown();
-    let ptr = NonNull::new(DRIVER.swap(std::ptr::null_mut(), Ordering::AcqRel))
+  /// Shuts down the driver:
+  /// 1. New operations are completed with `ECANCELED` instead of being submitted.
+  /// 2. The background thread, if any, is stopped and joined.
+  /// 3. In-flight operations get until `timeout` to complete, and are then cancelled.
+  /// 4. Operations that still haven't completed are leaked, because the kernel
+  ///    might still write into them. Their callbacks are dropped without being called.
+  /// 5. The driver, and with it the io_uring/poller, is freed.
+  pub fn exit(timeout: Duration) {
+    // The driver can't be freed under the caller, and lio's own threads can't
+    // join themselves.
+    if THREAD_REFS.get() > 0 {
+      panic!(
+        "lio::exit called while the driver is in use on this thread, like from a callback"
+      );
+    }
+    let driver = Driver::get();
+    if driver.store.dispatch.on_pool_thread() {
+      panic!("lio::exit called from a callback thread");
+    }
+    if driver.shutting_down.swap(true, Ordering::AcqRel) {
+      panic!("Driver already shutting down.");
+    }
+
+    driver.worker_shutdown();
+
+    driver.drain(Instant::now() + timeout);
+    for id in driver.store.pending() {
+      driver.driver.cancel(id, &driver.store);
+    }
+    driver.drain(Instant::now() + CANCEL_GRACE);
+
+    let _leaked = driver.store.leak_pending();
+    driver.store.dispatch.shutdown();
+    #[cfg(feature = "tracing")]
+    if _leaked > 0 {
+      tracing::warn!(
+        leaked = _leaked,
+        "lio: operations didn't complete before shutdown"
+      );
+    }
+
+    drop(driver);
+    let ptr = NonNull::new(DRIVER.swap(std::ptr::null_mut(), Ordering::SeqCst))
       .expect("driver not initialized");
+    // Other threads might still be using it, like a lio_poll waiting for
+    // completions, which is woken up to notice.
+    while REFS.load(Ordering::SeqCst) != 0 {
+      // SAFETY: Not freed yet.
+      unsafe { ptr.as_ref() }.driver.notify();
+      thread::sleep(DRAIN_INTERVAL);
+    }
     Self::deallocate(ptr);
   }
 
-  pub(crate) fn worker_shutdown(&'static self) {
-    let mut handle_lock = self.background_handle.lock();
-
-    if let Some(handle) = handle_lock.take() {
-      // driver.poller.
+  pub(crate) fn worker_shutdown(&self) {
+    let Some(handle) = self.background_handle.lock().take() else {
+      return;
     };
+
+    if let Some(sender) = self.worker_stop.lock().take() {
+      let _ = sender.send(());
+    }
+    // Wakes the worker up if it's waiting for completions.
+    self.driver.notify();
+
+    if handle.join().is_err() {
+      panic!("lio worker thread panicked");
+    }
+  }
+
+  /// Completes operations until none are in-flight, or `deadline` is reached.
+  fn drain(&self, deadline: Instant) {
+    while self.store.has_pending() && Instant::now() < deadline {
+      self.tick(false);
+      thread::sleep(DRAIN_INTERVAL);
+    }
   }
 
   /// Deallocates the Driver, freeing all resources.
@@ -142,22 +429,141 @@ impl Driver {
     }
   }
 
+  /// Releases the result of operation `id` once it completes, see
+  /// [`Operation::release`]. `None` if it isn't registered anymore.
   pub(crate) fn detach(&self, id: u64) -> Option<()> {
-    todo!();
+    let done = self.store.get_mut(id, |entry| entry.detach())?;
+    if let Some(callback) = done {
+      self.store.run_callback(id, callback);
+    }
+    Some(())
+  }
+
+  /// Like [`Driver::detach`], handing the result to `callback` instead of
+  /// [`Operation::release`](op::Operation::release).
+  #[cfg(feature = "high")]
+  pub(crate) fn detach_with(
+    &self,
+    id: u64,
+    callback: OpCallback,
+  ) -> Option<()> {
+    let done = self.store.get_mut(id, |entry| entry.detach_with(callback))?;
+    if let Some(callback) = done {
+      self.store.run_callback(id, callback);
+    }
+    Some(())
   }
+
   pub(crate) fn submit<T>(op: T) -> OperationProgress<T>
   where
     T: op::Operation,
   {
     let driver = Driver::get();
-    driver.driver.submit(op, &driver.store)
+    stats::counters::<T>().submitted();
+
+    #[cfg(feature = "tracing")]
+    let span = op_span(&op);
+    #[cfg(feature = "tracing")]
+    let _enter = span.enter();
+
+    #[cfg(feature = "tracing")]
+    tracing::debug!("submitting");
+
+    if driver.shutting_down.load(Ordering::Acquire) {
+      return OperationProgress::new_from_result(
+        op,
+        Err(io::Error::from_raw_os_error(libc::ECANCELED)),
+      );
+    }
+
+    #[cfg(feature = "fault_injection")]
+    let mut op = op;
+    #[cfg(feature = "fault_injection")]
+    {
+      let fault = crate::fault::pick::<T>();
+      Hold::set_for_next(fault);
+      crate::fault::set_unsupported(fault);
+      match fault {
+        Some(Fault::Error(errno)) => {
+          return OperationProgress::new_from_result(
+            op,
+            Err(io::Error::from_raw_os_error(errno)),
+          );
+        }
+        Some(Fault::Short(len)) => op.shorten(len),
+        _ => {}
+      }
+    }
+
+    let progress = driver.driver.submit(op, &driver.store);
+    // Only registered operations take the hold, so don't leave it for the next
+    // one when this one ran without being registered, like a blocking fallback.
+    // Backends without fallbacks leave the unsupported fault, too.
+    #[cfg(feature = "fault_injection")]
+    {
+      Hold::take();
+      crate::fault::take_unsupported();
+    }
+    progress
+  }
+
+  /// Runs `f` with submissions on this thread deferred, and submits them
+  /// together when the outermost scope ends.
+  pub(crate) fn batch<R>(&self, f: impl FnOnce() -> R) -> R {
+    struct Scope<'a>(&'a Driver);
+    impl Drop for Scope<'_> {
+      fn drop(&mut self) {
+        let depth = BATCH_DEPTH.get() - 1;
+        BATCH_DEPTH.set(depth);
+        if depth == 0 {
+          self.0.driver.flush();
+        }
+      }
+    }
+
+    BATCH_DEPTH.set(BATCH_DEPTH.get() + 1);
+    let _scope = Scope(self);
+    f()
+  }
+
+  pub(crate) fn set_submit_policy(&self, policy: backends::SubmitPolicy) {
+    self.driver.set_submit_policy(policy)
+  }
+
+  pub(crate) fn set_callback_dispatch(&self, dispatch: CallbackDispatch) {
+    self.store.dispatch.set(dispatch)
   }
 
   pub(crate) fn tick(&self, can_wait: bool) {
-    self.driver.tick(&self.store, can_wait)
+    // Held completions are released by ticking, so don't block on the kernel.
+    #[cfg(feature = "fault_injection")]
+    let can_wait = can_wait && !self.store.has_held();
+
+    self.driver.tick(&self.store, can_wait);
+
+    #[cfg(feature = "fault_injection")]
+    self.store.release_held();
   }
 
-  pub fn spawn_ev(&'static self, sender: mpsc::Receiver<()>) {
+  /// Cancels operation `id`, see [`IoBackend::cancel`]. Returns false if it
+  /// isn't in flight.
+  #[cfg(feature = "unstable_ffi")]
+  pub(crate) fn cancel(&self, id: u64) -> bool {
+    if !self.store.get_mut(id, |reg| reg.is_pending()).unwrap_or(false) {
+      return false;
+    }
+    self.driver.cancel(id, &self.store);
+    true
+  }
+
+  /// If callbacks run on the background thread from [`Driver::spawn_ev`].
+  #[cfg(feature = "unstable_ffi")]
+  pub(crate) fn has_worker(&self) -> bool {
+    self.background_handle.lock().is_some()
+  }
+
+  pub fn spawn_ev(&self) {
+    let (stop, sender) = mpsc::channel();
     let handle = utils::create_worker(move || {
       loop {
         match sender.try_recv() {
@@ -170,11 +576,16 @@ impl Driver {
           },
         }
 
-        self.driver.tick(&self.store, true);
+        // Not holding on to the driver in between, so exit can free it.
+        match Driver::try_get() {
+          Some(driver) => driver.tick(true),
+          None => break,
+        }
       }
     });
     let old = self.background_handle.lock().replace(handle);
     assert!(old.is_none());
+    *self.worker_stop.lock() = Some(stop);
   }
 
   // FIXME: On first run per key, run run_blocking and that will fix it.
@@ -196,13 +607,22 @@ impl Driver {
     T: op::Operation,
     F: FnOnce(T::Result) + Send,
   {
-    use crate::op_registration::OpCallback;
-    self
+    let done = self
       .store
       .get_mut(id, |entry| {
         entry.set_callback(OpCallback::new::<T, F>(callback))
       })
-      .unwrap()
+      .unwrap();
+    // Already completed, call it straight away.
+    if let Some(callback) = done {
+      self.store.run_callback(id, callback);
+    }
+  }
+
+  /// Span of a registered operation.
+  #[cfg(feature = "tracing")]
+  pub(crate) fn span(&self, id: u64) -> Option<tracing::Span> {
+    self.store.get_mut(id, |entry| entry.span().clone())
   }
 
   #[cfg(feature = "high")]
@@ -211,6 +631,56 @@ impl Driver {
   }
 }
 
+/// Span covering an operation from submission to completion. Fields not known
+/// yet are recorded later.
+#[cfg(feature = "tracing")]
+pub(crate) fn op_span<T: Operation>(op: &T) -> tracing::Span {
+  use tracing::field::Empty;
+
+  let span = tracing::debug_span!(
+    "lio_op",
+    op = stats::op_name::<T>(),
+    opcode = Empty,
+    id = Empty,
+    fd = Empty,
+    len = Empty,
+    result = Empty,
+    errno = Empty,
+    duration_us = Empty,
+  );
+  #[cfg(linux)]
+  span.record("opcode", T::OPCODE);
+  if let Some(fd) = op.trace_fd() {
+    span.record("fd", fd);
+  }
+  if let Some(len) = op.trace_len() {
+    span.record("len", len);
+  }
+  span
+}
+
+/// Records the outcome of an operation on its span.
+#[cfg(feature = "tracing")]
+pub(crate) fn record_result(
+  span: &tracing::Span,
+  res: &io::Result<i32>,
+  duration: Duration,
+) {
+  span.record("duration_us", duration.as_micros() as u64);
+  match res {
+    Ok(value) => {
+      span.record("result", value);
+      span.in_scope(|| tracing::debug!(result = value, "completed"));
+    }
+    Err(err) => {
+      if let Some(errno) = err.raw_os_error() {
+        span.record("errno", errno);
+      }
+      span.in_scope(|| tracing::debug!(error = %err, "failed"));
+    }
+  }
+}
+
 mod utils {
   use std::thread;
 
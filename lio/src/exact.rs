@@ -0,0 +1,303 @@
+//! Transfers that resubmit until the whole buffer is done, see [`write_all`](crate::write_all).
+
+use std::{error::Error, fmt, io, ops::Range, os::fd::RawFd};
+
+#[cfg(feature = "high")]
+use std::{
+  future::Future,
+  pin::Pin,
+  task::{Context, Poll, ready},
+};
+
+#[cfg(feature = "unstable_ffi")]
+use std::sync::{
+  Arc,
+  atomic::{AtomicBool, AtomicU64, Ordering},
+};
+
+use crate::{
+  BorrowFd, BufResult, IoBuf, IoBufMut, OperationProgress, Slice,
+  driver::Driver,
+  op::{self, Operation, Read, Recv, Write},
+};
+
+/// Result of an [`Exact`] transfer: the total bytes transferred, and the buffer.
+pub type ExactResult<B> = (Result<usize, TransferError>, B);
+
+/// Error from an [`Exact`] transfer, with how far it got.
+#[derive(Debug)]
+pub struct TransferError {
+  /// Bytes transferred before the error.
+  pub transferred: usize,
+  /// `UnexpectedEof` if a read hit end of file, `WriteZero` if a write made
+  /// no progress, and otherwise the error of the failed operation.
+  pub error: io::Error,
+}
+
+impl fmt::Display for TransferError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{} after {} bytes", self.error, self.transferred)
+  }
+}
+
+impl Error for TransferError {
+  fn source(&self) -> Option<&(dyn Error + 'static)> {
+    Some(&self.error)
+  }
+}
+
+impl From<TransferError> for io::Error {
+  fn from(err: TransferError) -> Self {
+    err.error
+  }
+}
+
+/// Operation that [`Exact`] resubmits with the rest of the buffer. Not
+/// nameable outside of lio, so it can't be implemented elsewhere.
+pub trait Step:
+  Operation<Result = BufResult<i32, Slice<Self::Buf>>>
+  + Sized
+  + Unpin
+  + Send
+  + 'static
+{
+  type Buf: IoBuf;
+
+  /// Error for a transfer that made no progress before the end.
+  const ZERO: io::ErrorKind;
+
+  /// Part of the buffer to transfer: the initialized bytes for writes, and
+  /// the spare capacity for reads.
+  fn range(buf: &Self::Buf) -> Range<usize>;
+
+  fn submit(
+    fd: RawFd,
+    buf: Slice<Self::Buf>,
+    offset: i64,
+  ) -> OperationProgress<Self>;
+}
+
+impl<B: IoBuf> Step for Write<Slice<B>> {
+  type Buf = B;
+  const ZERO: io::ErrorKind = io::ErrorKind::WriteZero;
+
+  fn range(buf: &B) -> Range<usize> {
+    0..buf.bytes_init()
+  }
+
+  fn submit(fd: RawFd, buf: Slice<B>, offset: i64) -> OperationProgress<Self> {
+    Driver::submit(Write::new(fd, buf, offset))
+  }
+}
+
+impl<B: IoBufMut> Step for Read<Slice<B>> {
+  type Buf = B;
+  const ZERO: io::ErrorKind = io::ErrorKind::UnexpectedEof;
+
+  fn range(buf: &B) -> Range<usize> {
+    buf.bytes_init()..buf.bytes_total()
+  }
+
+  fn submit(fd: RawFd, buf: Slice<B>, offset: i64) -> OperationProgress<Self> {
+    Driver::submit(Read::new(fd, buf, offset))
+  }
+}
+
+impl<B: IoBuf> Step for op::Send<Slice<B>> {
+  type Buf = B;
+  const ZERO: io::ErrorKind = io::ErrorKind::WriteZero;
+
+  fn range(buf: &B) -> Range<usize> {
+    0..buf.bytes_init()
+  }
+
+  fn submit(fd: RawFd, buf: Slice<B>, _offset: i64) -> OperationProgress<Self> {
+    Driver::submit(op::Send::new(fd, buf, None))
+  }
+}
+
+impl<B: IoBufMut> Step for Recv<Slice<B>> {
+  type Buf = B;
+  const ZERO: io::ErrorKind = io::ErrorKind::UnexpectedEof;
+
+  fn range(buf: &B) -> Range<usize> {
+    buf.bytes_init()..buf.bytes_total()
+  }
+
+  fn submit(fd: RawFd, buf: Slice<B>, _offset: i64) -> OperationProgress<Self> {
+    Driver::submit(Recv::new(fd, buf, None))
+  }
+}
+
+/// Step in flight of a transfer, so it can be cancelled from the C API.
+#[cfg(feature = "unstable_ffi")]
+#[derive(Default)]
+pub(crate) struct Steps {
+  current: AtomicU64,
+  cancelled: AtomicBool,
+}
+
+#[cfg(feature = "unstable_ffi")]
+impl Steps {
+  /// Cancels the step in flight, and any step submitted after it.
+  pub(crate) fn cancel(&self) {
+    self.cancelled.store(true, Ordering::SeqCst);
+    Driver::get().cancel(self.current.load(Ordering::SeqCst));
+  }
+
+  fn submitted(&self, id: u64) {
+    self.current.store(id, Ordering::SeqCst);
+    if self.cancelled.load(Ordering::SeqCst) {
+      Driver::get().cancel(id);
+    }
+  }
+}
+
+/// Where a transfer is at.
+struct Cursor {
+  fd: RawFd,
+  /// Offset of the first byte, negative for the current file position.
+  offset: i64,
+  /// Part of the buffer to transfer, from [`Step::range`] before the first step.
+  range: Range<usize>,
+  done: usize,
+}
+
+impl Cursor {
+  fn submit<O: Step>(&self, buf: O::Buf) -> OperationProgress<O> {
+    let offset = match self.offset {
+      offset if offset < 0 => offset,
+      offset => offset + self.done as i64,
+    };
+    let rest = self.range.start + self.done..self.range.end;
+    O::submit(self.fd, buf.slice(rest), offset)
+  }
+
+  /// Handles one completed step, and submits the next one unless the transfer
+  /// is finished.
+  fn advance<O: Step>(
+    &mut self,
+    res: io::Result<i32>,
+    buf: O::Buf,
+  ) -> Result<OperationProgress<O>, ExactResult<O::Buf>> {
+    match res {
+      Ok(n) => {
+        self.done += n as usize;
+        if self.done >= self.range.len() {
+          return Err((Ok(self.done), buf));
+        }
+        if n == 0 {
+          let error = io::Error::from(O::ZERO);
+          return Err((Err(self.error(error)), buf));
+        }
+      }
+      Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+      Err(err) => return Err((Err(self.error(err)), buf)),
+    }
+    Ok(self.submit(buf))
+  }
+
+  fn error(&self, error: io::Error) -> TransferError {
+    TransferError { transferred: self.done, error }
+  }
+}
+
+/// Progress of a [`write_all`](crate::write_all), [`read_exact`](crate::read_exact),
+/// [`send_all`](crate::send_all) or [`recv_exact`](crate::recv_exact).
+///
+/// Each short transfer is followed by another operation for the rest of the
+/// buffer, until all of it is transferred. `EINTR` is retried, any other error,
+/// or a transfer of zero bytes, ends it early with a [`TransferError`].
+///
+/// Like [`OperationProgress`], it can be awaited or given a callback.
+pub struct Exact<O: Step> {
+  cursor: Cursor,
+  progress: OperationProgress<O>,
+}
+
+impl<O: Step> Exact<O> {
+  pub(crate) fn new(fd: impl BorrowFd, buf: O::Buf, offset: i64) -> Self {
+    let range = O::range(&buf);
+    let cursor = Cursor { fd: fd.raw_fd(), offset, range, done: 0 };
+    let progress = cursor.submit(buf);
+    Self { cursor, progress }
+  }
+
+  /// Calls `callback` once the whole buffer is transferred, or the transfer failed.
+  pub fn when_done<F>(self, callback: F)
+  where
+    F: FnOnce(ExactResult<O::Buf>) + Send + 'static,
+  {
+    let Exact { mut cursor, progress } = self;
+    progress.when_done(move |(res, slice)| {
+      match cursor.advance::<O>(res, slice.into_inner()) {
+        Ok(progress) => Exact { cursor, progress }.when_done(callback),
+        Err(output) => callback(output),
+      }
+    });
+  }
+
+  /// Same as [`Exact::when_done`], keeping `steps` up to date.
+  #[cfg(feature = "unstable_ffi")]
+  pub(crate) fn when_done_tracked<F>(self, steps: Arc<Steps>, callback: F)
+  where
+    F: FnOnce(ExactResult<O::Buf>) + Send + 'static,
+  {
+    let Exact { mut cursor, progress } = self;
+    if let Some(id) = progress.id() {
+      steps.submitted(id);
+    }
+    progress.when_done(move |(res, slice)| {
+      match cursor.advance::<O>(res, slice.into_inner()) {
+        Ok(progress) => {
+          Exact { cursor, progress }.when_done_tracked(steps, callback)
+        }
+        Err(output) => callback(output),
+      }
+    });
+  }
+
+  /// Id of the first step, see [`OperationProgress::id`].
+  #[cfg(feature = "unstable_ffi")]
+  pub(crate) fn id(&self) -> Option<u64> {
+    self.progress.id()
+  }
+
+  /// Drops the transfer without submitting further steps, keeping `guard`
+  /// alive until the step in flight completed.
+  #[cfg(feature = "high")]
+  pub(crate) fn drop_with<G: Send + 'static>(self, guard: G) {
+    self.progress.drop_with(guard);
+  }
+
+  /// Convert the transfer into a channel receiver.
+  #[cfg(feature = "high")]
+  pub fn get_receiver(self) -> oneshot::Receiver<ExactResult<O::Buf>> {
+    let (sender, receiver) = oneshot::channel();
+
+    self.when_done(move |res| {
+      let _ = sender.send(res);
+    });
+
+    receiver
+  }
+}
+
+#[cfg(feature = "high")]
+impl<O: Step> Future for Exact<O> {
+  type Output = ExactResult<O::Buf>;
+
+  fn poll(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+  ) -> Poll<Self::Output> {
+    let this = &mut *self;
+    loop {
+      let (res, slice) = ready!(Pin::new(&mut this.progress).poll(cx));
+      match this.cursor.advance(res, slice.into_inner()) {
+        Ok(progress) => this.progress = progress,
+        Err(output) => return Poll::Ready(output),
+      }
+    }
+  }
+}
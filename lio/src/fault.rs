@@ -0,0 +1,209 @@
+//! Fault injection, see [`inject_faults`](crate::inject_faults).
+
+use std::{
+  cell::Cell,
+  collections::BTreeSet,
+  time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+
+use crate::{op::Operation, stats};
+
+/// What happens to an operation a [`FaultRule`] fires on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+  /// Fails with this errno, like `libc::EINTR`, without being submitted.
+  Error(i32),
+  /// Transfers at most this many bytes. Only affects reads, writes, sends and
+  /// receives, other operations run normally.
+  Short(usize),
+  /// Completes no earlier than this long after the kernel completes it.
+  Delay(Duration),
+  /// Completes one [`tick`](crate::tick) late, after operations that completed
+  /// in the next tick.
+  Reorder,
+  /// Runs as if the kernel didn't support the operation, through its fallback.
+  /// Only affects io_uring, other backends run the operation normally.
+  Unsupported,
+}
+
+#[derive(Debug, Clone)]
+enum Trigger {
+  Always,
+  Probability(f64),
+  /// Zero-based indices of the matching submissions to fire on.
+  Schedule(BTreeSet<u64>),
+}
+
+/// Applies a [`Fault`] to some submissions, picked by operation type and by
+/// probability or a fixed schedule.
+#[derive(Debug, Clone)]
+pub struct FaultRule {
+  fault: Fault,
+  op: Option<&'static str>,
+  trigger: Trigger,
+  /// Matching submissions seen so far.
+  seen: u64,
+}
+
+impl FaultRule {
+  /// Fires on every submission, of any operation type.
+  pub fn new(fault: Fault) -> Self {
+    Self { fault, op: None, trigger: Trigger::Always, seen: 0 }
+  }
+
+  /// Only matches operations called `name`, like `"Send"`. Names are the
+  /// same as in [`OpStats::name`](crate::OpStats::name).
+  pub fn op(mut self, name: &'static str) -> Self {
+    self.op = Some(name);
+    self
+  }
+
+  /// Fires on each matching submission with probability `p`, drawn from the
+  /// [`Faults::seed`] generator.
+  pub fn probability(mut self, p: f64) -> Self {
+    self.trigger = Trigger::Probability(p);
+    self
+  }
+
+  /// Fires on the matching submissions at these zero-based positions, so
+  /// `[0, 2]` fires on the first and third.
+  pub fn schedule(mut self, nth: impl IntoIterator<Item = u64>) -> Self {
+    self.trigger = Trigger::Schedule(nth.into_iter().collect());
+    self
+  }
+
+  fn fires(&mut self, name: &str, rng: &mut u64) -> bool {
+    if self.op.is_some_and(|op| op != name) {
+      return false;
+    }
+    let nth = self.seen;
+    self.seen += 1;
+
+    match &self.trigger {
+      Trigger::Always => true,
+      Trigger::Probability(p) => {
+        // Top 53 bits, as a float in [0, 1).
+        let sample = (next_random(rng) >> 11) as f64 / (1u64 << 53) as f64;
+        sample < *p
+      }
+      Trigger::Schedule(nth_set) => nth_set.contains(&nth),
+    }
+  }
+}
+
+/// Set of [`FaultRule`]s, installed with [`inject_faults`](crate::inject_faults).
+///
+/// Rules are checked in the order they were added, and the first one that
+/// fires wins.
+#[derive(Debug, Clone)]
+pub struct Faults {
+  rules: Vec<FaultRule>,
+  rng: u64,
+}
+
+impl Default for Faults {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl Faults {
+  pub fn new() -> Self {
+    Self { rules: Vec::new(), rng: 0x9E37_79B9_7F4A_7C15 }
+  }
+
+  /// Seeds the generator used by [`FaultRule::probability`].
+  pub fn seed(mut self, seed: u64) -> Self {
+    // Xorshift gets stuck on zero.
+    self.rng = seed | 1;
+    self
+  }
+
+  pub fn rule(mut self, rule: FaultRule) -> Self {
+    self.rules.push(rule);
+    self
+  }
+}
+
+fn next_random(rng: &mut u64) -> u64 {
+  *rng ^= *rng << 13;
+  *rng ^= *rng >> 7;
+  *rng ^= *rng << 17;
+  *rng
+}
+
+static FAULTS: Mutex<Option<Faults>> = Mutex::new(None);
+
+thread_local! {
+  /// Hold for the operation being submitted on this thread, picked up by its registration.
+  static HOLD: Cell<Option<Hold>> = const { Cell::new(None) };
+  /// If the operation being submitted on this thread runs through its fallback.
+  static UNSUPPORTED: Cell<bool> = const { Cell::new(false) };
+}
+
+pub(crate) fn install(faults: Option<Faults>) {
+  *FAULTS.lock() = faults;
+}
+
+/// Fault for the next submission of `T`, if any.
+pub(crate) fn pick<T: Operation>() -> Option<Fault> {
+  let mut faults = FAULTS.lock();
+  let Faults { rules, rng } = faults.as_mut()?;
+  let name = stats::op_name::<T>();
+  let fault = rules
+    .iter_mut()
+    .find_map(|rule| rule.fires(name, rng).then_some(rule.fault))?;
+
+  #[cfg(feature = "tracing")]
+  tracing::debug!(?fault, "injecting fault");
+
+  Some(fault)
+}
+
+/// Makes the backend run the next submission on this thread through its
+/// fallback, if `fault` asks for it.
+pub(crate) fn set_unsupported(fault: Option<Fault>) {
+  UNSUPPORTED.set(fault == Some(Fault::Unsupported));
+}
+
+pub(crate) fn take_unsupported() -> bool {
+  UNSUPPORTED.take()
+}
+
+/// How long a completed operation is kept from its waker or callback.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Hold {
+  Until(Instant),
+  Ticks(u32),
+}
+
+impl Hold {
+  /// Makes the next registration created on this thread hold its completion,
+  /// if `fault` asks for it.
+  pub(crate) fn set_for_next(fault: Option<Fault>) {
+    let hold = match fault {
+      Some(Fault::Delay(delay)) => Some(Hold::Until(Instant::now() + delay)),
+      Some(Fault::Reorder) => Some(Hold::Ticks(1)),
+      _ => None,
+    };
+    HOLD.set(hold);
+  }
+
+  pub(crate) fn take() -> Option<Hold> {
+    HOLD.take()
+  }
+
+  /// Called once per tick. Returns true once the completion can be released.
+  pub(crate) fn expired(&mut self) -> bool {
+    match self {
+      Hold::Until(deadline) => Instant::now() >= *deadline,
+      Hold::Ticks(0) => true,
+      Hold::Ticks(ticks) => {
+        *ticks -= 1;
+        false
+      }
+    }
+  }
+}
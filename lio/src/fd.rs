@@ -0,0 +1,41 @@
+//! File descriptors the operations take, see [`BorrowFd`].
+
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd, RawFd};
+
+trait Sealed {}
+
+/// A file descriptor an operation borrows: a [`RawFd`], a [`BorrowedFd`], or a
+/// reference to anything implementing [`AsFd`], like [`std::fs::File`].
+///
+/// Operations run past the call that submitted them, so the borrow doesn't
+/// keep the descriptor open. It must stay open until the operation completed,
+/// which the owned `fs::File` and `net::Socket` take care of.
+#[allow(private_bounds)]
+pub trait BorrowFd: Sealed {
+  /// The descriptor the operation uses.
+  fn raw_fd(&self) -> RawFd;
+}
+
+impl Sealed for RawFd {}
+
+impl BorrowFd for RawFd {
+  fn raw_fd(&self) -> RawFd {
+    *self
+  }
+}
+
+impl Sealed for BorrowedFd<'_> {}
+
+impl BorrowFd for BorrowedFd<'_> {
+  fn raw_fd(&self) -> RawFd {
+    self.as_raw_fd()
+  }
+}
+
+impl<T: AsFd + ?Sized> Sealed for &T {}
+
+impl<T: AsFd + ?Sized> BorrowFd for &T {
+  fn raw_fd(&self) -> RawFd {
+    self.as_fd().as_raw_fd()
+  }
+}
@@ -5,7 +5,10 @@
 //! ```sh
 //! make lio-cbuild
 //! ```
-//! `lio` dynamic library can be found at `target/release/liblio.{dylib,dll,so}`
+//! `lio` dynamic library can be found at `target/release/liblio.{dylib,dll,so}`,
+//! and its header at `lio/include/lio.h`.
+//!
+//! Call `lio_init` once before anything else, and `lio_exit` when done.
 //!
 //! ## Buffer Ownership Model
 //!
@@ -16,103 +19,388 @@
 //! 3. Do not access or free buffer until callback. UB otherwise.
 //! 4. Callback returns buffer, which the caller is responsible for de-allocating.
 //!
-//! Callbacks are guaranteed to be called. Cancellation is not supported.
+//! Callbacks are guaranteed to be called exactly once, also when cancelled.
+//!
+//! ## Cancellation
+//!
+//! Every submission returns a `LioHandle`, which `lio_cancel` takes. A
+//! cancelled operation completes with `-ECANCELED`, or with its usual result if
+//! it was too late, and hands its buffer back like any other completion.
+//!
+//! ## User Data
+//!
+//! Every function takes a `void *userdata` before its callback, which is passed
+//! back as the callback's first argument. lio never dereferences it, so it can
+//! point to anything identifying the request, like a per-request struct.
+//! Callbacks run on lio's background thread, so what it points to must be safe
+//! to use from there.
+//!
+//! An operation that completes without being submitted, like one started
+//! during `lio_exit` or one the kernel has no asynchronous version of, calls
+//! its callback on the calling thread before the `lio_*` function returns. So
+//! the caller mustn't hold a lock the callback takes.
+//!
+//! ## Polling
+//!
+//! For event loops that don't want callbacks on lio's background thread, start
+//! lio with `lio_init_polled` instead of `lio_init`. Nothing then happens in
+//! the background, and callbacks run inside `lio_poll` on the calling thread.
+//!
+//! Every `lio_*` submission has a `lio_*_tagged` variant, which takes a
+//! `uint64_t tag` instead of `userdata` and a callback. Its completion is
+//! queued, and [`lio_poll`] hands it back on the calling thread. Buffer
+//! ownership is the same as with callbacks. Tagged operations also work after
+//! `lio_init`, `lio_poll` then only waits for them.
+//!
+//! ```c
+//! lio_init_polled();
+//! lio_read_tagged(fd, buf, 1024, 0, 42);
+//!
+//! struct LioCompletion completions[16];
+//! int n = lio_poll(completions, 16, -1);
+//! for (int i = 0; i < n; i++) {
+//!     // completions[i].tag == 42
+//!     free(completions[i].buf);
+//! }
+//! ```
 //!
 //! ## Example
 //!
 //! ```c
-//! void write_done(int result, uint8_t *buf, size_t len) {
-//!     printf("Wrote %d bytes\n", result);
+//! void write_done(void *userdata, int result, uint8_t *buf, size_t len) {
+//!     struct request *req = userdata;
+//!     printf("Request %d wrote %d bytes\n", req->id, result);
 //!     free(buf);  // Required
 //! }
 //!
 //! uint8_t *buf = malloc(1024);
 //! memcpy(buf, "data", 4);
-//! lio_write(fd, buf, 1024, 0, write_done);
+//! lio_write(fd, buf, 1024, 0, req, write_done);
 //! // buf is now owned by lio
 //! ```
 #![allow(clippy::not_unsafe_ptr_arg_deref)]
 
-use std::ptr;
+use std::{
+  collections::BTreeMap,
+  ffi::{CStr, OsStr},
+  os::unix::{ffi::OsStrExt, process::ExitStatusExt},
+  path::Path,
+  ptr,
+  sync::Arc,
+  time::Duration,
+};
 
-use crate::op::net_utils::{self, sockaddr_to_socketaddr};
+use parking_lot::Mutex;
+
+use crate::{
+  Exact, ExactResult, MsgFlags, OpenFlags, OperationProgress, SocketAddress,
+  driver::Driver,
+  exact::{Step, Steps},
+  op::{OpenAt, Operation},
+};
+
+mod poll;
+pub use poll::*;
+
+/// Refers to a submitted operation, for [`lio_cancel`]. 0 if it can't be
+/// cancelled, because it completed right away or runs on a thread of its own.
+pub type LioHandle = u64;
+
+/// `when_done`, returning the [`LioHandle`] of the operation.
+trait OnDone<R> {
+  fn on_done<F>(self, callback: F) -> LioHandle
+  where
+    F: FnOnce(R) + Send + 'static;
+}
+
+impl<T> OnDone<T::Result> for OperationProgress<T>
+where
+  T: Operation + Send + 'static,
+{
+  fn on_done<F>(self, callback: F) -> LioHandle
+  where
+    F: FnOnce(T::Result) + Send + 'static,
+  {
+    let handle = self.id().map_or(0, |id| id + 1);
+    self.when_done(callback);
+    handle
+  }
+}
+
+/// Steps of the `_all`/`_exact` transfers in flight, by handle. Each step is a
+/// new operation, so the handle is only that of the first.
+static TRANSFERS: Mutex<BTreeMap<LioHandle, Arc<Steps>>> =
+  Mutex::new(BTreeMap::new());
+
+impl<O: Step> OnDone<ExactResult<O::Buf>> for Exact<O> {
+  fn on_done<F>(self, callback: F) -> LioHandle
+  where
+    F: FnOnce(ExactResult<O::Buf>) + Send + 'static,
+  {
+    let Some(handle) = self.id().map(|id| id + 1) else {
+      self.when_done(callback);
+      return 0;
+    };
+    let steps = Arc::new(Steps::default());
+    TRANSFERS.lock().insert(handle, steps.clone());
+    self.when_done_tracked(steps, move |res| {
+      TRANSFERS.lock().remove(&handle);
+      callback(res);
+    });
+    handle
+  }
+}
+
+/// The caller's `userdata`, handed back to its callback.
+#[derive(Clone, Copy)]
+struct UserData(*mut libc::c_void);
+
+// SAFETY: lio never dereferences it, making it usable from the callback's
+// thread is up to the caller, see the module docs.
+unsafe impl Send for UserData {}
+
+impl UserData {
+  /// Closures capturing `self.0` directly would capture the non-`Send` pointer.
+  fn get(self) -> *mut libc::c_void {
+    self.0
+  }
+}
+
+/// C strings can't contain nul bytes, so converting them to paths can't fail.
+const NUL_FREE: &str = "C string contains a nul byte";
+
+/// Borrows a nul-terminated C path.
+fn path<'a>(ptr: *const libc::c_char) -> &'a Path {
+  // SAFETY: The caller passes a valid nul-terminated string, which outlives the
+  // call that copies it.
+  Path::new(OsStr::from_bytes(unsafe { CStr::from_ptr(ptr) }.to_bytes()))
+}
 
 /// Shut down part of a full-duplex connection.
 ///
 /// # Parameters
 /// - `fd`: Socket file descriptor
 /// - `how`: How to shutdown (SHUT_RD=0, SHUT_WR=1, SHUT_RDWR=2)
-/// - `callback(result)`: Called when complete
-///   - `result`: 0 on success, or negative errno on error
+/// - `userdata`: Passed back to `callback` untouched
+/// - `callback(userdata, result)`: Called when complete
+///   - `result`: 0 on success, or negative errno on error (EINVAL for an unknown `how`)
 #[unsafe(no_mangle)]
 pub extern "C" fn lio_shutdown(
   fd: libc::c_int,
   how: i32,
-  callback: extern "C" fn(i32),
-) {
-  crate::shutdown(fd, how).when_done(move |res| {
+  userdata: *mut libc::c_void,
+  callback: extern "C" fn(*mut libc::c_void, i32),
+) -> LioHandle {
+  let userdata = UserData(userdata);
+  let how = match how {
+    libc::SHUT_RD => std::net::Shutdown::Read,
+    libc::SHUT_WR => std::net::Shutdown::Write,
+    libc::SHUT_RDWR => std::net::Shutdown::Both,
+    _ => {
+      callback(userdata.get(), -libc::EINVAL);
+      return 0;
+    }
+  };
+  crate::shutdown(fd, how).on_done(move |res| {
     let result_code = match res {
       Ok(_) => 0,
-      Err(err) => err.raw_os_error().unwrap_or(-1),
+      Err(err) => -err.raw_os_error().unwrap_or(1),
     };
-    callback(result_code);
-  });
+    callback(userdata.get(), result_code);
+  })
 }
 
-// TODO
-#[allow(unused)]
+/// Create a symbolic link.
+///
+/// # Parameters
+/// - `new_dir_fd`: Directory `linkpath` is relative to, or `AT_FDCWD`
+/// - `target`: Nul-terminated path the link points to
+/// - `linkpath`: Nul-terminated path of the new link
+/// - `userdata`: Passed back to `callback` untouched
+/// - `callback(userdata, result)`: Called when complete
+///   - `result`: 0 on success, or negative errno on error
 #[unsafe(no_mangle)]
 pub extern "C" fn lio_symlinkat(
   new_dir_fd: libc::c_int,
   target: *const libc::c_char,
   linkpath: *const libc::c_char,
-  callback: extern "C" fn(i32),
-) {
-  todo!();
-  // crate::symlinkat(new_dir_fd, target.).when_done(move |res| {
-  //   let result_code = match res {
-  //     Ok(_) => 0,
-  //     Err(err) => err.raw_os_error().unwrap_or(-1),
-  //   };
-  //   callback(result_code);
-  // });
+  userdata: *mut libc::c_void,
+  callback: extern "C" fn(*mut libc::c_void, i32),
+) -> LioHandle {
+  let userdata = UserData(userdata);
+  crate::symlinkat(new_dir_fd, path(target), path(linkpath))
+    .expect(NUL_FREE)
+    .on_done(move |res| {
+      let result_code = match res {
+        Ok(_) => 0,
+        Err(err) => -err.raw_os_error().unwrap_or(1),
+      };
+      callback(userdata.get(), result_code);
+    })
 }
 
-// TODO
-#[allow(unused)]
+/// Create a hard link.
+///
+/// # Parameters
+/// - `old_dir_fd`: Directory `old_path` is relative to, or `AT_FDCWD`
+/// - `old_path`: Nul-terminated path of the existing file
+/// - `new_dir_fd`: Directory `new_path` is relative to, or `AT_FDCWD`
+/// - `new_path`: Nul-terminated path of the new link
+/// - `userdata`: Passed back to `callback` untouched
+/// - `callback(userdata, result)`: Called when complete
+///   - `result`: 0 on success, or negative errno on error
 #[unsafe(no_mangle)]
 pub extern "C" fn lio_linkat(
   old_dir_fd: libc::c_int,
   old_path: *const libc::c_char,
   new_dir_fd: libc::c_int,
   new_path: *const libc::c_char,
-  callback: extern "C" fn(i32),
-) {
-  todo!();
-  // crate::linkat(new_dir_fd, target.).when_done(move |res| {
-  //   let result_code = match res {
-  //     Ok(_) => 0,
-  //     Err(err) => err.raw_os_error().unwrap_or(-1),
-  //   };
-  //   callback(result_code);
-  // });
+  userdata: *mut libc::c_void,
+  callback: extern "C" fn(*mut libc::c_void, i32),
+) -> LioHandle {
+  let userdata = UserData(userdata);
+  crate::linkat(old_dir_fd, path(old_path), new_dir_fd, path(new_path))
+    .expect(NUL_FREE)
+    .on_done(move |res| {
+      let result_code = match res {
+        Ok(_) => 0,
+        Err(err) => -err.raw_os_error().unwrap_or(1),
+      };
+      callback(userdata.get(), result_code);
+    })
+}
+
+/// Rename a file.
+///
+/// # Parameters
+/// - `old_dir_fd`: Directory `old_path` is relative to, or `AT_FDCWD`
+/// - `old_path`: Nul-terminated path of the existing file
+/// - `new_dir_fd`: Directory `new_path` is relative to, or `AT_FDCWD`
+/// - `new_path`: Nul-terminated new path
+/// - `userdata`: Passed back to `callback` untouched
+/// - `callback(userdata, result)`: Called when complete
+///   - `result`: 0 on success, or negative errno on error
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_renameat(
+  old_dir_fd: libc::c_int,
+  old_path: *const libc::c_char,
+  new_dir_fd: libc::c_int,
+  new_path: *const libc::c_char,
+  userdata: *mut libc::c_void,
+  callback: extern "C" fn(*mut libc::c_void, i32),
+) -> LioHandle {
+  let userdata = UserData(userdata);
+  crate::renameat(old_dir_fd, path(old_path), new_dir_fd, path(new_path))
+    .expect(NUL_FREE)
+    .on_done(move |res| {
+      let result_code = match res {
+        Ok(_) => 0,
+        Err(err) => -err.raw_os_error().unwrap_or(1),
+      };
+      callback(userdata.get(), result_code);
+    })
+}
+
+/// Remove a file.
+///
+/// # Parameters
+/// - `dir_fd`: Directory `pathname` is relative to, or `AT_FDCWD`
+/// - `pathname`: Nul-terminated path of the file
+/// - `userdata`: Passed back to `callback` untouched
+/// - `callback(userdata, result)`: Called when complete
+///   - `result`: 0 on success, or negative errno on error
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_unlinkat(
+  dir_fd: libc::c_int,
+  pathname: *const libc::c_char,
+  userdata: *mut libc::c_void,
+  callback: extern "C" fn(*mut libc::c_void, i32),
+) -> LioHandle {
+  let userdata = UserData(userdata);
+  crate::unlinkat(dir_fd, path(pathname)).expect(NUL_FREE).on_done(move |res| {
+    let result_code = match res {
+      Ok(_) => 0,
+      Err(err) => -err.raw_os_error().unwrap_or(1),
+    };
+    callback(userdata.get(), result_code);
+  })
 }
 
 /// Synchronize a file's in-core state with storage device.
 ///
 /// # Parameters
 /// - `fd`: File descriptor
-/// - `callback(result)`: Called when complete
+/// - `userdata`: Passed back to `callback` untouched
+/// - `callback(userdata, result)`: Called when complete
+///   - `result`: 0 on success, or negative errno on error
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_fsync(
+  fd: libc::c_int,
+  userdata: *mut libc::c_void,
+  callback: extern "C" fn(*mut libc::c_void, i32),
+) -> LioHandle {
+  let userdata = UserData(userdata);
+  crate::fsync(fd).on_done(move |res| {
+    let result_code = match res {
+      Ok(_) => 0,
+      Err(err) => -err.raw_os_error().unwrap_or(1),
+    };
+    callback(userdata.get(), result_code);
+  })
+}
+
+/// Synchronize a file's data with storage device, without metadata that isn't
+/// needed to read it back.
+///
+/// # Parameters
+/// - `fd`: File descriptor
+/// - `userdata`: Passed back to `callback` untouched
+/// - `callback(userdata, result)`: Called when complete
+///   - `result`: 0 on success, or negative errno on error
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_fdatasync(
+  fd: libc::c_int,
+  userdata: *mut libc::c_void,
+  callback: extern "C" fn(*mut libc::c_void, i32),
+) -> LioHandle {
+  let userdata = UserData(userdata);
+  crate::fdatasync(fd).on_done(move |res| {
+    let result_code = match res {
+      Ok(_) => 0,
+      Err(err) => -err.raw_os_error().unwrap_or(1),
+    };
+    callback(userdata.get(), result_code);
+  })
+}
+
+/// Synchronize a byte range of a file with storage device (Linux only).
+///
+/// # Parameters
+/// - `fd`: File descriptor
+/// - `offset`: Start of the range
+/// - `nbytes`: Length of the range, or 0 for up to the end of the file
+/// - `flags`: SYNC_FILE_RANGE_* flags
+/// - `userdata`: Passed back to `callback` untouched
+/// - `callback(userdata, result)`: Called when complete
 ///   - `result`: 0 on success, or negative errno on error
+#[cfg(linux)]
 #[unsafe(no_mangle)]
-pub extern "C" fn lio_fsync(fd: libc::c_int, callback: extern "C" fn(i32)) {
-  crate::fsync(fd).when_done(move |res| {
+pub extern "C" fn lio_sync_file_range(
+  fd: libc::c_int,
+  offset: u64,
+  nbytes: u32,
+  flags: u32,
+  userdata: *mut libc::c_void,
+  callback: extern "C" fn(*mut libc::c_void, i32),
+) -> LioHandle {
+  let userdata = UserData(userdata);
+  crate::sync_file_range(fd, offset, nbytes, flags).on_done(move |res| {
     let result_code = match res {
       Ok(_) => 0,
-      Err(err) => err.raw_os_error().unwrap_or(-1),
+      Err(err) => -err.raw_os_error().unwrap_or(1),
     };
-    callback(result_code);
-  });
+    callback(userdata.get(), result_code);
+  })
 }
 
 /// Write data to a file descriptor.
@@ -124,7 +412,8 @@ pub extern "C" fn lio_fsync(fd: libc::c_int, callback: extern "C" fn(i32)) {
 /// - `buf`: malloc-allocated buffer containing data to write
 /// - `buf_len`: Buffer length in bytes
 /// - `offset`: File offset, or -1 for current position
-/// - `callback(result, buf, len)`: Called when complete
+/// - `userdata`: Passed back to `callback` untouched
+/// - `callback(userdata, result, buf, len)`: Called when complete
 ///   - `result`: Bytes written, or negative errno on error
 ///   - `buf`: Original buffer pointer (must free)
 ///   - `len`: Original buffer length
@@ -134,8 +423,10 @@ pub extern "C" fn lio_write(
   buf: *mut u8,
   buf_len: usize,
   offset: i64,
-  callback: extern "C" fn(i32, *mut u8, usize),
-) {
+  userdata: *mut libc::c_void,
+  callback: extern "C" fn(*mut libc::c_void, i32, *mut u8, usize),
+) -> LioHandle {
+  let userdata = UserData(userdata);
   // SAFETY: Take ownership of the C buffer by reconstructing the Vec.
   // This is safe because:
   // 1. The C caller has allocated this with malloc (same allocator as Vec)
@@ -143,10 +434,10 @@ pub extern "C" fn lio_write(
   // 3. We'll return it via the callback where C can free it
   let buf_vec = unsafe { Vec::from_raw_parts(buf, buf_len, buf_len) };
 
-  crate::write(fd, buf_vec, offset).when_done(move |(res, mut buf)| {
+  crate::write(fd, buf_vec, offset).on_done(move |(res, mut buf)| {
     let result_code = match res {
       Ok(n) => n,
-      Err(err) => err.raw_os_error().unwrap_or(-1),
+      Err(err) => -err.raw_os_error().unwrap_or(1),
     };
 
     // Return buffer ownership to C caller
@@ -157,8 +448,8 @@ pub extern "C" fn lio_write(
     // C must now free this buffer to avoid memory leak.
     std::mem::forget(buf);
 
-    callback(result_code, buf_ptr, buf_len);
-  });
+    callback(userdata.get(), result_code, buf_ptr, buf_len);
+  })
 }
 
 /// Read data from a file descriptor.
@@ -170,7 +461,8 @@ pub extern "C" fn lio_write(
 /// - `buf`: malloc-allocated buffer to read into
 /// - `buf_len`: Buffer length in bytes
 /// - `offset`: File offset, or -1 for current position
-/// - `callback(result, buf, len)`: Called when complete
+/// - `userdata`: Passed back to `callback` untouched
+/// - `callback(userdata, result, buf, len)`: Called when complete
 ///   - `result`: Bytes read (check this, not `len`), 0 on EOF, or negative errno on error
 ///   - `buf`: Original buffer pointer containing data (must free)
 ///   - `len`: Original buffer length
@@ -180,31 +472,34 @@ pub extern "C" fn lio_read(
   buf: *mut u8,
   buf_len: usize,
   offset: i64,
-  callback: extern "C" fn(i32, *mut u8, usize),
-) {
+  userdata: *mut libc::c_void,
+  callback: extern "C" fn(*mut libc::c_void, i32, *mut u8, usize),
+) -> LioHandle {
+  let userdata = UserData(userdata);
   // SAFETY: Take ownership of the C buffer by reconstructing the Vec.
   // This is safe because:
   // 1. The C caller has allocated this with malloc (same allocator as Vec)
   // 2. We're taking exclusive ownership (C must not touch it anymore)
   // 3. We'll return it via the callback where C can free it
-  let buf_vec = unsafe { Vec::from_raw_parts(buf, buf_len, buf_len) };
+  // Empty, so all of it is spare capacity to read into.
+  let buf_vec = unsafe { Vec::from_raw_parts(buf, 0, buf_len) };
 
-  crate::read(fd, buf_vec, offset).when_done(move |(res, mut buf)| {
+  crate::read(fd, buf_vec, offset).on_done(move |(res, mut buf)| {
     let result_code = match res {
       Ok(n) => n,
-      Err(err) => err.raw_os_error().unwrap_or(-1),
+      Err(err) => -err.raw_os_error().unwrap_or(1),
     };
 
     // Return buffer ownership to C caller
     let buf_ptr = buf.as_mut_ptr();
-    let buf_len = buf.len();
+    let buf_len = buf.capacity();
 
     // SAFETY: Prevent Rust from freeing the buffer since we're giving ownership back to C.
     // C must now free this buffer to avoid memory leak.
     std::mem::forget(buf);
 
-    callback(result_code, buf_ptr, buf_len);
-  });
+    callback(userdata.get(), result_code, buf_ptr, buf_len);
+  })
 }
 
 /// Truncate a file to a specified length.
@@ -212,21 +507,24 @@ pub extern "C" fn lio_read(
 /// # Parameters
 /// - `fd`: File descriptor
 /// - `len`: New file length in bytes
-/// - `callback(result)`: Called when complete
+/// - `userdata`: Passed back to `callback` untouched
+/// - `callback(userdata, result)`: Called when complete
 ///   - `result`: 0 on success, or negative errno on error
 #[unsafe(no_mangle)]
 pub extern "C" fn lio_truncate(
   fd: libc::c_int,
   len: u64,
-  callback: extern "C" fn(i32),
-) {
-  crate::truncate(fd, len).when_done(move |res| {
+  userdata: *mut libc::c_void,
+  callback: extern "C" fn(*mut libc::c_void, i32),
+) -> LioHandle {
+  let userdata = UserData(userdata);
+  crate::truncate(fd, len).on_done(move |res| {
     let result_code = match res {
       Ok(_) => 0,
-      Err(err) => err.raw_os_error().unwrap_or(-1),
+      Err(err) => -err.raw_os_error().unwrap_or(1),
     };
-    callback(result_code);
-  });
+    callback(userdata.get(), result_code);
+  })
 }
 
 /// Create a socket.
@@ -235,78 +533,91 @@ pub extern "C" fn lio_truncate(
 /// - `domain`: Protocol family (AF_INET=2, AF_INET6=10, etc.)
 /// - `ty`: Socket type (SOCK_STREAM=1, SOCK_DGRAM=2, etc.)
 /// - `proto`: Protocol (IPPROTO_TCP=6, IPPROTO_UDP=17, or 0 for default)
-/// - `callback(result)`: Called when complete
+/// - `userdata`: Passed back to `callback` untouched
+/// - `callback(userdata, result)`: Called when complete
 ///   - `result`: Socket file descriptor on success, or negative errno on error
 #[unsafe(no_mangle)]
 pub extern "C" fn lio_socket(
   domain: i32,
   ty: i32,
   proto: i32,
-  callback: extern "C" fn(i32),
-) {
-  crate::socket(domain.into(), ty.into(), Some(proto.into())).when_done(
+  userdata: *mut libc::c_void,
+  callback: extern "C" fn(*mut libc::c_void, i32),
+) -> LioHandle {
+  let userdata = UserData(userdata);
+  crate::socket(domain.into(), ty.into(), Some(proto.into())).on_done(
     move |res| {
       let result_code = match res {
         Ok(fd) => fd,
-        Err(err) => err.raw_os_error().unwrap_or(-1),
+        Err(err) => -err.raw_os_error().unwrap_or(1),
       };
-      callback(result_code);
+      callback(userdata.get(), result_code);
     },
-  );
+  )
 }
 
 /// Bind a socket to an address.
 ///
 /// # Parameters
 /// - `fd`: Socket file descriptor
-/// - `sock`: Pointer to sockaddr structure (sockaddr_in or sockaddr_in6)
+/// - `sock`: Pointer to sockaddr structure (sockaddr_in, sockaddr_in6 or sockaddr_un)
 /// - `sock_len`: Pointer to size of sockaddr structure
-/// - `callback(result)`: Called when complete
-///   - `result`: 0 on success, or negative errno on error
+/// - `userdata`: Passed back to `callback` untouched
+/// - `callback(userdata, result)`: Called when complete
+///   - `result`: 0 on success, or negative errno on error (EINVAL for an unsupported address)
 #[unsafe(no_mangle)]
 pub extern "C" fn lio_bind(
   fd: libc::c_int,
   sock: *const libc::sockaddr,
   sock_len: *const libc::socklen_t,
-  callback: extern "C" fn(i32),
-) {
-  // TODO: fix unwrap.
-  let addr = sockaddr_to_socketaddr(sock, unsafe { *sock_len }).unwrap();
-  // TODO: Optimise
-  crate::bind(fd, addr).when_done(move |res| {
+  userdata: *mut libc::c_void,
+  callback: extern "C" fn(*mut libc::c_void, i32),
+) -> LioHandle {
+  let userdata = UserData(userdata);
+  // SAFETY: The caller passes a valid address of `*sock_len` bytes.
+  let Ok(addr) = (unsafe { SocketAddress::from_raw(sock, *sock_len) }) else {
+    callback(userdata.get(), -libc::EINVAL);
+    return 0;
+  };
+  crate::bind(fd, addr).on_done(move |res| {
     let result_code = match res {
       Ok(_) => 0,
-      Err(err) => err.raw_os_error().unwrap_or(-1),
+      Err(err) => -err.raw_os_error().unwrap_or(1),
     };
-    callback(result_code);
-  });
+    callback(userdata.get(), result_code);
+  })
 }
 
 /// Accept a connection on a socket.
 ///
 /// # Parameters
 /// - `fd`: Listening socket file descriptor
-/// - `callback(result, addr)`: Called when complete
+/// - `userdata`: Passed back to `callback` untouched
+/// - `callback(userdata, result, addr)`: Called when complete
 ///   - `result`: New socket file descriptor on success, or negative errno on error
-///   - `addr`: Pointer to peer address (null on error, caller must free on success)
+///   - `addr`: Pointer to peer address, a sockaddr_in, sockaddr_in6 or sockaddr_un
+///     depending on `ss_family` (null on error, caller must free on success)
 #[unsafe(no_mangle)]
 pub extern "C" fn lio_accept(
   fd: libc::c_int,
-  callback: extern "C" fn(i32, *const libc::sockaddr_storage),
-) {
-  // TODO: fix unwrap.
-  crate::accept(fd).when_done(move |res| {
+  userdata: *mut libc::c_void,
+  callback: extern "C" fn(
+    *mut libc::c_void,
+    i32,
+    *const libc::sockaddr_storage,
+  ),
+) -> LioHandle {
+  let userdata = UserData(userdata);
+  crate::accept(fd).on_done(move |res| {
     let (res, addr) = match res {
-      Ok((fd, addr)) => (
-        fd,
-        Box::into_raw(Box::new(net_utils::std_socketaddr_into_libc(addr)))
-          as *const _,
-      ),
-      Err(err) => (err.raw_os_error().unwrap_or(-1), ptr::null()),
+      Ok((fd, addr)) => {
+        (fd, Box::into_raw(Box::new(addr.to_libc().0)) as *const _)
+      }
+      Err(err) => (-err.raw_os_error().unwrap_or(1), ptr::null()),
     };
 
-    callback(res, addr)
-  });
+    callback(userdata.get(), res, addr)
+  })
 }
 
 /// Listen for connections on a socket.
@@ -314,21 +625,56 @@ pub extern "C" fn lio_accept(
 /// # Parameters
 /// - `fd`: Socket file descriptor
 /// - `backlog`: Maximum length of pending connections queue
-/// - `callback(result)`: Called when complete
+/// - `userdata`: Passed back to `callback` untouched
+/// - `callback(userdata, result)`: Called when complete
 ///   - `result`: 0 on success, or negative errno on error
 #[unsafe(no_mangle)]
 pub extern "C" fn lio_listen(
   fd: libc::c_int,
   backlog: i32,
-  callback: extern "C" fn(i32),
-) {
-  crate::listen(fd, backlog).when_done(move |res| {
+  userdata: *mut libc::c_void,
+  callback: extern "C" fn(*mut libc::c_void, i32),
+) -> LioHandle {
+  let userdata = UserData(userdata);
+  crate::listen(fd, backlog).on_done(move |res| {
     let result_code = match res {
       Ok(_) => 0,
-      Err(err) => err.raw_os_error().unwrap_or(-1),
+      Err(err) => -err.raw_os_error().unwrap_or(1),
     };
-    callback(result_code);
-  });
+    callback(userdata.get(), result_code);
+  })
+}
+
+/// Connect a socket to an address.
+///
+/// # Parameters
+/// - `fd`: Socket file descriptor
+/// - `sock`: Pointer to sockaddr structure (sockaddr_in, sockaddr_in6 or sockaddr_un)
+/// - `sock_len`: Size of sockaddr structure
+/// - `userdata`: Passed back to `callback` untouched
+/// - `callback(userdata, result)`: Called when complete
+///   - `result`: 0 on success, or negative errno on error (EINVAL for an unsupported address)
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_connect(
+  fd: libc::c_int,
+  sock: *const libc::sockaddr,
+  sock_len: libc::socklen_t,
+  userdata: *mut libc::c_void,
+  callback: extern "C" fn(*mut libc::c_void, i32),
+) -> LioHandle {
+  let userdata = UserData(userdata);
+  // SAFETY: The caller passes a valid address of `sock_len` bytes.
+  let Ok(addr) = (unsafe { SocketAddress::from_raw(sock, sock_len) }) else {
+    callback(userdata.get(), -libc::EINVAL);
+    return 0;
+  };
+  crate::connect(fd, addr).on_done(move |res| {
+    let result_code = match res {
+      Ok(_) => 0,
+      Err(err) => -err.raw_os_error().unwrap_or(1),
+    };
+    callback(userdata.get(), result_code);
+  })
 }
 
 /// Send data to a socket.
@@ -340,7 +686,8 @@ pub extern "C" fn lio_listen(
 /// - `buf`: malloc-allocated buffer containing data to send
 /// - `buf_len`: Buffer length in bytes
 /// - `flags`: Send flags (e.g., MSG_DONTWAIT, MSG_NOSIGNAL)
-/// - `callback(result, buf, len)`: Called when complete
+/// - `userdata`: Passed back to `callback` untouched
+/// - `callback(userdata, result, buf, len)`: Called when complete
 ///   - `result`: Bytes sent, or negative errno on error
 ///   - `buf`: Original buffer pointer (must free)
 ///   - `len`: Original buffer length
@@ -350,8 +697,10 @@ pub extern "C" fn lio_send(
   buf: *mut u8,
   buf_len: usize,
   flags: i32,
-  callback: extern "C" fn(i32, *mut u8, usize),
-) {
+  userdata: *mut libc::c_void,
+  callback: extern "C" fn(*mut libc::c_void, i32, *mut u8, usize),
+) -> LioHandle {
+  let userdata = UserData(userdata);
   // SAFETY: Take ownership of the C buffer by reconstructing the Vec.
   // This is safe because:
   // 1. The C caller has allocated this with malloc (same allocator as Vec)
@@ -359,10 +708,11 @@ pub extern "C" fn lio_send(
   // 3. We'll return it via the callback where C can free it
   let buf_vec = unsafe { Vec::from_raw_parts(buf, buf_len, buf_len) };
 
-  crate::send(fd, buf_vec, Some(flags)).when_done(move |(res, mut buf)| {
+  let flags = MsgFlags::from_bits(flags);
+  crate::send(fd, buf_vec, Some(flags)).on_done(move |(res, mut buf)| {
     let result_code = match res {
       Ok(n) => n,
-      Err(err) => err.raw_os_error().unwrap_or(-1),
+      Err(err) => -err.raw_os_error().unwrap_or(1),
     };
 
     // Return buffer ownership to C caller
@@ -373,8 +723,8 @@ pub extern "C" fn lio_send(
     // C must now free this buffer to avoid memory leak.
     std::mem::forget(buf);
 
-    callback(result_code, buf_ptr, buf_len);
-  });
+    callback(userdata.get(), result_code, buf_ptr, buf_len);
+  })
 }
 
 /// Receive data from a socket.
@@ -386,7 +736,8 @@ pub extern "C" fn lio_send(
 /// - `buf`: malloc-allocated buffer to receive into
 /// - `buf_len`: Buffer length in bytes
 /// - `flags`: Receive flags (e.g., MSG_PEEK, MSG_WAITALL)
-/// - `callback(result, buf, len)`: Called when complete
+/// - `userdata`: Passed back to `callback` untouched
+/// - `callback(userdata, result, buf, len)`: Called when complete
 ///   - `result`: Bytes received (check this, not `len`), or negative errno on error
 ///   - `buf`: Original buffer pointer containing data (must free)
 ///   - `len`: Original buffer length
@@ -396,51 +747,425 @@ pub extern "C" fn lio_recv(
   buf: *mut u8,
   buf_len: usize,
   flags: i32,
-  callback: extern "C" fn(i32, *mut u8, usize),
-) {
+  userdata: *mut libc::c_void,
+  callback: extern "C" fn(*mut libc::c_void, i32, *mut u8, usize),
+) -> LioHandle {
+  let userdata = UserData(userdata);
   // SAFETY: Take ownership of the C buffer by reconstructing the Vec.
   // This is safe because:
   // 1. The C caller has allocated this with malloc (same allocator as Vec)
   // 2. We're taking exclusive ownership (C must not touch it anymore)
   // 3. We'll return it via the callback where C can free it
-  let buf_vec = unsafe { Vec::from_raw_parts(buf, buf_len, buf_len) };
+  // Empty, so all of it is spare capacity to read into.
+  let buf_vec = unsafe { Vec::from_raw_parts(buf, 0, buf_len) };
 
-  crate::recv(fd, buf_vec, Some(flags)).when_done(move |(res, mut buf)| {
+  let flags = MsgFlags::from_bits(flags);
+  crate::recv(fd, buf_vec, Some(flags)).on_done(move |(res, mut buf)| {
     let result_code = match res {
       Ok(n) => n,
-      Err(err) => err.raw_os_error().unwrap_or(-1),
+      Err(err) => -err.raw_os_error().unwrap_or(1),
     };
 
     // Return buffer ownership to C caller
     let buf_ptr = buf.as_mut_ptr();
-    let buf_len = buf.len();
+    let buf_len = buf.capacity();
 
     // SAFETY: Prevent Rust from freeing the buffer since we're giving ownership back to C.
     // C must now free this buffer to avoid memory leak.
     std::mem::forget(buf);
 
-    callback(result_code, buf_ptr, buf_len);
-  });
+    callback(userdata.get(), result_code, buf_ptr, buf_len);
+  })
+}
+
+/// Result code for the `_all`/`_exact` variants: total bytes on success, bytes
+/// transferred if the file or connection ended first, or negative errno.
+fn exact_result_code(res: Result<usize, crate::TransferError>) -> i32 {
+  match res {
+    Ok(n) => n as i32,
+    Err(err) => match err.error.raw_os_error() {
+      Some(errno) => -errno,
+      None => err.transferred as i32,
+    },
+  }
+}
+
+/// Hands the buffer back to C, see [`lio_write`].
+fn exact_callback(
+  userdata: UserData,
+  callback: extern "C" fn(*mut libc::c_void, i32, *mut u8, usize),
+) -> impl FnOnce(crate::ExactResult<Vec<u8>>) + Send + 'static {
+  move |(res, buf)| {
+    let mut buf = std::mem::ManuallyDrop::new(buf);
+    callback(
+      userdata.get(),
+      exact_result_code(res),
+      buf.as_mut_ptr(),
+      buf.capacity(),
+    );
+  }
+}
+
+/// Write all of a buffer, resubmitting after short writes.
+///
+/// Same as [`lio_write`], except for the result.
+///
+/// # Parameters
+/// - `userdata`: Passed back to `callback` untouched
+/// - `callback(userdata, result, buf, len)`: Called when all of `buf` is written, or on error
+///   - `result`: `buf_len` on success, bytes written if a write made no progress,
+///     or negative errno on error
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_write_all(
+  fd: libc::c_int,
+  buf: *mut u8,
+  buf_len: usize,
+  offset: i64,
+  userdata: *mut libc::c_void,
+  callback: extern "C" fn(*mut libc::c_void, i32, *mut u8, usize),
+) -> LioHandle {
+  let userdata = UserData(userdata);
+  // SAFETY: See lio_write.
+  let buf_vec = unsafe { Vec::from_raw_parts(buf, buf_len, buf_len) };
+  crate::write_all(fd, buf_vec, offset)
+    .on_done(exact_callback(userdata, callback))
+}
+
+/// Fill a whole buffer, resubmitting after short reads.
+///
+/// Same as [`lio_read`], except for the result.
+///
+/// # Parameters
+/// - `userdata`: Passed back to `callback` untouched
+/// - `callback(userdata, result, buf, len)`: Called when `buf` is full, or on error
+///   - `result`: `buf_len` on success, bytes read if the file ended first,
+///     or negative errno on error
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_read_exact(
+  fd: libc::c_int,
+  buf: *mut u8,
+  buf_len: usize,
+  offset: i64,
+  userdata: *mut libc::c_void,
+  callback: extern "C" fn(*mut libc::c_void, i32, *mut u8, usize),
+) -> LioHandle {
+  let userdata = UserData(userdata);
+  // SAFETY: See lio_read.
+  // Empty, so all of it is spare capacity to read into.
+  let buf_vec = unsafe { Vec::from_raw_parts(buf, 0, buf_len) };
+  crate::read_exact(fd, buf_vec, offset)
+    .on_done(exact_callback(userdata, callback))
+}
+
+/// Send all of a buffer, resubmitting after short sends.
+///
+/// Same as [`lio_send`] without flags, except for the result.
+///
+/// # Parameters
+/// - `userdata`: Passed back to `callback` untouched
+/// - `callback(userdata, result, buf, len)`: Called when all of `buf` is sent, or on error
+///   - `result`: `buf_len` on success, or negative errno on error
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_send_all(
+  fd: libc::c_int,
+  buf: *mut u8,
+  buf_len: usize,
+  userdata: *mut libc::c_void,
+  callback: extern "C" fn(*mut libc::c_void, i32, *mut u8, usize),
+) -> LioHandle {
+  let userdata = UserData(userdata);
+  // SAFETY: See lio_send.
+  let buf_vec = unsafe { Vec::from_raw_parts(buf, buf_len, buf_len) };
+  crate::send_all(fd, buf_vec).on_done(exact_callback(userdata, callback))
+}
+
+/// Fill a whole buffer from a socket, resubmitting after short receives.
+///
+/// Same as [`lio_recv`] without flags, except for the result.
+///
+/// # Parameters
+/// - `userdata`: Passed back to `callback` untouched
+/// - `callback(userdata, result, buf, len)`: Called when `buf` is full, or on error
+///   - `result`: `buf_len` on success, bytes received if the peer shut down
+///     first, or negative errno on error
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_recv_exact(
+  fd: libc::c_int,
+  buf: *mut u8,
+  buf_len: usize,
+  userdata: *mut libc::c_void,
+  callback: extern "C" fn(*mut libc::c_void, i32, *mut u8, usize),
+) -> LioHandle {
+  let userdata = UserData(userdata);
+  // SAFETY: See lio_recv.
+  // Empty, so all of it is spare capacity to read into.
+  let buf_vec = unsafe { Vec::from_raw_parts(buf, 0, buf_len) };
+  crate::recv_exact(fd, buf_vec).on_done(exact_callback(userdata, callback))
 }
 
 /// Close a file descriptor.
 ///
 /// # Parameters
 /// - `fd`: File descriptor to close
-/// - `callback(result)`: Called when complete
+/// - `userdata`: Passed back to `callback` untouched
+/// - `callback(userdata, result)`: Called when complete
 ///   - `result`: 0 on success, or negative errno on error
 #[unsafe(no_mangle)]
-pub extern "C" fn lio_close(fd: libc::c_int, callback: extern "C" fn(i32)) {
-  crate::close(fd).when_done(move |res| {
+pub extern "C" fn lio_close(
+  fd: libc::c_int,
+  userdata: *mut libc::c_void,
+  callback: extern "C" fn(*mut libc::c_void, i32),
+) -> LioHandle {
+  let userdata = UserData(userdata);
+  crate::close(fd).on_done(move |res| {
+    let result_code = match res {
+      Ok(_) => 0,
+      Err(err) => -err.raw_os_error().unwrap_or(1),
+    };
+    callback(userdata.get(), result_code);
+  })
+}
+
+/// Open a file.
+///
+/// # Parameters
+/// - `dir_fd`: Directory `pathname` is relative to, or `AT_FDCWD`
+/// - `pathname`: Nul-terminated path of the file
+/// - `flags`: Open flags (e.g., O_RDONLY, O_CREAT)
+/// - `mode`: Permissions of a newly created file, ignored without O_CREAT or O_TMPFILE
+/// - `userdata`: Passed back to `callback` untouched
+/// - `callback(userdata, result)`: Called when complete
+///   - `result`: File descriptor on success, or negative errno on error
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_openat(
+  dir_fd: libc::c_int,
+  pathname: *const libc::c_char,
+  flags: i32,
+  mode: libc::mode_t,
+  userdata: *mut libc::c_void,
+  callback: extern "C" fn(*mut libc::c_void, i32),
+) -> LioHandle {
+  let userdata = UserData(userdata);
+  // SAFETY: The caller passes a valid nul-terminated string.
+  let pathname = unsafe { CStr::from_ptr(pathname) }.to_owned();
+  Driver::submit(OpenAt::with_mode(
+    dir_fd,
+    pathname,
+    OpenFlags::from_bits(flags),
+    mode,
+  ))
+  .on_done(move |res| {
+    let result_code = match res {
+      Ok(fd) => fd,
+      Err(err) => -err.raw_os_error().unwrap_or(1),
+    };
+    callback(userdata.get(), result_code);
+  })
+}
+
+/// Open a file, with control over path resolution (Linux only).
+///
+/// # Parameters
+/// - `dir_fd`: Directory `pathname` is relative to, or `AT_FDCWD`
+/// - `pathname`: Nul-terminated path of the file
+/// - `flags`: Open flags (e.g., O_RDONLY, O_CREAT)
+/// - `mode`: Permissions of a newly created file, only allowed with O_CREAT or O_TMPFILE
+/// - `resolve`: RESOLVE_* flags (e.g., RESOLVE_BENEATH), or 0
+/// - `userdata`: Passed back to `callback` untouched
+/// - `callback(userdata, result)`: Called when complete
+///   - `result`: File descriptor on success, or negative errno on error
+#[cfg(linux)]
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_openat2(
+  dir_fd: libc::c_int,
+  pathname: *const libc::c_char,
+  flags: i32,
+  mode: libc::mode_t,
+  resolve: u64,
+  userdata: *mut libc::c_void,
+  callback: extern "C" fn(*mut libc::c_void, i32),
+) -> LioHandle {
+  let userdata = UserData(userdata);
+  let how = crate::op::OpenHow::new(OpenFlags::from_bits(flags))
+    .mode(mode)
+    .resolve(crate::op::Resolve::from_bits(resolve));
+  crate::openat2(dir_fd, path(pathname), how).expect(NUL_FREE).on_done(
+    move |res| {
+      let result_code = match res {
+        Ok(fd) => fd,
+        Err(err) => -err.raw_os_error().unwrap_or(1),
+      };
+      callback(userdata.get(), result_code);
+    },
+  )
+}
+
+/// Duplicate pipe content without consuming it (Linux only).
+///
+/// # Parameters
+/// - `fd_in`: Pipe to read from
+/// - `fd_out`: Pipe to write to
+/// - `size`: Maximum number of bytes to duplicate
+/// - `userdata`: Passed back to `callback` untouched
+/// - `callback(userdata, result)`: Called when complete
+///   - `result`: Bytes duplicated, or negative errno on error
+#[cfg(linux)]
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_tee(
+  fd_in: libc::c_int,
+  fd_out: libc::c_int,
+  size: u32,
+  userdata: *mut libc::c_void,
+  callback: extern "C" fn(*mut libc::c_void, i32),
+) -> LioHandle {
+  let userdata = UserData(userdata);
+  crate::tee(fd_in, fd_out, size).on_done(move |res| {
+    let result_code = match res {
+      Ok(n) => n,
+      Err(err) => -err.raw_os_error().unwrap_or(1),
+    };
+    callback(userdata.get(), result_code);
+  })
+}
+
+/// Wait for a duration (Linux only).
+///
+/// # Parameters
+/// - `duration_ms`: Milliseconds to wait
+/// - `userdata`: Passed back to `callback` untouched
+/// - `callback(userdata, result)`: Called when complete
+///   - `result`: 0 once the duration has passed, or negative errno on error
+#[cfg(linux)]
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_timeout(
+  duration_ms: u64,
+  userdata: *mut libc::c_void,
+  callback: extern "C" fn(*mut libc::c_void, i32),
+) -> LioHandle {
+  let userdata = UserData(userdata);
+  crate::timeout(Duration::from_millis(duration_ms)).on_done(move |res| {
+    let result_code = match res {
+      Ok(_) => 0,
+      Err(err) => -err.raw_os_error().unwrap_or(1),
+    };
+    callback(userdata.get(), result_code);
+  })
+}
+
+/// Wait until a futex word is woken (Linux only).
+///
+/// Completes immediately if `*futex` isn't `expected`, so re-check the value in a loop.
+///
+/// # Parameters
+/// - `futex`: 4-byte aligned futex word, must stay valid until `callback` is called
+/// - `expected`: Value `*futex` is expected to hold
+/// - `userdata`: Passed back to `callback` untouched
+/// - `callback(userdata, result)`: Called when complete
+///   - `result`: 0 on wake-up or if `*futex` isn't `expected`, or negative errno on error
+#[cfg(linux)]
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_futex_wait(
+  futex: *const u32,
+  expected: u32,
+  userdata: *mut libc::c_void,
+  callback: extern "C" fn(*mut libc::c_void, i32),
+) -> LioHandle {
+  let userdata = UserData(userdata);
+  // SAFETY: The caller keeps it alive until the callback.
+  unsafe { crate::futex_wait(futex, expected) }.on_done(move |res| {
     let result_code = match res {
       Ok(_) => 0,
-      Err(err) => err.raw_os_error().unwrap_or(-1),
+      Err(err) => -err.raw_os_error().unwrap_or(1),
+    };
+    callback(userdata.get(), result_code);
+  })
+}
+
+/// Wake waiters of a futex word (Linux only).
+///
+/// # Parameters
+/// - `futex`: 4-byte aligned futex word, must stay valid until `callback` is called
+/// - `count`: Maximum number of waiters to wake
+/// - `userdata`: Passed back to `callback` untouched
+/// - `callback(userdata, result)`: Called when complete
+///   - `result`: Number of waiters woken, or negative errno on error
+#[cfg(linux)]
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_futex_wake(
+  futex: *const u32,
+  count: u32,
+  userdata: *mut libc::c_void,
+  callback: extern "C" fn(*mut libc::c_void, i32),
+) -> LioHandle {
+  let userdata = UserData(userdata);
+  // SAFETY: See lio_futex_wait.
+  unsafe { crate::futex_wake(futex, count) }.on_done(move |res| {
+    let result_code = match res {
+      Ok(woken) => woken as i32,
+      Err(err) => -err.raw_os_error().unwrap_or(1),
     };
-    callback(result_code);
-  });
+    callback(userdata.get(), result_code);
+  })
 }
 
-// openat: TODO
+/// Wait for a child process to exit, and reap it.
+///
+/// # Parameters
+/// - `pid`: Process id of a child of this process
+/// - `userdata`: Passed back to `callback` untouched
+/// - `callback(userdata, result)`: Called when complete
+///   - `result`: Wait status for WIFEXITED/WEXITSTATUS and friends, or negative errno on error
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_waitid(
+  pid: u32,
+  userdata: *mut libc::c_void,
+  callback: extern "C" fn(*mut libc::c_void, i32),
+) -> LioHandle {
+  let userdata = UserData(userdata);
+  crate::waitid(pid).on_done(move |res| {
+    let result_code = match res {
+      Ok(status) => status.into_raw(),
+      Err(err) => -err.raw_os_error().unwrap_or(1),
+    };
+    callback(userdata.get(), result_code);
+  })
+}
+
+/// Cancel an operation.
+///
+/// Its callback is still called exactly once, with `-ECANCELED` if it was
+/// cancelled in time, and otherwise with its usual result.
+///
+/// # Parameters
+/// - `handle`: Returned by the operation's submission
+///
+/// # Returns
+/// 0 if cancellation was requested, or `-ENOENT` if the operation already
+/// completed or can't be cancelled.
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_cancel(handle: LioHandle) -> i32 {
+  let Some(id) = handle.checked_sub(1) else {
+    return -libc::ENOENT;
+  };
+  let steps = TRANSFERS.lock().get(&handle).cloned();
+  let cancelled = match steps {
+    Some(steps) => {
+      steps.cancel();
+      true
+    }
+    None => Driver::get().cancel(id),
+  };
+  if cancelled { 0 } else { -libc::ENOENT }
+}
+
+/// Start the lio runtime, and a background thread running the callbacks.
+///
+/// Must be called once before any other function.
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_init() {
+  Driver::init();
+  Driver::get().spawn_ev();
+}
 
 /// Shutdown the lio runtime and wait for all pending operations to complete.
 ///
@@ -0,0 +1,493 @@
+//! Pull-based completions, see the module docs of `ffi`.
+
+use std::{
+  collections::VecDeque,
+  time::{Duration, Instant},
+};
+
+use parking_lot::{Condvar, Mutex};
+
+use super::*;
+use crate::driver::Driver;
+
+/// A completed tagged operation, filled in by [`lio_poll`].
+#[repr(C)]
+#[derive(Debug)]
+pub struct LioCompletion {
+  /// Tag the operation was submitted with.
+  pub tag: u64,
+  /// Same as the `result` the callback would get.
+  pub result: i32,
+  /// The buffer handed back, which the caller must free, or null. For
+  /// `lio_accept_tagged`, the peer address as a `struct sockaddr_storage`.
+  pub buf: *mut u8,
+  /// Length of `buf`.
+  pub len: usize,
+}
+
+// SAFETY: `buf` is owned by the completion, and only handed to C.
+unsafe impl Send for LioCompletion {}
+
+static COMPLETIONS: Mutex<VecDeque<LioCompletion>> =
+  Mutex::new(VecDeque::new());
+static QUEUED: Condvar = Condvar::new();
+/// Held around the polled ticks, as the backend expects one tick at a time.
+static TICKING: Mutex<()> = Mutex::new(());
+
+fn push(tag: *mut libc::c_void, result: i32, buf: *mut u8, len: usize) {
+  // SAFETY: Made by `boxed`, and each callback runs once.
+  let tag = *unsafe { Box::from_raw(tag.cast::<u64>()) };
+  COMPLETIONS.lock().push_back(LioCompletion { tag, result, buf, len });
+  QUEUED.notify_all();
+}
+
+/// `userdata` carrying the tag to the `queue_*` callbacks.
+fn boxed(tag: u64) -> *mut libc::c_void {
+  Box::into_raw(Box::new(tag)).cast()
+}
+
+extern "C" fn queue(tag: *mut libc::c_void, result: i32) {
+  push(tag, result, ptr::null_mut(), 0);
+}
+
+extern "C" fn queue_buf(
+  tag: *mut libc::c_void,
+  result: i32,
+  buf: *mut u8,
+  len: usize,
+) {
+  push(tag, result, buf, len);
+}
+
+extern "C" fn queue_accept(
+  tag: *mut libc::c_void,
+  result: i32,
+  addr: *const libc::sockaddr_storage,
+) {
+  let len = if addr.is_null() {
+    0
+  } else {
+    std::mem::size_of::<libc::sockaddr_storage>()
+  };
+  push(tag, result, addr.cast_mut().cast(), len);
+}
+
+/// Move up to `max` queued completions into `out`, returning how many.
+fn drain(out: *mut LioCompletion, max: usize) -> usize {
+  let mut queue = COMPLETIONS.lock();
+  let n = queue.len().min(max);
+  for (i, completion) in queue.drain(..n).enumerate() {
+    // SAFETY: The caller passes room for `max` completions.
+    unsafe { out.add(i).write(completion) };
+  }
+  n
+}
+
+/// Start the lio runtime without a background thread. Completions, and the
+/// callbacks of non-tagged operations, then only happen inside [`lio_poll`].
+///
+/// Use instead of `lio_init`.
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_init_polled() {
+  Driver::init();
+}
+
+/// Wait for tagged operations to complete.
+///
+/// Without a background thread, see [`lio_init_polled`], this also drives lio,
+/// and runs the callbacks of non-tagged operations on the calling thread.
+///
+/// # Parameters
+/// - `completions`: Array with room for `max` completions
+/// - `max`: Maximum number of completions to return
+/// - `timeout_ms`: Milliseconds to wait for a first completion, 0 to not wait,
+///   or -1 to wait indefinitely
+///
+/// # Returns
+/// Number of completions written to `completions`, 0 on timeout, or negative
+/// errno on error.
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_poll(
+  completions: *mut LioCompletion,
+  max: usize,
+  timeout_ms: i32,
+) -> i32 {
+  if completions.is_null() || max == 0 {
+    return -libc::EINVAL;
+  }
+  let timeout = u64::try_from(timeout_ms).ok().map(Duration::from_millis);
+  let driver = Driver::get();
+
+  if driver.has_worker() {
+    let mut queue = COMPLETIONS.lock();
+    match timeout {
+      Some(timeout) => {
+        QUEUED.wait_while_for(&mut queue, |queue| queue.is_empty(), timeout);
+      }
+      None => QUEUED.wait_while(&mut queue, |queue| queue.is_empty()),
+    }
+    drop(queue);
+    return drain(completions, max) as i32;
+  }
+
+  let deadline = timeout.map(|timeout| Instant::now() + timeout);
+  let _ticking = match deadline {
+    Some(deadline) => match TICKING.try_lock_until(deadline) {
+      Some(guard) => guard,
+      // Another thread is ticking, and may have queued our completions.
+      None => return drain(completions, max) as i32,
+    },
+    None => TICKING.lock(),
+  };
+
+  driver.tick(false);
+  let n = drain(completions, max);
+  if n > 0 || timeout == Some(Duration::ZERO) {
+    return n as i32;
+  }
+
+  #[cfg(linux)]
+  {
+    // Armed so waiting in the backend ends at the deadline, and cancelled on
+    // return so it doesn't outlive the call.
+    let timer = deadline.and_then(|deadline| {
+      let progress =
+        crate::timeout(deadline.saturating_duration_since(Instant::now()));
+      let id = progress.id();
+      progress.when_done(|_| {});
+      id
+    });
+    loop {
+      driver.tick(true);
+      let n = drain(completions, max);
+      if n > 0 || deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+        if let Some(id) = timer {
+          driver.cancel(id);
+        }
+        return n as i32;
+      }
+    }
+  }
+
+  #[cfg(not(linux))]
+  loop {
+    driver.tick(false);
+    let n = drain(completions, max);
+    if n > 0 || deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+      return n as i32;
+    }
+    std::thread::sleep(Duration::from_millis(1));
+  }
+}
+
+/// Same as [`lio_shutdown`], but the completion is queued for [`lio_poll`] under `tag`.
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_shutdown_tagged(
+  fd: libc::c_int,
+  how: i32,
+  tag: u64,
+) -> LioHandle {
+  lio_shutdown(fd, how, boxed(tag), queue)
+}
+
+/// Same as [`lio_symlinkat`], but the completion is queued for [`lio_poll`] under `tag`.
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_symlinkat_tagged(
+  new_dir_fd: libc::c_int,
+  target: *const libc::c_char,
+  linkpath: *const libc::c_char,
+  tag: u64,
+) -> LioHandle {
+  lio_symlinkat(new_dir_fd, target, linkpath, boxed(tag), queue)
+}
+
+/// Same as [`lio_linkat`], but the completion is queued for [`lio_poll`] under `tag`.
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_linkat_tagged(
+  old_dir_fd: libc::c_int,
+  old_path: *const libc::c_char,
+  new_dir_fd: libc::c_int,
+  new_path: *const libc::c_char,
+  tag: u64,
+) -> LioHandle {
+  lio_linkat(old_dir_fd, old_path, new_dir_fd, new_path, boxed(tag), queue)
+}
+
+/// Same as [`lio_renameat`], but the completion is queued for [`lio_poll`] under `tag`.
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_renameat_tagged(
+  old_dir_fd: libc::c_int,
+  old_path: *const libc::c_char,
+  new_dir_fd: libc::c_int,
+  new_path: *const libc::c_char,
+  tag: u64,
+) -> LioHandle {
+  lio_renameat(old_dir_fd, old_path, new_dir_fd, new_path, boxed(tag), queue)
+}
+
+/// Same as [`lio_unlinkat`], but the completion is queued for [`lio_poll`] under `tag`.
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_unlinkat_tagged(
+  dir_fd: libc::c_int,
+  pathname: *const libc::c_char,
+  tag: u64,
+) -> LioHandle {
+  lio_unlinkat(dir_fd, pathname, boxed(tag), queue)
+}
+
+/// Same as [`lio_fsync`], but the completion is queued for [`lio_poll`] under `tag`.
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_fsync_tagged(fd: libc::c_int, tag: u64) -> LioHandle {
+  lio_fsync(fd, boxed(tag), queue)
+}
+
+/// Same as [`lio_fdatasync`], but the completion is queued for [`lio_poll`] under `tag`.
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_fdatasync_tagged(fd: libc::c_int, tag: u64) -> LioHandle {
+  lio_fdatasync(fd, boxed(tag), queue)
+}
+
+/// Same as [`lio_sync_file_range`], but the completion is queued for [`lio_poll`] under `tag`.
+#[cfg(linux)]
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_sync_file_range_tagged(
+  fd: libc::c_int,
+  offset: u64,
+  nbytes: u32,
+  flags: u32,
+  tag: u64,
+) -> LioHandle {
+  lio_sync_file_range(fd, offset, nbytes, flags, boxed(tag), queue)
+}
+
+/// Same as [`lio_write`], but the completion is queued for [`lio_poll`] under `tag`.
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_write_tagged(
+  fd: libc::c_int,
+  buf: *mut u8,
+  buf_len: usize,
+  offset: i64,
+  tag: u64,
+) -> LioHandle {
+  lio_write(fd, buf, buf_len, offset, boxed(tag), queue_buf)
+}
+
+/// Same as [`lio_read`], but the completion is queued for [`lio_poll`] under `tag`.
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_read_tagged(
+  fd: libc::c_int,
+  buf: *mut u8,
+  buf_len: usize,
+  offset: i64,
+  tag: u64,
+) -> LioHandle {
+  lio_read(fd, buf, buf_len, offset, boxed(tag), queue_buf)
+}
+
+/// Same as [`lio_truncate`], but the completion is queued for [`lio_poll`] under `tag`.
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_truncate_tagged(
+  fd: libc::c_int,
+  len: u64,
+  tag: u64,
+) -> LioHandle {
+  lio_truncate(fd, len, boxed(tag), queue)
+}
+
+/// Same as [`lio_socket`], but the completion is queued for [`lio_poll`] under `tag`.
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_socket_tagged(
+  domain: i32,
+  ty: i32,
+  proto: i32,
+  tag: u64,
+) -> LioHandle {
+  lio_socket(domain, ty, proto, boxed(tag), queue)
+}
+
+/// Same as [`lio_bind`], but the completion is queued for [`lio_poll`] under `tag`.
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_bind_tagged(
+  fd: libc::c_int,
+  sock: *const libc::sockaddr,
+  sock_len: *const libc::socklen_t,
+  tag: u64,
+) -> LioHandle {
+  lio_bind(fd, sock, sock_len, boxed(tag), queue)
+}
+
+/// Same as [`lio_accept`], but the completion is queued for [`lio_poll`] under `tag`.
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_accept_tagged(fd: libc::c_int, tag: u64) -> LioHandle {
+  lio_accept(fd, boxed(tag), queue_accept)
+}
+
+/// Same as [`lio_listen`], but the completion is queued for [`lio_poll`] under `tag`.
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_listen_tagged(
+  fd: libc::c_int,
+  backlog: i32,
+  tag: u64,
+) -> LioHandle {
+  lio_listen(fd, backlog, boxed(tag), queue)
+}
+
+/// Same as [`lio_connect`], but the completion is queued for [`lio_poll`] under `tag`.
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_connect_tagged(
+  fd: libc::c_int,
+  sock: *const libc::sockaddr,
+  sock_len: libc::socklen_t,
+  tag: u64,
+) -> LioHandle {
+  lio_connect(fd, sock, sock_len, boxed(tag), queue)
+}
+
+/// Same as [`lio_send`], but the completion is queued for [`lio_poll`] under `tag`.
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_send_tagged(
+  fd: libc::c_int,
+  buf: *mut u8,
+  buf_len: usize,
+  flags: i32,
+  tag: u64,
+) -> LioHandle {
+  lio_send(fd, buf, buf_len, flags, boxed(tag), queue_buf)
+}
+
+/// Same as [`lio_recv`], but the completion is queued for [`lio_poll`] under `tag`.
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_recv_tagged(
+  fd: libc::c_int,
+  buf: *mut u8,
+  buf_len: usize,
+  flags: i32,
+  tag: u64,
+) -> LioHandle {
+  lio_recv(fd, buf, buf_len, flags, boxed(tag), queue_buf)
+}
+
+/// Same as [`lio_write_all`], but the completion is queued for [`lio_poll`] under `tag`.
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_write_all_tagged(
+  fd: libc::c_int,
+  buf: *mut u8,
+  buf_len: usize,
+  offset: i64,
+  tag: u64,
+) -> LioHandle {
+  lio_write_all(fd, buf, buf_len, offset, boxed(tag), queue_buf)
+}
+
+/// Same as [`lio_read_exact`], but the completion is queued for [`lio_poll`] under `tag`.
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_read_exact_tagged(
+  fd: libc::c_int,
+  buf: *mut u8,
+  buf_len: usize,
+  offset: i64,
+  tag: u64,
+) -> LioHandle {
+  lio_read_exact(fd, buf, buf_len, offset, boxed(tag), queue_buf)
+}
+
+/// Same as [`lio_send_all`], but the completion is queued for [`lio_poll`] under `tag`.
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_send_all_tagged(
+  fd: libc::c_int,
+  buf: *mut u8,
+  buf_len: usize,
+  tag: u64,
+) -> LioHandle {
+  lio_send_all(fd, buf, buf_len, boxed(tag), queue_buf)
+}
+
+/// Same as [`lio_recv_exact`], but the completion is queued for [`lio_poll`] under `tag`.
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_recv_exact_tagged(
+  fd: libc::c_int,
+  buf: *mut u8,
+  buf_len: usize,
+  tag: u64,
+) -> LioHandle {
+  lio_recv_exact(fd, buf, buf_len, boxed(tag), queue_buf)
+}
+
+/// Same as [`lio_close`], but the completion is queued for [`lio_poll`] under `tag`.
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_close_tagged(fd: libc::c_int, tag: u64) -> LioHandle {
+  lio_close(fd, boxed(tag), queue)
+}
+
+/// Same as [`lio_openat`], but the completion is queued for [`lio_poll`] under `tag`.
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_openat_tagged(
+  dir_fd: libc::c_int,
+  pathname: *const libc::c_char,
+  flags: i32,
+  mode: libc::mode_t,
+  tag: u64,
+) -> LioHandle {
+  lio_openat(dir_fd, pathname, flags, mode, boxed(tag), queue)
+}
+
+/// Same as [`lio_openat2`], but the completion is queued for [`lio_poll`] under `tag`.
+#[cfg(linux)]
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_openat2_tagged(
+  dir_fd: libc::c_int,
+  pathname: *const libc::c_char,
+  flags: i32,
+  mode: libc::mode_t,
+  resolve: u64,
+  tag: u64,
+) -> LioHandle {
+  lio_openat2(dir_fd, pathname, flags, mode, resolve, boxed(tag), queue)
+}
+
+/// Same as [`lio_tee`], but the completion is queued for [`lio_poll`] under `tag`.
+#[cfg(linux)]
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_tee_tagged(
+  fd_in: libc::c_int,
+  fd_out: libc::c_int,
+  size: u32,
+  tag: u64,
+) -> LioHandle {
+  lio_tee(fd_in, fd_out, size, boxed(tag), queue)
+}
+
+/// Same as [`lio_timeout`], but the completion is queued for [`lio_poll`] under `tag`.
+#[cfg(linux)]
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_timeout_tagged(duration_ms: u64, tag: u64) -> LioHandle {
+  lio_timeout(duration_ms, boxed(tag), queue)
+}
+
+/// Same as [`lio_futex_wait`], but the completion is queued for [`lio_poll`] under `tag`.
+#[cfg(linux)]
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_futex_wait_tagged(
+  futex: *const u32,
+  expected: u32,
+  tag: u64,
+) -> LioHandle {
+  lio_futex_wait(futex, expected, boxed(tag), queue)
+}
+
+/// Same as [`lio_futex_wake`], but the completion is queued for [`lio_poll`] under `tag`.
+#[cfg(linux)]
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_futex_wake_tagged(
+  futex: *const u32,
+  count: u32,
+  tag: u64,
+) -> LioHandle {
+  lio_futex_wake(futex, count, boxed(tag), queue)
+}
+
+/// Same as [`lio_waitid`], but the completion is queued for [`lio_poll`] under `tag`.
+#[unsafe(no_mangle)]
+pub extern "C" fn lio_waitid_tagged(pid: u32, tag: u64) -> LioHandle {
+  lio_waitid(pid, boxed(tag), queue)
+}
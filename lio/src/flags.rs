@@ -0,0 +1,159 @@
+//! Typed flags for [`openat`](crate::openat), [`send`](crate::send) and
+//! [`recv`](crate::recv).
+//!
+//! Only flags the target platform has are defined. Others can still be passed
+//! with `from_bits`.
+
+use std::ops::BitOr;
+
+macro_rules! flags {
+  (
+    $(#[$doc:meta])*
+    $name:ident {
+      $(
+        $(#[$flag_doc:meta])*
+        $flag:ident = $value:ident,
+      )*
+    }
+  ) => {
+    $(#[$doc])*
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct $name(i32);
+
+    impl $name {
+      $(
+        $(#[$flag_doc])*
+        pub const $flag: $name = $name(libc::$value);
+      )*
+
+      /// From raw bits, for flags without a constant here. They aren't checked.
+      pub const fn from_bits(bits: i32) -> $name {
+        $name(bits)
+      }
+
+      pub const fn bits(self) -> i32 {
+        self.0
+      }
+
+      pub const fn contains(self, other: $name) -> bool {
+        self.0 & other.0 == other.0
+      }
+    }
+
+    impl BitOr for $name {
+      type Output = $name;
+
+      fn bitor(self, rhs: $name) -> $name {
+        $name(self.0 | rhs.0)
+      }
+    }
+  };
+}
+
+flags!(
+  /// `O_*` flags for opening a file, combined with `|`.
+  ///
+  /// One of [`RDONLY`](OpenFlags::RDONLY), [`WRONLY`](OpenFlags::WRONLY) or
+  /// [`RDWR`](OpenFlags::RDWR) sets the access mode. `RDONLY` is zero, so
+  /// every value [`contains`](OpenFlags::contains) it.
+  ///
+  /// # Examples
+  ///
+  /// ```rust
+  /// use lio::OpenFlags;
+  ///
+  /// let flags = OpenFlags::WRONLY | OpenFlags::CREAT | OpenFlags::TRUNC;
+  /// assert!(flags.contains(OpenFlags::CREAT));
+  /// ```
+  OpenFlags {
+    /// Open for reading only.
+    RDONLY = O_RDONLY,
+    /// Open for writing only.
+    WRONLY = O_WRONLY,
+    /// Open for reading and writing.
+    RDWR = O_RDWR,
+    /// Create the file if it doesn't exist.
+    CREAT = O_CREAT,
+    /// With [`CREAT`](OpenFlags::CREAT), fail with `EEXIST` if the file exists.
+    EXCL = O_EXCL,
+    /// Truncate the file to length zero.
+    TRUNC = O_TRUNC,
+    /// Every write goes to the end of the file.
+    APPEND = O_APPEND,
+    /// Reads and writes don't block, for pipes and devices.
+    NONBLOCK = O_NONBLOCK,
+    /// Close the file descriptor on `exec`.
+    CLOEXEC = O_CLOEXEC,
+    /// Fail with `ENOTDIR` if the path isn't a directory.
+    DIRECTORY = O_DIRECTORY,
+    /// Fail with `ELOOP` if the last path component is a symbolic link.
+    NOFOLLOW = O_NOFOLLOW,
+    /// Don't make a terminal the controlling terminal of the process.
+    NOCTTY = O_NOCTTY,
+    /// Writes return once data and metadata reached the disk.
+    SYNC = O_SYNC,
+    /// Writes return once data reached the disk.
+    DSYNC = O_DSYNC,
+  }
+);
+
+#[cfg(linux)]
+impl OpenFlags {
+  /// Bypass the page cache. Buffers, offsets and lengths must be aligned.
+  pub const DIRECT: OpenFlags = OpenFlags(libc::O_DIRECT);
+  /// Don't update the access time when reading.
+  pub const NOATIME: OpenFlags = OpenFlags(libc::O_NOATIME);
+  /// Only get a descriptor to the path itself, which can't be read or written.
+  pub const PATH: OpenFlags = OpenFlags(libc::O_PATH);
+  /// Create an unnamed file in the given directory.
+  pub const TMPFILE: OpenFlags = OpenFlags(libc::O_TMPFILE);
+}
+
+flags!(
+  /// `MSG_*` flags for [`send`](crate::send) and [`recv`](crate::recv),
+  /// combined with `|`.
+  ///
+  /// # Examples
+  ///
+  /// ```rust
+  /// use lio::MsgFlags;
+  ///
+  /// let flags = MsgFlags::PEEK | MsgFlags::DONTWAIT;
+  /// assert!(flags.contains(MsgFlags::PEEK));
+  /// ```
+  MsgFlags {
+    /// Return data without removing it from the receive queue.
+    PEEK = MSG_PEEK,
+    /// Wait until the whole buffer is filled.
+    WAITALL = MSG_WAITALL,
+    /// Fail with `EAGAIN` instead of waiting.
+    DONTWAIT = MSG_DONTWAIT,
+    /// Send or receive out-of-band data.
+    OOB = MSG_OOB,
+    /// Return the real length of a datagram, even if longer than the buffer.
+    TRUNC = MSG_TRUNC,
+    /// Send only to directly connected hosts.
+    DONTROUTE = MSG_DONTROUTE,
+    /// Ends a record, for `SOCK_SEQPACKET`.
+    EOR = MSG_EOR,
+  }
+);
+
+impl MsgFlags {
+  /// No flags.
+  pub const NONE: MsgFlags = MsgFlags(0);
+}
+
+#[cfg(not(apple))]
+impl MsgFlags {
+  /// Fail with `EPIPE` instead of raising `SIGPIPE` when the peer is gone.
+  pub const NOSIGNAL: MsgFlags = MsgFlags(libc::MSG_NOSIGNAL);
+}
+
+#[cfg(linux)]
+impl MsgFlags {
+  /// More data follows, so hold off sending a partial packet.
+  pub const MORE: MsgFlags = MsgFlags(libc::MSG_MORE);
+  /// Receive a queued error, like from `IP_RECVERR`.
+  pub const ERRQUEUE: MsgFlags = MsgFlags(libc::MSG_ERRQUEUE);
+}
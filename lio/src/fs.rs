@@ -0,0 +1,183 @@
+//! Files that close themselves, built on the operations in the crate root.
+//!
+//! A [`File`] owns its descriptor, and closes it through the driver when
+//! dropped, without blocking. Use [`File::close`] to wait for the close and
+//! see its error. Its methods borrow the file until the operation completes,
+//! and if their future is dropped before that, the file stays open until the
+//! operation did.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use lio::fs::File;
+//!
+//! async fn fs_example() -> std::io::Result<()> {
+//!     let file = File::create("/tmp/lio_fs_example.txt").await?;
+//!     let (res, _buf) = file.write_all_at(b"Hello, World!".to_vec(), 0).await;
+//!     res?;
+//!     file.sync_all().await?;
+//!
+//!     let file = File::open("/tmp/lio_fs_example.txt").await?;
+//!     let (res, buf) = file.read_at(Vec::with_capacity(64), 0).await;
+//!     assert_eq!(&buf[..res? as usize], b"Hello, World!");
+//!     Ok(())
+//! }
+//! ```
+
+use std::{
+  ffi::CString,
+  io,
+  os::{
+    fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, OwnedFd, RawFd},
+    unix::ffi::OsStrExt,
+  },
+  path::Path,
+  sync::Arc,
+};
+
+use crate::{
+  BufResult, ExactResult, IoBuf, IoBufMut, OpenFlags, driver::Driver,
+  op::OpenAt, owned::Owned,
+};
+
+/// An open file, closed when dropped. See the [module docs](self).
+#[derive(Debug)]
+pub struct File {
+  fd: Arc<Owned>,
+}
+
+impl File {
+  /// Opens a file for reading.
+  pub async fn open(path: impl AsRef<Path>) -> io::Result<File> {
+    File::open_with(path, OpenFlags::RDONLY, 0).await
+  }
+
+  /// Opens a file for writing, creating it with mode `0o644` (before the
+  /// process umask) or truncating it.
+  pub async fn create(path: impl AsRef<Path>) -> io::Result<File> {
+    let flags = OpenFlags::WRONLY | OpenFlags::CREAT | OpenFlags::TRUNC;
+    File::open_with(path, flags, 0o644).await
+  }
+
+  /// Opens a file with `flags`, and `mode` for [`OpenFlags::CREAT`].
+  /// [`OpenFlags::CLOEXEC`] is always added.
+  pub async fn open_with(
+    path: impl AsRef<Path>,
+    flags: OpenFlags,
+    mode: libc::mode_t,
+  ) -> io::Result<File> {
+    let path = CString::new(path.as_ref().as_os_str().as_bytes())?;
+    let fd = Driver::submit(OpenAt::with_mode(
+      libc::AT_FDCWD,
+      path,
+      flags | OpenFlags::CLOEXEC,
+      mode,
+    ))
+    .await?;
+    // SAFETY: The descriptor was just opened, and nothing else owns it.
+    Ok(unsafe { File::from_raw_fd(fd) })
+  }
+
+  /// Reads into the spare capacity of `buf` at `offset`, see [`read`](crate::read).
+  pub async fn read_at<B: IoBufMut>(
+    &self,
+    buf: B,
+    offset: i64,
+  ) -> BufResult<i32, B> {
+    self.fd.hold(crate::read(self.as_raw_fd(), buf, offset)).await
+  }
+
+  /// Writes `buf` at `offset`, see [`write`](crate::write).
+  pub async fn write_at<B: IoBuf>(
+    &self,
+    buf: B,
+    offset: i64,
+  ) -> BufResult<i32, B> {
+    self.fd.hold(crate::write(self.as_raw_fd(), buf, offset)).await
+  }
+
+  /// Fills the spare capacity of `buf` from `offset`, see [`read_exact`](crate::read_exact).
+  pub async fn read_exact_at<B: IoBufMut>(
+    &self,
+    buf: B,
+    offset: i64,
+  ) -> ExactResult<B> {
+    self.fd.hold(crate::read_exact(self.as_raw_fd(), buf, offset)).await
+  }
+
+  /// Writes all of `buf` from `offset`, see [`write_all`](crate::write_all).
+  pub async fn write_all_at<B: IoBuf>(
+    &self,
+    buf: B,
+    offset: i64,
+  ) -> ExactResult<B> {
+    self.fd.hold(crate::write_all(self.as_raw_fd(), buf, offset)).await
+  }
+
+  /// Flushes data and metadata to disk, see [`fsync`](crate::fsync).
+  pub async fn sync_all(&self) -> io::Result<()> {
+    self.fd.hold(crate::fsync(self.as_raw_fd())).await
+  }
+
+  /// Flushes data to disk, see [`fdatasync`](crate::fdatasync).
+  pub async fn sync_data(&self) -> io::Result<()> {
+    self.fd.hold(crate::fdatasync(self.as_raw_fd())).await
+  }
+
+  /// Truncates or extends the file to `len` bytes, see [`truncate`](crate::truncate).
+  pub async fn set_len(&self, len: u64) -> io::Result<()> {
+    self.fd.hold(crate::truncate(self.as_raw_fd(), len)).await
+  }
+
+  /// Closes the file and waits for it, unlike dropping it.
+  pub async fn close(self) -> io::Result<()> {
+    Owned::close(self.fd).await
+  }
+
+  /// Takes the descriptor out, so it's no longer closed on drop.
+  ///
+  /// Fails, handing the file back, while an operation whose future was dropped
+  /// still uses the descriptor, since that one closes it once it completed.
+  pub fn try_into_owned(self) -> Result<OwnedFd, File> {
+    Owned::try_into_owned(self.fd).map_err(|fd| File { fd })
+  }
+}
+
+impl AsFd for File {
+  fn as_fd(&self) -> BorrowedFd<'_> {
+    self.fd.as_fd()
+  }
+}
+
+impl AsRawFd for File {
+  fn as_raw_fd(&self) -> RawFd {
+    self.fd.as_raw_fd()
+  }
+}
+
+impl From<OwnedFd> for File {
+  fn from(fd: OwnedFd) -> Self {
+    File { fd: Owned::new(fd) }
+  }
+}
+
+impl TryFrom<File> for OwnedFd {
+  type Error = File;
+
+  fn try_from(file: File) -> Result<Self, File> {
+    file.try_into_owned()
+  }
+}
+
+impl From<std::fs::File> for File {
+  fn from(file: std::fs::File) -> Self {
+    File::from(OwnedFd::from(file))
+  }
+}
+
+impl FromRawFd for File {
+  unsafe fn from_raw_fd(fd: RawFd) -> Self {
+    // SAFETY: Upheld by the caller.
+    File::from(unsafe { OwnedFd::from_raw_fd(fd) })
+  }
+}
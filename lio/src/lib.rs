@@ -10,8 +10,10 @@
 //! - **Automatic fallback** to blocking operations when async isn't supported.
 //! - **Manual control** with high level async API.
 //!
-//! *Note:* This is a quite low-level library. This library creates os resources
-//! (fd's) which it doesn't cleanup automatically.
+//! *Note:* This is a quite low-level library. The functions in the crate root
+//! create os resources (fd's) which they don't cleanup automatically. With the
+//! "high" feature, [`fs::File`] and [`net::Socket`] own their fd's and close
+//! them when dropped.
 //!
 //! ## Platform support
 //!
@@ -72,7 +74,6 @@
 pub mod ffi;
 use std::{
   ffi::{CString, NulError},
-  net::SocketAddr,
   os::fd::RawFd,
 };
 
@@ -87,18 +88,61 @@ pub type BufResult<T, B> = (std::io::Result<T>, B);
 #[macro_use]
 mod macros;
 
+mod address;
+pub use address::{SocketAddress, UnixAddress};
+
+mod buf;
+pub use buf::*;
+
+mod dispatch;
+pub use dispatch::{CallbackDispatch, CallbackJob};
+
+mod flags;
+pub use flags::{MsgFlags, OpenFlags};
+
+mod exact;
+pub use exact::{Exact, ExactResult, TransferError};
+
+mod fd;
+pub use fd::BorrowFd;
+
 mod driver;
 
 pub mod op;
+pub mod sockopt;
+/// The io-uring crate lio builds entries with, for
+/// [`CustomOperation`](op::CustomOperation).
+#[cfg(linux)]
+#[cfg_attr(docsrs, doc(cfg(linux)))]
+pub use io_uring;
 use op::*;
+use sockopt::SockOpt;
 
 mod op_progress;
 mod op_registration;
 
 mod backends;
-pub use backends::IoBackend;
+#[cfg(feature = "sim")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sim")))]
+pub use backends::{CompletionOrder, Simulated};
+pub use backends::{IoBackend, SubmitPolicy};
+
+#[cfg(feature = "fault_injection")]
+mod fault;
+#[cfg(feature = "fault_injection")]
+#[cfg_attr(docsrs, doc(cfg(feature = "fault_injection")))]
+pub use fault::{Fault, FaultRule, Faults};
+mod stats;
+#[cfg(feature = "histogram")]
+pub use stats::Histogram;
+pub use stats::{OpStats, RingStats, Stats};
 
 pub use op_progress::OperationProgress;
+#[cfg(feature = "high")]
+mod op_set;
+#[cfg(feature = "high")]
+#[cfg_attr(docsrs, doc(cfg(feature = "high")))]
+pub use op_set::OpSet;
 
 use crate::driver::Driver;
 use std::path::Path;
@@ -150,6 +194,23 @@ macro_rules! impl_op {
     );
   };
 
+  // Unsafe to call, the caller upholds the `# Safety` section of the docs
+  (
+    unsafe $desc:expr,
+    $(#[$($doc:tt)*])*
+    $operation:ident, fn $name:ident ( $($arg:ident: $arg_ty:ty),* ) -> $ret:ty
+  ) => {
+    impl_op!(@impl_fn
+      { $desc, $operation, $name, [$($arg : $arg_ty),*], $ret }
+      $( #[$($doc)*] )*
+      {
+        pub unsafe fn $name($($arg: $arg_ty),*) -> OperationProgress<$operation> {
+          Driver::submit($operation::new($($arg),*))
+        }
+      }
+    );
+  };
+
   // With error type - fallible constructor
   (
     $desc:expr,
@@ -169,6 +230,23 @@ macro_rules! impl_op {
     );
   };
 
+  // Generic over a single type parameter, for example the buffer type
+  (
+    $desc:expr,
+    $(#[$($doc:tt)*])*
+    $operation:ident<$gen:ident: $bound:path>, fn $name:ident ( $($arg:ident: $arg_ty:ty),* ) -> $ret:ty
+  ) => {
+    impl_op!(@impl_fn
+      { $desc, $operation, $name, [$($arg : $arg_ty),*], $ret }
+      $( #[$($doc)*] )*
+      {
+        pub fn $name<$gen: $bound>($($arg: $arg_ty),*) -> OperationProgress<$operation<$gen>> {
+          Driver::submit($operation::new($($arg),*))
+        }
+      }
+    );
+  };
+
   // Without error type - infallible constructor
   (
     $desc:expr,
@@ -195,7 +273,6 @@ macro_rules! impl_op {
   };
 }
 
-#[cfg(linux)]
 use std::time::Duration;
 
 impl_op!(
@@ -205,12 +282,88 @@ impl_op!(
  /// ```rust
  /// async fn write_example() -> std::io::Result<()> {
  ///     let socket = lio::socket(socket2::Domain::IPV4, socket2::Type::STREAM, None).await?;
- ///     let how = 0;
- ///     lio::shutdown(socket, how).await?;
+ ///     lio::shutdown(socket, std::net::Shutdown::Write).await?;
  ///     Ok(())
  /// }
  /// ```
- Shutdown, fn shutdown(fd: RawFd, how: i32) -> io::Result<()>
+ Shutdown, fn shutdown(fd: impl BorrowFd, how: std::net::Shutdown) -> io::Result<()>
+);
+
+impl_op!(
+  "Sets a socket option.",
+  /// Options are types in [`sockopt`], like [`TcpNoDelay`](sockopt::TcpNoDelay).
+  /// On Linux 6.7 and newer this is an io_uring socket command, older kernels
+  /// and other platforms call `setsockopt(2)`.
+  ///
+  /// # Examples
+  ///
+  /// ```rust
+  /// use lio::sockopt::{ReuseAddr, RecvBufferSize};
+  ///
+  /// async fn setsockopt_example() -> std::io::Result<()> {
+  ///     let socket = lio::socket(socket2::Domain::IPV4, socket2::Type::STREAM, None).await?;
+  ///     lio::setsockopt(socket, ReuseAddr, true).await?;
+  ///     lio::setsockopt(socket, RecvBufferSize, 1 << 20).await?;
+  ///     Ok(())
+  /// }
+  /// ```
+  SetSockOpt<O: SockOpt>, fn setsockopt(fd: impl BorrowFd, opt: O, value: O::Value) -> io::Result<()>
+);
+
+impl_op!(
+  "Reads a socket option.",
+  /// Options are types in [`sockopt`], like [`TcpNoDelay`](sockopt::TcpNoDelay).
+  /// Always calls `getsockopt(2)`, which doesn't block.
+  ///
+  /// # Examples
+  ///
+  /// ```rust
+  /// use lio::sockopt::Linger;
+  ///
+  /// async fn getsockopt_example() -> std::io::Result<()> {
+  ///     let socket = lio::socket(socket2::Domain::IPV4, socket2::Type::STREAM, None).await?;
+  ///     assert_eq!(lio::getsockopt(socket, Linger).await?, None);
+  ///     Ok(())
+  /// }
+  /// ```
+  GetSockOpt<O: SockOpt>, fn getsockopt(fd: impl BorrowFd, opt: O) -> io::Result<O::Value>
+);
+
+impl_op!(
+  "Returns the address a socket is bound to.",
+  /// Useful after binding to port 0, to find the port the kernel picked.
+  ///
+  /// # Examples
+  ///
+  /// ```rust
+  /// use std::net::SocketAddr;
+  ///
+  /// async fn local_addr_example() -> std::io::Result<()> {
+  ///     let socket = lio::socket(socket2::Domain::IPV4, socket2::Type::STREAM, None).await?;
+  ///     lio::bind(socket, "127.0.0.1:0".parse::<SocketAddr>().unwrap()).await?;
+  ///     let addr = lio::local_addr(socket).await?;
+  ///     println!("Bound to port {}", addr.as_inet().unwrap().port());
+  ///     Ok(())
+  /// }
+  /// ```
+  LocalAddr, fn local_addr(fd: impl BorrowFd) -> io::Result<SocketAddress>
+);
+
+impl_op!(
+  "Returns the address of the peer a socket is connected to.",
+  /// Fails with `ENOTCONN` if the socket isn't connected.
+  ///
+  /// # Examples
+  ///
+  /// ```rust
+  /// async fn peer_addr_example() -> std::io::Result<()> {
+  ///     # let fd = 0;
+  ///     let peer = lio::peer_addr(fd).await?;
+  ///     println!("Connected to {peer:?}");
+  ///     Ok(())
+  /// }
+  /// ```
+  PeerAddr, fn peer_addr(fd: impl BorrowFd) -> io::Result<SocketAddress>
 );
 
 #[cfg(linux)]
@@ -231,6 +384,73 @@ impl_op!(
   Timeout, fn timeout(duration: Duration) -> io::Result<()>
 );
 
+#[cfg(linux)]
+#[cfg_attr(docsrs, doc(cfg(linux)))]
+impl_op!(
+  unsafe "Waits until the futex word is woken, for example by [`futex_wake`] or by another process (Linux only).",
+  /// Resolves immediately if `futex` doesn't contain `expected` when the wait starts,
+  /// so callers should re-check the value in a loop, like with `futex(2)`.
+  ///
+  /// The futex is not process-private: waiters and wakers may live in different
+  /// processes, as long as they share the memory mapping `futex` points into.
+  /// Uses `IORING_OP_FUTEX_WAIT` (Linux 6.7). Older kernels wait in `futex(2)`
+  /// on a helper thread instead, so the thread driving lio never blocks. If the
+  /// [`OperationProgress`] is dropped first, that thread keeps waiting until the
+  /// word is woken.
+  ///
+  /// # Safety
+  ///
+  /// `futex` must point to a 4-byte aligned `u32` that stays mapped until the
+  /// operation completes, also if the [`OperationProgress`] is dropped before.
+  ///
+  /// # Examples
+  ///
+  /// ```rust
+  /// use std::sync::atomic::{AtomicU32, Ordering};
+  ///
+  /// static READY: AtomicU32 = AtomicU32::new(0);
+  ///
+  /// async fn futex_wait_example() -> std::io::Result<()> {
+  ///     while READY.load(Ordering::Acquire) == 0 {
+  ///         // SAFETY: A static is aligned and never unmapped.
+  ///         unsafe { lio::futex_wait(READY.as_ptr(), 0) }.await?;
+  ///     }
+  ///     Ok(())
+  /// }
+  /// ```
+  FutexWait, fn futex_wait(futex: *const u32, expected: u32) -> io::Result<()>
+);
+
+#[cfg(linux)]
+#[cfg_attr(docsrs, doc(cfg(linux)))]
+impl_op!(
+  unsafe "Wakes at most `count` waiters of a futex word, returning how many were woken (Linux only).",
+  /// Uses `IORING_OP_FUTEX_WAKE` (Linux 6.7), and falls back to `futex(2)` on
+  /// older kernels, which doesn't block.
+  ///
+  /// # Safety
+  ///
+  /// `futex` must point to a 4-byte aligned `u32` that stays mapped until the
+  /// operation completes, also if the [`OperationProgress`] is dropped before.
+  ///
+  /// # Examples
+  ///
+  /// ```rust
+  /// use std::sync::atomic::{AtomicU32, Ordering};
+  ///
+  /// static READY: AtomicU32 = AtomicU32::new(0);
+  ///
+  /// async fn futex_wake_example() -> std::io::Result<()> {
+  ///     READY.store(1, Ordering::Release);
+  ///     // SAFETY: A static is aligned and never unmapped.
+  ///     let woken = unsafe { lio::futex_wake(READY.as_ptr(), u32::MAX) }.await?;
+  ///     println!("Woke {} waiters", woken);
+  ///     Ok(())
+  /// }
+  /// ```
+  FutexWake, fn futex_wake(futex: *const u32, count: u32) -> io::Result<u32>
+);
+
 impl_op!(
   "Create a soft-link",
   /// # Examples
@@ -243,7 +463,7 @@ impl_op!(
   ///     Ok(())
   /// }
   /// ```
-  SymlinkAt, fn symlinkat(new_dir_fd: RawFd, target: impl AsRef<Path>, linkpath: impl AsRef<Path>) -> io::Result<()> ; NulError
+  SymlinkAt, fn symlinkat(new_dir_fd: impl BorrowFd, target: impl AsRef<Path>, linkpath: impl AsRef<Path>) -> io::Result<()> ; NulError
 );
 
 impl_op!(
@@ -258,7 +478,33 @@ impl_op!(
   ///     Ok(())
   /// }
   /// ```
-  LinkAt, fn linkat(old_dir_fd: RawFd, old_path: impl AsRef<Path>, new_dir_fd: RawFd, new_path: impl AsRef<Path>) -> io::Result<()> ; NulError
+  LinkAt, fn linkat(old_dir_fd: impl BorrowFd, old_path: impl AsRef<Path>, new_dir_fd: impl BorrowFd, new_path: impl AsRef<Path>) -> io::Result<()> ; NulError
+);
+
+impl_op!(
+  "Rename a file, relative to directory file descriptors. Equivalent to the `renameat` syscall.",
+  /// # Examples
+  ///
+  /// ```rust
+  /// async fn renameat_example() -> std::io::Result<()> {
+  ///     lio::renameat(libc::AT_FDCWD, "/tmp/old.txt", libc::AT_FDCWD, "/tmp/new.txt")?.await?;
+  ///     Ok(())
+  /// }
+  /// ```
+  RenameAt, fn renameat(old_dir_fd: impl BorrowFd, old_path: impl AsRef<Path>, new_dir_fd: impl BorrowFd, new_path: impl AsRef<Path>) -> io::Result<()> ; NulError
+);
+
+impl_op!(
+  "Remove a file, relative to a directory file descriptor. Equivalent to the `unlinkat` syscall.",
+  /// # Examples
+  ///
+  /// ```rust
+  /// async fn unlinkat_example() -> std::io::Result<()> {
+  ///     lio::unlinkat(libc::AT_FDCWD, "/tmp/old.txt")?.await?;
+  ///     Ok(())
+  /// }
+  /// ```
+  UnlinkAt, fn unlinkat(dir_fd: impl BorrowFd, path: impl AsRef<Path>) -> io::Result<()> ; NulError
 );
 
 impl_op!(
@@ -272,7 +518,44 @@ impl_op!(
   ///     Ok(())
   /// }
   /// ```
-  Fsync, fn fsync(fd: RawFd) -> io::Result<()>
+  Fsync, fn fsync(fd: impl BorrowFd) -> io::Result<()>
+);
+
+impl_op!(
+  "Sync file data to disk, without flushing metadata that isn't needed to read the data back. Equivalent to the `fdatasync` syscall.",
+  /// # Examples
+  ///
+  /// ```rust
+  /// async fn fdatasync_example() -> std::io::Result<()> {
+  ///     # let fd = 0;
+  ///     lio::fdatasync(fd).await?;
+  ///     Ok(())
+  /// }
+  /// ```
+  Fdatasync, fn fdatasync(fd: impl BorrowFd) -> io::Result<()>
+);
+
+#[cfg(linux)]
+#[cfg_attr(docsrs, doc(cfg(linux)))]
+impl_op!(
+  "Sync a byte range of a file to disk (Linux only). Equivalent to the `sync_file_range(2)` syscall.",
+  /// `flags` is a combination of `libc::SYNC_FILE_RANGE_WAIT_BEFORE`,
+  /// `libc::SYNC_FILE_RANGE_WRITE` and `libc::SYNC_FILE_RANGE_WAIT_AFTER`.
+  /// A `nbytes` of 0 syncs from `offset` to the end of the file.
+  ///
+  /// **Note**: This doesn't flush metadata, use [`fdatasync`] or [`fsync`] for durability.
+  ///
+  /// # Examples
+  ///
+  /// ```rust
+  /// async fn sync_file_range_example() -> std::io::Result<()> {
+  ///     # let fd = 0;
+  ///     let flags = libc::SYNC_FILE_RANGE_WRITE as u32;
+  ///     lio::sync_file_range(fd, 0, 4096, flags).await?;
+  ///     Ok(())
+  /// }
+  /// ```
+  SyncFileRange, fn sync_file_range(fd: impl BorrowFd, offset: u64, nbytes: u32, flags: u32) -> io::Result<()>
 );
 
 impl_op!(
@@ -288,24 +571,28 @@ impl_op!(
   ///     Ok(())
   /// }
   /// ```
-  Write, fn write(fd: RawFd, buf: Vec<u8>, offset: i64) -> BufResult<i32, Vec<u8>>
+  Write<B: IoBuf>, fn write(fd: impl BorrowFd, buf: B, offset: i64) -> BufResult<i32, B>
 );
 
 impl_op!(
   "Performs a read operation on a file descriptor. Equivalent of the `pread` syscall.",
+  /// Reads into the spare capacity of `mem`, after its initialized bytes, and
+  /// extends its length to cover the bytes read. Any [`IoBufMut`] can be used,
+  /// for example a [`Slice`] to read into the middle of a larger buffer.
+  ///
   /// # Examples
   ///
   /// ```rust
   /// async fn read_example() -> std::io::Result<()> {
   ///     # let fd = 0;
-  ///     let mut buffer = vec![0u8; 1024];
+  ///     let buffer = Vec::with_capacity(1024);
   ///     let (res_bytes_read, buf) = lio::read(fd, buffer, 0).await;
   ///     let bytes_read = res_bytes_read?;
-  ///     println!("Read {} bytes: {:?}", bytes_read, &buf[..bytes_read as usize]);
+  ///     println!("Read {} bytes: {:?}", bytes_read, buf);
   ///     Ok(())
   /// }
   /// ```
-  Read, fn read(fd: RawFd, mem: Vec<u8>, offset: i64) -> BufResult<i32, Vec<u8>>
+  Read<B: IoBufMut>, fn read(fd: impl BorrowFd, mem: B, offset: i64) -> BufResult<i32, B>
 );
 
 impl_op!(
@@ -319,11 +606,10 @@ impl_op!(
   ///     Ok(())
   /// }
   /// ```
-  Truncate, fn truncate(fd: RawFd, len: u64) -> std::io::Result<()>
+  Truncate, fn truncate(fd: impl BorrowFd, len: u64) -> std::io::Result<()>
 );
 
 impl_op!(
-  !detach
   "Creates a new socket with the specified domain, type, and protocol.",
   /// # Examples
   ///
@@ -341,39 +627,44 @@ impl_op!(
 
 impl_op!(
   "Binds a socket to a specific address.",
+  /// Takes a [`std::net::SocketAddr`], or a [`UnixAddress`] for `AF_UNIX` sockets.
+  ///
   /// # Examples
   ///
   /// ```rust
-  /// use socket2::SockAddr;
+  /// use lio::UnixAddress;
   ///
   /// async fn bind_example() -> std::io::Result<()> {
   ///     # let fd = 0;
+  ///     # let unix_fd = 0;
   ///     let addr = "127.0.0.1:8080".parse::<std::net::SocketAddr>().unwrap();
   ///     lio::bind(fd, addr).await?;
+  ///
+  ///     lio::bind(unix_fd, UnixAddress::from_pathname("/tmp/lio.sock")?).await?;
   ///     Ok(())
   /// }
   ///
   /// ```
-  Bind, fn bind(fd: RawFd, addr: SocketAddr) -> std::io::Result<()>
+  Bind, fn bind(fd: impl BorrowFd, addr: impl Into<SocketAddress>) -> std::io::Result<()>
 );
 
 impl_op!(
-  !detach
   "Accepts a connection on a listening socket.",
+  /// The peer address of a `AF_UNIX` connection is usually
+  /// [unnamed](UnixAddress::is_unnamed), because clients rarely bind.
+  ///
   /// # Examples
   ///
   /// ```rust
-  /// use std::mem::MaybeUninit;
-  ///
   /// async fn accept_example() -> std::io::Result<()> {
   ///     # let fd = 0;
   ///
   ///     let (client_fd, addr) = lio::accept(fd).await?;
-  ///     println!("Accepted connection on fd: {}", client_fd);
+  ///     println!("Accepted connection from {:?} on fd: {}", addr, client_fd);
   ///     Ok(())
   /// }
   /// ```
-  Accept, fn accept(fd: RawFd) -> std::io::Result<(RawFd, SocketAddr)>
+  Accept, fn accept(fd: impl BorrowFd) -> std::io::Result<(RawFd, SocketAddress)>
 );
 
 impl_op!(
@@ -390,11 +681,13 @@ impl_op!(
   ///     Ok(())
   /// }
   /// ```
-  Listen, fn listen(fd: RawFd, backlog: i32) -> std::io::Result<()>
+  Listen, fn listen(fd: impl BorrowFd, backlog: i32) -> std::io::Result<()>
 );
 
 impl_op!(
   "Connects a socket to a remote address.",
+  /// Takes a [`std::net::SocketAddr`], or a [`UnixAddress`] for `AF_UNIX` sockets.
+  ///
   /// # Examples
   ///
   /// ```rust
@@ -408,7 +701,7 @@ impl_op!(
   ///     Ok(())
   /// }
   /// ```
-  Connect, fn connect(fd: RawFd, addr: SocketAddr) -> std::io::Result<()>
+  Connect, fn connect(fd: impl BorrowFd, addr: impl Into<SocketAddress>) -> std::io::Result<()>
 );
 
 impl_op!(
@@ -424,26 +717,89 @@ impl_op!(
   ///     Ok(())
   /// }
   /// ```
-  Send, fn send(fd: RawFd, buf: Vec<u8>, flags: Option<i32>) -> BufResult<i32, Vec<u8>>
+  Send<B: IoBuf>, fn send(fd: impl BorrowFd, buf: B, flags: Option<MsgFlags>) -> BufResult<i32, B>
 );
 
 impl_op!(
   "Receives data from a connected socket.",
+  /// Receives into the spare capacity of `buf`, like [`read`].
+  ///
   /// # Examples
   ///
   /// ```rust
   /// async fn recv_example() -> std::io::Result<()> {
   ///     # let fd = 0;
-  ///     let mut buffer = vec![0u8; 1024];
+  ///     let buffer = Vec::with_capacity(1024);
   ///     let (res_bytes_received, buf) = lio::recv(fd, buffer, None).await;
   ///     let bytes_received = res_bytes_received?;
-  ///     println!("Received {} bytes: {:?}", bytes_received, &buf[..bytes_received as usize]);
+  ///     println!("Received {} bytes: {:?}", bytes_received, buf);
   ///     Ok(())
   /// }
   /// ```
-  Recv, fn recv(fd: RawFd, buf: Vec<u8>, flags: Option<i32>) -> BufResult<i32, Vec<u8>>
+  Recv<B: IoBufMut>, fn recv(fd: impl BorrowFd, buf: B, flags: Option<MsgFlags>) -> BufResult<i32, B>
 );
 
+/// Writes all initialized bytes of `buf`, resubmitting after short writes. See [`Exact`].
+///
+/// A negative `offset` writes at the current file position.
+///
+/// # Examples
+///
+/// ```rust
+/// async fn write_all_example() -> std::io::Result<()> {
+///     # let fd = 0;
+///     let (res, _buf) = lio::write_all(fd, b"Hello, World!".to_vec(), 0).await;
+///     assert_eq!(res?, 13);
+///     Ok(())
+/// }
+/// ```
+pub fn write_all<B: IoBuf>(
+  fd: impl BorrowFd,
+  buf: B,
+  offset: i64,
+) -> Exact<Write<Slice<B>>> {
+  Exact::new(fd, buf, offset)
+}
+
+/// Reads until the spare capacity of `buf` is filled, resubmitting after short
+/// reads. Fails with `UnexpectedEof` if the file ends first. See [`Exact`].
+///
+/// # Examples
+///
+/// ```rust
+/// async fn read_exact_example() -> std::io::Result<()> {
+///     # let fd = 0;
+///     let (res, header) = lio::read_exact(fd, Vec::with_capacity(16), 0).await;
+///     res?;
+///     assert_eq!(header.len(), 16);
+///     Ok(())
+/// }
+/// ```
+pub fn read_exact<B: IoBufMut>(
+  fd: impl BorrowFd,
+  buf: B,
+  offset: i64,
+) -> Exact<Read<Slice<B>>> {
+  Exact::new(fd, buf, offset)
+}
+
+/// Sends all initialized bytes of `buf`, resubmitting after short sends. See [`Exact`].
+pub fn send_all<B: IoBuf>(
+  fd: impl BorrowFd,
+  buf: B,
+) -> Exact<op::Send<Slice<B>>> {
+  Exact::new(fd, buf, 0)
+}
+
+/// Receives until the spare capacity of `buf` is filled, resubmitting after
+/// short receives. Fails with `UnexpectedEof` if the peer shuts down first. See [`Exact`].
+pub fn recv_exact<B: IoBufMut>(
+  fd: impl BorrowFd,
+  buf: B,
+) -> Exact<Recv<Slice<B>>> {
+  Exact::new(fd, buf, 0)
+}
+
 impl_op!(
   "Closes a file descriptor.",
   /// # Examples
@@ -465,15 +821,47 @@ impl_op!(
   ///
   /// ```rust
   /// use std::ffi::CString;
+  /// use lio::OpenFlags;
   ///
   /// async fn openat_example() -> std::io::Result<()> {
   ///     let path = CString::new("/tmp/test.txt").unwrap();
-  ///     let fd = lio::openat(libc::AT_FDCWD, path, libc::O_RDONLY).await?;
+  ///     let fd = lio::openat(libc::AT_FDCWD, path, OpenFlags::RDONLY).await?;
   ///     println!("Opened file with fd: {}", fd);
   ///     Ok(())
   /// }
   /// ```
-  OpenAt, fn openat(fd: RawFd, path: CString, flags: i32) -> std::io::Result<i32>
+  OpenAt, fn openat(fd: impl BorrowFd, path: CString, flags: OpenFlags) -> std::io::Result<i32>
+);
+
+#[cfg(linux)]
+#[cfg_attr(docsrs, doc(cfg(linux)))]
+impl_op!(
+  "Opens a file relative to a directory file descriptor, with control over path resolution (Linux only). Equivalent to the `openat2(2)` syscall.",
+  /// See [`OpenHow`](crate::op::OpenHow) for the available options. With
+  /// [`Resolve::BENEATH`](crate::op::Resolve::BENEATH) or
+  /// [`Resolve::IN_ROOT`](crate::op::Resolve::IN_ROOT), untrusted paths can be opened
+  /// without escaping `dir_fd`.
+  ///
+  /// Uses `IORING_OP_OPENAT2` (Linux 5.6) and falls back to a blocking `openat2` call.
+  ///
+  /// # Examples
+  ///
+  /// ```rust
+  /// use lio::{
+  ///   OpenFlags,
+  ///   op::{OpenHow, Resolve},
+  /// };
+  ///
+  /// async fn openat2_example(root_fd: std::os::fd::RawFd) -> std::io::Result<()> {
+  ///     let how = OpenHow::new(OpenFlags::CREAT | OpenFlags::WRONLY | OpenFlags::CLOEXEC)
+  ///       .mode(0o644)
+  ///       .resolve(Resolve::BENEATH | Resolve::NO_SYMLINKS);
+  ///     let fd = lio::openat2(root_fd, "uploads/user.txt", how)?.await?;
+  ///     println!("Opened file with fd: {}", fd);
+  ///     Ok(())
+  /// }
+  /// ```
+  OpenAt2, fn openat2(dir_fd: impl BorrowFd, path: impl AsRef<Path>, how: op::OpenHow) -> std::io::Result<i32> ; NulError
 );
 
 #[cfg(linux)]
@@ -495,7 +883,43 @@ impl_op!(
   ///     Ok(())
   /// }
   /// ```
-  Tee, fn tee(fd_in: RawFd, fd_out: RawFd, size: u32) -> std::io::Result<()>
+  Tee, fn tee(fd_in: impl BorrowFd, fd_out: impl BorrowFd, size: u32) -> std::io::Result<()>
+);
+
+#[cfg(feature = "high")]
+mod atomic_write;
+#[cfg(feature = "high")]
+#[cfg_attr(docsrs, doc(cfg(feature = "high")))]
+pub mod fs;
+#[cfg(feature = "high")]
+#[cfg_attr(docsrs, doc(cfg(feature = "high")))]
+pub mod net;
+#[cfg(feature = "high")]
+mod owned;
+#[cfg(feature = "high")]
+#[cfg_attr(docsrs, doc(cfg(feature = "high")))]
+pub use atomic_write::atomic_write;
+
+impl_op!(
+  "Waits for a child process to exit, reaps it and returns its exit status. Equivalent to `waitid(P_PID, pid, .., WEXITED)`.",
+  /// `pid` is the id of a child of the current process, for example from
+  /// [`std::process::Child::id`].
+  ///
+  /// On Linux this uses `IORING_OP_WAITID` (Linux 6.7), and otherwise polls a pidfd
+  /// (Linux 5.3) until the child has exited, so no thread is blocked. On other
+  /// platforms the submitting thread blocks in `waitpid` until the child exits.
+  ///
+  /// # Examples
+  ///
+  /// ```rust
+  /// async fn waitid_example() -> std::io::Result<()> {
+  ///     let child = std::process::Command::new("true").spawn()?;
+  ///     let status = lio::waitid(child.id()).await?;
+  ///     assert!(status.success());
+  ///     Ok(())
+  /// }
+  /// ```
+  WaitId, fn waitid(pid: u32) -> io::Result<std::process::ExitStatus>
 );
 
 // / Shut down the lio I/O driver background thread(s) and release OS resources.
@@ -506,16 +930,140 @@ impl_op!(
 //   Driver::shutdown()
 // }
 
+/// Returns a snapshot of the driver's metrics: per operation type counts and
+/// latencies, registered operations and io_uring queue occupancy.
+///
+/// # Examples
+///
+/// ```rust
+/// let stats = lio::stats();
+/// for op in &stats.ops {
+///   println!("{}: {} submitted, {} failed", op.name, op.submitted, op.failed);
+/// }
+/// ```
+pub fn stats() -> Stats {
+  stats::snapshot()
+}
+
+/// Submits an operation defined outside of lio, see [`CustomOperation`].
+///
+/// # Panics
+///
+/// If the driver isn't initialized.
+pub fn submit<O: CustomOperation>(op: O) -> OperationProgress<O> {
+  Driver::submit(op)
+}
+
+/// Installs fault injection rules for all operations submitted from now on,
+/// replacing any installed before. Meant for testing how code copes with
+/// errors like `EINTR` and `EAGAIN`, short transfers and late completions.
+///
+/// Works with every backend, and keeps applying across [`exit`] and [`init`].
+///
+/// # Examples
+///
+/// ```rust
+/// use lio::{Fault, FaultRule, Faults};
+///
+/// lio::inject_faults(
+///   Faults::new()
+///     .seed(42)
+///     // The first and third send are interrupted.
+///     .rule(FaultRule::new(Fault::Error(libc::EINTR)).op("Send").schedule([0, 2]))
+///     // Every other send transfers at most 3 bytes.
+///     .rule(FaultRule::new(Fault::Short(3)).op("Send"))
+///     // Half of all reads complete a tick late.
+///     .rule(FaultRule::new(Fault::Reorder).op("Read").probability(0.5)),
+/// );
+/// # lio::clear_faults();
+/// ```
+#[cfg(feature = "fault_injection")]
+#[cfg_attr(docsrs, doc(cfg(feature = "fault_injection")))]
+pub fn inject_faults(faults: Faults) {
+  fault::install(Some(faults));
+}
+
+/// Removes the rules installed with [`inject_faults`]. Operations already held
+/// back by a delay still complete late.
+#[cfg(feature = "fault_injection")]
+#[cfg_attr(docsrs, doc(cfg(feature = "fault_injection")))]
+pub fn clear_faults() {
+  fault::install(None);
+}
+
+/// Runs `f`, and submits the operations it starts with a single
+/// `io_uring_enter` when it returns, instead of one per operation. Scopes can
+/// be nested, only the outermost one submits.
+///
+/// Only operations started on the current thread are batched.
+///
+/// # Examples
+///
+/// ```rust
+/// # lio::init();
+/// # let fd = 1;
+/// let (first, second) = lio::batch(|| {
+///   (lio::write(fd, b"one ".to_vec(), -1), lio::write(fd, b"two".to_vec(), -1))
+/// });
+/// # drop((first, second));
+/// # lio::exit();
+/// ```
+///
+/// # Panics
+///
+/// If the driver isn't initialized.
+pub fn batch<R>(f: impl FnOnce() -> R) -> R {
+  Driver::get().batch(f)
+}
+
+/// Sets when operations outside of a [`batch`] are submitted. Submits anything
+/// already waiting. The default is [`SubmitPolicy::Immediate`].
+///
+/// # Panics
+///
+/// If the driver isn't initialized.
+pub fn set_submit_policy(policy: SubmitPolicy) {
+  Driver::get().set_submit_policy(policy)
+}
+
+/// Sets where [`when_done`](OperationProgress::when_done) callbacks run. The
+/// default is [`CallbackDispatch::Inline`].
+///
+/// # Panics
+///
+/// If the driver isn't initialized.
+pub fn set_callback_dispatch(dispatch: CallbackDispatch) {
+  Driver::get().set_callback_dispatch(dispatch)
+}
+
 pub fn tick() {
   Driver::get().tick(false)
 }
 
-/// Deallocates the lio I/O driver, freeing all resources.
-///
-/// This must be called after `exit()` to properly clean up the driver.
-/// Calling this before `exit()` or when the driver is not initialized will panic.
+/// Shuts down the lio I/O driver, waiting up to one second for in-flight
+/// operations. See [`exit_timeout`].
 pub fn exit() {
-  Driver::exit()
+  exit_timeout(Duration::from_secs(1))
+}
+
+/// Shuts down the lio I/O driver and frees all its resources.
+///
+/// - Operations started after this is called complete with `ECANCELED`.
+/// - The background thread, if running, is stopped and joined.
+/// - In-flight operations get up to `timeout` to complete, and are then
+///   cancelled. Their callbacks are called and their futures woken as usual.
+/// - Operations the kernel still hasn't given back shortly after cancelling are
+///   leaked instead of freed, and their callbacks are dropped without being called.
+///
+/// Futures can't be polled after this returns, so await them before shutting down.
+///
+/// # Panics
+///
+/// If the driver isn't initialized, or is already shutting down. Also if called
+/// from a [`when_done`](OperationProgress::when_done) callback, since the
+/// driver is still in use there.
+pub fn exit_timeout(timeout: Duration) {
+  Driver::exit(timeout)
 }
 
 // #[cfg(any(loom, test))]
@@ -523,3 +1071,15 @@ pub fn exit() {
 pub fn init() {
   Driver::init();
 }
+
+/// Starts the driver on the [`Simulated`] backend instead of the kernel, for
+/// deterministic tests. Shut it down with [`exit`] as usual.
+///
+/// # Panics
+///
+/// If the driver is already initialized.
+#[cfg(feature = "sim")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sim")))]
+pub fn init_simulated(sim: Simulated) {
+  Driver::init_with(driver::Backend::Simulated(Box::new(sim)));
+}
@@ -31,3 +31,21 @@ macro_rules! impl_no_readyness {
     }
   };
 }
+
+macro_rules! impl_trace {
+  (fd) => {
+    #[cfg(feature = "tracing")]
+    fn trace_fd(&self) -> Option<std::os::fd::RawFd> {
+      Some(self.fd)
+    }
+  };
+
+  (fd, $len:ident) => {
+    impl_trace!(fd);
+
+    #[cfg(feature = "tracing")]
+    fn trace_len(&self) -> Option<usize> {
+      self.buf.as_ref().map(|buf| buf.$len())
+    }
+  };
+}
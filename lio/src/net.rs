@@ -0,0 +1,195 @@
+//! Sockets that close themselves, built on the operations in the crate root.
+//!
+//! A [`Socket`] owns its descriptor, and closes it through the driver when
+//! dropped, without blocking. Use [`Socket::close`] to wait for the close and
+//! see its error. Accepted connections are [`Socket`]s as well, so they can't
+//! leak on error paths. Its methods borrow the socket until the operation
+//! completes, and if their future is dropped before that, the socket stays open
+//! until the operation did.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use lio::net::Socket;
+//! use socket2::{Domain, Type};
+//!
+//! async fn echo_once() -> std::io::Result<()> {
+//!     let listener = Socket::new(Domain::IPV4, Type::STREAM, None).await?;
+//!     listener.bind("127.0.0.1:0".parse::<std::net::SocketAddr>().unwrap()).await?;
+//!     listener.listen(128).await?;
+//!
+//!     let (conn, _addr) = listener.accept().await?;
+//!     let (res, buf) = conn.recv(Vec::with_capacity(1024), None).await;
+//!     res?;
+//!     let (res, _buf) = conn.send_all(buf).await;
+//!     res?;
+//!     Ok(())
+//! }
+//! ```
+
+use std::{
+  io,
+  net::Shutdown,
+  os::fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, OwnedFd, RawFd},
+  sync::Arc,
+};
+
+use crate::{
+  BufResult, ExactResult, IoBuf, IoBufMut, MsgFlags, SocketAddress,
+  owned::Owned, sockopt::SockOpt,
+};
+
+/// A socket, closed when dropped. See the [module docs](self).
+#[derive(Debug)]
+pub struct Socket {
+  fd: Arc<Owned>,
+}
+
+impl Socket {
+  /// Creates a socket, see [`socket`](crate::socket).
+  pub async fn new(
+    domain: socket2::Domain,
+    ty: socket2::Type,
+    proto: Option<socket2::Protocol>,
+  ) -> io::Result<Socket> {
+    let fd = crate::socket(domain, ty, proto).await?;
+    // SAFETY: The descriptor was just created, and nothing else owns it.
+    Ok(unsafe { Socket::from_raw_fd(fd) })
+  }
+
+  /// Binds the socket to `addr`, see [`bind`](crate::bind).
+  pub async fn bind(&self, addr: impl Into<SocketAddress>) -> io::Result<()> {
+    self.fd.hold(crate::bind(self.as_raw_fd(), addr)).await
+  }
+
+  /// Marks the socket as accepting connections, see [`listen`](crate::listen).
+  pub async fn listen(&self, backlog: i32) -> io::Result<()> {
+    self.fd.hold(crate::listen(self.as_raw_fd(), backlog)).await
+  }
+
+  /// Connects the socket to `addr`, see [`connect`](crate::connect).
+  pub async fn connect(
+    &self,
+    addr: impl Into<SocketAddress>,
+  ) -> io::Result<()> {
+    self.fd.hold(crate::connect(self.as_raw_fd(), addr)).await
+  }
+
+  /// Accepts a connection, see [`accept`](crate::accept).
+  pub async fn accept(&self) -> io::Result<(Socket, SocketAddress)> {
+    let (fd, addr) = self.fd.hold(crate::accept(self.as_raw_fd())).await?;
+    // SAFETY: The descriptor was just accepted, and nothing else owns it.
+    Ok((unsafe { Socket::from_raw_fd(fd) }, addr))
+  }
+
+  /// Returns the address the socket is bound to, see
+  /// [`local_addr`](crate::local_addr).
+  pub async fn local_addr(&self) -> io::Result<SocketAddress> {
+    self.fd.hold(crate::local_addr(self.as_raw_fd())).await
+  }
+
+  /// Returns the address of the connected peer, see
+  /// [`peer_addr`](crate::peer_addr).
+  pub async fn peer_addr(&self) -> io::Result<SocketAddress> {
+    self.fd.hold(crate::peer_addr(self.as_raw_fd())).await
+  }
+
+  /// Sends `buf`, see [`send`](crate::send).
+  pub async fn send<B: IoBuf>(
+    &self,
+    buf: B,
+    flags: Option<MsgFlags>,
+  ) -> BufResult<i32, B> {
+    self.fd.hold(crate::send(self.as_raw_fd(), buf, flags)).await
+  }
+
+  /// Receives into the spare capacity of `buf`, see [`recv`](crate::recv).
+  pub async fn recv<B: IoBufMut>(
+    &self,
+    buf: B,
+    flags: Option<MsgFlags>,
+  ) -> BufResult<i32, B> {
+    self.fd.hold(crate::recv(self.as_raw_fd(), buf, flags)).await
+  }
+
+  /// Sends all of `buf`, see [`send_all`](crate::send_all).
+  pub async fn send_all<B: IoBuf>(&self, buf: B) -> ExactResult<B> {
+    self.fd.hold(crate::send_all(self.as_raw_fd(), buf)).await
+  }
+
+  /// Fills the spare capacity of `buf`, see [`recv_exact`](crate::recv_exact).
+  pub async fn recv_exact<B: IoBufMut>(&self, buf: B) -> ExactResult<B> {
+    self.fd.hold(crate::recv_exact(self.as_raw_fd(), buf)).await
+  }
+
+  /// Shuts down reading, writing or both, see [`shutdown`](crate::shutdown).
+  pub async fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+    self.fd.hold(crate::shutdown(self.as_raw_fd(), how)).await
+  }
+
+  /// Sets a socket option, see [`setsockopt`](crate::setsockopt).
+  pub async fn setsockopt<O: SockOpt>(
+    &self,
+    opt: O,
+    value: O::Value,
+  ) -> io::Result<()> {
+    self.fd.hold(crate::setsockopt(self.as_raw_fd(), opt, value)).await
+  }
+
+  /// Reads a socket option, see [`getsockopt`](crate::getsockopt).
+  pub async fn getsockopt<O: SockOpt>(&self, opt: O) -> io::Result<O::Value> {
+    self.fd.hold(crate::getsockopt(self.as_raw_fd(), opt)).await
+  }
+
+  /// Closes the socket and waits for it, unlike dropping it.
+  pub async fn close(self) -> io::Result<()> {
+    Owned::close(self.fd).await
+  }
+
+  /// Takes the descriptor out, so it's no longer closed on drop.
+  ///
+  /// Fails, handing the socket back, while an operation whose future was dropped
+  /// still uses the descriptor, since that one closes it once it completed.
+  pub fn try_into_owned(self) -> Result<OwnedFd, Socket> {
+    Owned::try_into_owned(self.fd).map_err(|fd| Socket { fd })
+  }
+}
+
+impl AsFd for Socket {
+  fn as_fd(&self) -> BorrowedFd<'_> {
+    self.fd.as_fd()
+  }
+}
+
+impl AsRawFd for Socket {
+  fn as_raw_fd(&self) -> RawFd {
+    self.fd.as_raw_fd()
+  }
+}
+
+impl From<OwnedFd> for Socket {
+  fn from(fd: OwnedFd) -> Self {
+    Socket { fd: Owned::new(fd) }
+  }
+}
+
+impl TryFrom<Socket> for OwnedFd {
+  type Error = Socket;
+
+  fn try_from(socket: Socket) -> Result<Self, Socket> {
+    socket.try_into_owned()
+  }
+}
+
+impl From<socket2::Socket> for Socket {
+  fn from(socket: socket2::Socket) -> Self {
+    Socket::from(OwnedFd::from(socket))
+  }
+}
+
+impl FromRawFd for Socket {
+  unsafe fn from_raw_fd(fd: RawFd) -> Self {
+    // SAFETY: Upheld by the caller.
+    Socket::from(unsafe { OwnedFd::from_raw_fd(fd) })
+  }
+}
@@ -11,6 +11,11 @@ macro_rules! syscall {
 }
 
 use std::io;
+#[cfg(feature = "sim")]
+use std::task::Poll;
+
+#[cfg(feature = "sim")]
+use crate::backends::SimState;
 
 #[cfg(not(linux))]
 use std::os::fd::RawFd;
@@ -19,57 +24,82 @@ mod accept;
 mod bind;
 mod close;
 mod connect;
+mod custom;
 mod listen;
 pub(crate) mod net_utils;
 mod openat;
+#[cfg(linux)]
+mod openat2;
 mod read;
 mod recv;
+mod renameat;
 mod send;
 mod socket;
+mod sockname;
+mod sockopt;
 
 mod fsync;
+#[cfg(linux)]
+mod futex;
 mod linkat;
 #[cfg(linux)]
 pub mod nop;
 mod shutdown;
 mod symlink;
 #[cfg(linux)]
+mod sync_file_range;
+#[cfg(linux)]
 mod tee;
 #[cfg(linux)]
 mod timeout;
 mod truncate;
+mod unlinkat;
+mod waitid;
 mod write;
 
 pub use accept::*;
 pub use bind::*;
 pub use close::*;
 pub use connect::*;
+pub use custom::CustomOperation;
 pub use fsync::*;
+#[cfg(linux)]
+pub use futex::*;
 pub use linkat::*;
 pub use listen::*;
 #[cfg(linux)]
 pub(crate) use nop::*;
 pub use openat::*;
+#[cfg(linux)]
+pub use openat2::*;
 pub use read::*;
 pub use recv::*;
+pub use renameat::*;
 pub use send::*;
 pub use shutdown::*;
 pub use socket::*;
+pub use sockname::*;
+pub use sockopt::*;
 pub use symlink::*;
 #[cfg(linux)]
+pub use sync_file_range::*;
+#[cfg(linux)]
 pub use timeout::*;
 
 #[cfg(linux)]
 pub use tee::*;
 
 pub use truncate::*;
+pub use unlinkat::*;
+pub use waitid::*;
 pub use write::*;
 
 /// Done to disallow someone creating a operation outside of lio, which will cause issues.
 trait Sealed {}
 impl<O: Operation> Sealed for O {}
 
-// Safety: Decides if resources can be leaked when using OperationProgress::detach
+// Safety: Decides if resources can be leaked when using OperationProgress::detach,
+// anything the operation creates must be cleaned up by Operation::release.
 #[allow(private_bounds)]
 pub unsafe trait DetachSafe: Sealed {}
 
@@ -81,6 +111,13 @@ pub trait Operation: Sealed {
   /// i32 is guarranteed to be >= 0.
   fn result(&mut self, _ret: io::Result<i32>) -> Self::Result;
 
+  /// Cleans up a result nobody will see, because the
+  /// [`OperationProgress`](crate::OperationProgress) was detached or dropped
+  /// before it completed. Operations creating file descriptors close them here.
+  fn release(result: Self::Result) {
+    drop(result);
+  }
+
   #[cfg(linux)]
   const OPCODE: u8;
 
@@ -92,6 +129,15 @@ pub trait Operation: Sealed {
   #[cfg(linux)]
   fn create_entry(&mut self) -> io_uring::squeue::Entry;
 
+  /// Entry used instead of [`Operation::create_entry`] when the kernel doesn't
+  /// support [`Operation::OPCODE`], for operations whose
+  /// [`Operation::run_blocking`] would block the polling thread indefinitely.
+  /// It runs the fallback elsewhere and completes once that finished.
+  #[cfg(linux)]
+  fn fallback_entry(&mut self) -> Option<io_uring::squeue::Entry> {
+    None
+  }
+
   #[cfg(not(linux))]
   const IS_CONNECT: bool = false;
 
@@ -103,6 +149,36 @@ pub trait Operation: Sealed {
 
   #[cfg(not(linux))]
   fn run_blocking(&self) -> io::Result<i32>;
+
+  /// File descriptor recorded on the operation's tracing span.
+  #[cfg(feature = "tracing")]
+  fn trace_fd(&self) -> Option<std::os::fd::RawFd> {
+    None
+  }
+
+  /// Buffer length recorded on the operation's tracing span.
+  #[cfg(feature = "tracing")]
+  fn trace_len(&self) -> Option<usize> {
+    None
+  }
+
+  /// Caps the number of bytes transferred at `len`, for [`Fault::Short`](crate::Fault::Short).
+  #[cfg(feature = "fault_injection")]
+  fn shorten(&mut self, _len: usize) {}
+
+  /// Runs the operation against the [`Simulated`](crate::Simulated) backend.
+  /// Returns `Pending` while it can't complete yet, like a `recv` without data,
+  /// and is then retried on the next tick.
+  #[cfg(feature = "sim")]
+  fn simulate(&mut self, _sim: &mut SimState) -> Poll<io::Result<i32>> {
+    Poll::Ready(Err(io::Error::from_raw_os_error(libc::ENOSYS)))
+  }
+
+  /// Fallback used when the running kernel doesn't support [`Operation::OPCODE`].
+  #[cfg(linux)]
+  fn run_blocking(&self) -> io::Result<i32> {
+    Err(io::Error::from_raw_os_error(libc::ENOSYS))
+  }
 }
 
 #[cfg(not(linux))]
@@ -1,8 +1,7 @@
 use std::{
   cell::UnsafeCell,
   mem::{self},
-  net::SocketAddr,
-  os::fd::RawFd,
+  os::fd::{FromRawFd, OwnedFd, RawFd},
 };
 
 #[cfg(linux)]
@@ -10,22 +9,26 @@ use io_uring::{opcode, squeue, types::Fd};
 
 #[cfg(not(linux))]
 use crate::op::EventType;
-use crate::op::net_utils::libc_socketaddr_into_std;
+use crate::{
+  BorrowFd, SocketAddress,
+  op::{Close, DetachSafe},
+};
 
 use super::Operation;
 
-// Not detach safe.
 pub struct Accept {
   fd: RawFd,
   addr: UnsafeCell<libc::sockaddr_storage>,
   len: UnsafeCell<libc::socklen_t>,
 }
 
+unsafe impl DetachSafe for Accept {}
+
 impl Accept {
-  pub(crate) fn new(fd: RawFd) -> Self {
+  pub(crate) fn new(fd: impl BorrowFd) -> Self {
     let addr: libc::sockaddr_storage = unsafe { mem::zeroed() };
     Self {
-      fd,
+      fd: fd.raw_fd(),
       addr: UnsafeCell::new(addr),
       len: UnsafeCell::new(mem::size_of_val(&addr) as libc::socklen_t),
     }
@@ -33,10 +36,27 @@ impl Accept {
 }
 
 impl Operation for Accept {
-  type Result = std::io::Result<(RawFd, SocketAddr)>;
+  impl_trace!(fd);
+
+  type Result = std::io::Result<(RawFd, SocketAddress)>;
 
   fn result(&mut self, res: std::io::Result<i32>) -> Self::Result {
-    Ok((res?, libc_socketaddr_into_std(self.addr.get())?))
+    let fd = res?;
+    match SocketAddress::from_libc(self.addr.get_mut(), *self.len.get_mut()) {
+      Ok(addr) => Ok((fd, addr)),
+      Err(err) => {
+        // SAFETY: The accepted descriptor isn't handed out.
+        Close::detached(unsafe { OwnedFd::from_raw_fd(fd) });
+        Err(err)
+      }
+    }
+  }
+
+  fn release(result: Self::Result) {
+    if let Ok((fd, _)) = result {
+      // SAFETY: Nobody else will see the accepted descriptor.
+      Close::detached(unsafe { OwnedFd::from_raw_fd(fd) });
+    }
   }
 
   #[cfg(linux)]
@@ -52,6 +72,18 @@ impl Operation for Accept {
     .build()
   }
 
+  #[cfg(feature = "sim")]
+  fn simulate(
+    &mut self,
+    sim: &mut crate::backends::SimState,
+  ) -> std::task::Poll<std::io::Result<i32>> {
+    sim.accept(self.fd).map_ok(|(fd, addr)| {
+      (*self.addr.get_mut(), *self.len.get_mut()) =
+        SocketAddress::from(addr).to_libc();
+      fd
+    })
+  }
+
   #[cfg(not(linux))]
   const EVENT_TYPE: Option<EventType> = Some(EventType::Read);
 
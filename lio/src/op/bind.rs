@@ -1,26 +1,30 @@
-use std::{cell::UnsafeCell, io, mem, net::SocketAddr, os::fd::RawFd};
+use std::{cell::UnsafeCell, io, os::fd::RawFd};
 
 #[cfg(linux)]
 use io_uring::types::Fd;
 
-use crate::op::{DetachSafe, net_utils::std_socketaddr_into_libc};
+use crate::{BorrowFd, SocketAddress, op::DetachSafe};
 
 use super::Operation;
 
 pub struct Bind {
   fd: RawFd,
-  addr: UnsafeCell<libc::sockaddr_storage>, // addr: SocketAddr,
+  addr: UnsafeCell<libc::sockaddr_storage>,
+  len: libc::socklen_t,
 }
 
 unsafe impl DetachSafe for Bind {}
 
 impl Bind {
-  pub(crate) fn new(fd: RawFd, addr: SocketAddr) -> Self {
-    Self { fd, addr: UnsafeCell::new(std_socketaddr_into_libc(addr)) }
+  pub(crate) fn new(fd: impl BorrowFd, addr: impl Into<SocketAddress>) -> Self {
+    let (addr, len) = addr.into().to_libc();
+    Self { fd: fd.raw_fd(), addr: UnsafeCell::new(addr), len }
   }
 }
 
 impl Operation for Bind {
+  impl_trace!(fd);
+
   impl_result!(());
 
   #[cfg(linux)]
@@ -28,27 +32,23 @@ impl Operation for Bind {
 
   #[cfg(linux)]
   fn create_entry(&mut self) -> io_uring::squeue::Entry {
-    io_uring::opcode::Bind::new(
-      Fd(self.fd),
-      self.addr.get().cast(),
-      mem::size_of_val(&self.addr) as libc::socklen_t,
-    )
-    .build()
+    io_uring::opcode::Bind::new(Fd(self.fd), self.addr.get().cast(), self.len)
+      .build()
+  }
+
+  #[cfg(feature = "sim")]
+  fn simulate(
+    &mut self,
+    sim: &mut crate::backends::SimState,
+  ) -> std::task::Poll<std::io::Result<i32>> {
+    let addr = super::net_utils::libc_socketaddr_into_std(self.addr.get())?;
+    std::task::Poll::Ready(sim.bind(self.fd, addr))
   }
 
   impl_no_readyness!();
 
   #[cfg(not(linux))]
   fn run_blocking(&self) -> io::Result<i32> {
-    let storage = unsafe { &*self.addr.get() };
-    let addrlen = if storage.ss_family == libc::AF_INET as libc::sa_family_t {
-      mem::size_of::<libc::sockaddr_in>()
-    } else if storage.ss_family == libc::AF_INET6 as libc::sa_family_t {
-      mem::size_of::<libc::sockaddr_in6>()
-    } else {
-      mem::size_of::<libc::sockaddr_storage>()
-    };
-
-    syscall!(bind(self.fd, self.addr.get().cast(), addrlen as libc::socklen_t,))
+    syscall!(bind(self.fd, self.addr.get().cast(), self.len))
   }
 }
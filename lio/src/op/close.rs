@@ -1,8 +1,9 @@
-use std::os::fd::RawFd;
+use std::os::fd::{IntoRawFd, OwnedFd, RawFd};
 
 #[cfg(linux)]
 use io_uring::{opcode, types::Fd};
 
+use crate::driver::Driver;
 use crate::op::DetachSafe;
 
 use super::Operation;
@@ -17,9 +18,27 @@ impl Close {
   pub(crate) fn new(fd: RawFd) -> Self {
     Self { fd }
   }
+
+  /// Closes `fd` without waiting for it, through the driver if it's running,
+  /// and otherwise with a blocking `close`.
+  pub(crate) fn detached(fd: OwnedFd) {
+    match Driver::try_get() {
+      Some(driver) if !driver.is_shutting_down() => {
+        Driver::submit(Close::new(fd.into_raw_fd())).detach()
+      }
+      // Simulated descriptors don't exist in the kernel.
+      #[cfg(feature = "sim")]
+      Some(driver) if driver.is_simulated() => {
+        let _ = fd.into_raw_fd();
+      }
+      _ => drop(fd),
+    }
+  }
 }
 
 impl Operation for Close {
+  impl_trace!(fd);
+
   impl_result!(());
 
   #[cfg(linux)]
@@ -30,6 +49,14 @@ impl Operation for Close {
     opcode::Close::new(Fd(self.fd)).build()
   }
 
+  #[cfg(feature = "sim")]
+  fn simulate(
+    &mut self,
+    sim: &mut crate::backends::SimState,
+  ) -> std::task::Poll<std::io::Result<i32>> {
+    std::task::Poll::Ready(sim.close(self.fd))
+  }
+
   impl_no_readyness!();
 
   #[cfg(not(linux))]
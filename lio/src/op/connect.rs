@@ -1,51 +1,42 @@
 use std::cell::UnsafeCell;
-use std::mem;
-use std::net::SocketAddr;
 use std::os::fd::RawFd;
 use std::sync::atomic::{AtomicBool, Ordering};
 
 #[cfg(linux)]
 use io_uring::types::Fd;
 
+use crate::BorrowFd;
+use crate::SocketAddress;
 use crate::op::DetachSafe;
 #[cfg(not(linux))]
 use crate::op::EventType;
-use crate::op::net_utils::std_socketaddr_into_libc;
 
 use super::Operation;
 
 pub struct Connect {
   fd: RawFd,
   addr: UnsafeCell<libc::sockaddr_storage>,
+  len: libc::socklen_t,
   connect_called: AtomicBool,
 }
 
 unsafe impl DetachSafe for Connect {}
 
 impl Connect {
-  pub(crate) fn new(fd: RawFd, addr: SocketAddr) -> Self {
+  pub(crate) fn new(fd: impl BorrowFd, addr: impl Into<SocketAddress>) -> Self {
+    let (addr, len) = addr.into().to_libc();
     Self {
-      fd,
-      addr: UnsafeCell::new(std_socketaddr_into_libc(addr)),
+      fd: fd.raw_fd(),
+      addr: UnsafeCell::new(addr),
+      len,
       connect_called: AtomicBool::new(false),
     }
   }
-
-  fn get_addrlen(&self) -> libc::socklen_t {
-    let storage = unsafe { &*self.addr.get() };
-    let addrlen = if storage.ss_family == libc::AF_INET as libc::sa_family_t {
-      mem::size_of::<libc::sockaddr_in>()
-    } else if storage.ss_family == libc::AF_INET6 as libc::sa_family_t {
-      mem::size_of::<libc::sockaddr_in6>()
-    } else {
-      mem::size_of::<libc::sockaddr_storage>()
-    };
-
-    addrlen as libc::socklen_t
-  }
 }
 
 impl Operation for Connect {
+  impl_trace!(fd);
+
   impl_result!(());
 
   #[cfg(linux)]
@@ -56,11 +47,20 @@ impl Operation for Connect {
     io_uring::opcode::Connect::new(
       Fd(self.fd),
       self.addr.get().cast(),
-      self.get_addrlen(),
+      self.len,
     )
     .build()
   }
 
+  #[cfg(feature = "sim")]
+  fn simulate(
+    &mut self,
+    sim: &mut crate::backends::SimState,
+  ) -> std::task::Poll<std::io::Result<i32>> {
+    let addr = super::net_utils::libc_socketaddr_into_std(self.addr.get())?;
+    std::task::Poll::Ready(sim.connect(self.fd, addr))
+  }
+
   #[cfg(not(linux))]
   const IS_CONNECT: bool = true;
 
@@ -74,8 +74,7 @@ impl Operation for Connect {
 
   #[cfg(not(linux))]
   fn run_blocking(&self) -> std::io::Result<i32> {
-    let result =
-      syscall!(connect(self.fd, self.addr.get().cast(), self.get_addrlen(),));
+    let result = syscall!(connect(self.fd, self.addr.get().cast(), self.len,));
 
     // Track if this is the first connect() call for this operation
     let is_first_call = !self.connect_called.swap(true, Ordering::SeqCst);
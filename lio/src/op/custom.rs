@@ -0,0 +1,144 @@
+use std::io;
+
+#[cfg(not(linux))]
+use std::os::fd::RawFd;
+
+#[cfg(not(linux))]
+use super::EventType;
+use super::Operation;
+
+/// An operation defined outside of lio, submitted with [`submit`](crate::submit).
+///
+/// Custom operations go through the same machinery as lio's own: they can be
+/// awaited, given a [`when_done`](crate::OperationProgress::when_done)
+/// callback, batched, and detached if they implement
+/// [`DetachSafe`](super::DetachSafe).
+///
+/// # Safety
+///
+/// - Everything the entry from [`create_entry`](CustomOperation::create_entry)
+///   points to must be owned by the operation, or live for `'static`. Only the
+///   io_uring backend calls `create_entry`, on an operation it already boxed,
+///   so it isn't moved until [`result`](CustomOperation::result), and isn't
+///   dropped before the kernel gave it back, leaking it if it never does.
+/// - Without `create_entry`, through
+///   [`run_blocking`](CustomOperation::run_blocking) or other backends, the
+///   operation may be moved between calls. Don't keep pointers into it there.
+/// - The entry's `user_data` is overwritten by lio, don't rely on it.
+/// - The entry must complete with a single completion. Multishot requests and
+///   linked entries aren't supported.
+/// - `result` must not assume the result is positive beyond what the opcode
+///   promises. Errors are passed as `Err`.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[cfg(target_os = "linux")]
+/// # {
+/// use lio::{io_uring, op::CustomOperation};
+///
+/// struct Nop;
+///
+/// unsafe impl CustomOperation for Nop {
+///   type Result = std::io::Result<()>;
+///
+///   fn result(&mut self, res: std::io::Result<i32>) -> Self::Result {
+///     res.map(drop)
+///   }
+///
+///   const OPCODE: u8 = io_uring::opcode::Nop::CODE;
+///
+///   fn create_entry(&mut self) -> io_uring::squeue::Entry {
+///     io_uring::opcode::Nop::new().build()
+///   }
+/// }
+///
+/// lio::init();
+/// lio::submit(Nop).when_done(|res| res.unwrap());
+/// # lio::exit();
+/// # }
+/// ```
+pub unsafe trait CustomOperation: Send {
+  type Result;
+
+  /// Turns the completion into the operation's result, called exactly once.
+  fn result(&mut self, res: io::Result<i32>) -> Self::Result;
+
+  /// Cleans up a result nobody will see, because the
+  /// [`OperationProgress`](crate::OperationProgress) was detached or dropped
+  /// before it completed.
+  fn release(result: Self::Result) {
+    drop(result);
+  }
+
+  /// The io_uring opcode of [`create_entry`](CustomOperation::create_entry).
+  #[cfg(linux)]
+  const OPCODE: u8;
+
+  /// If the running kernel can run the entry. Otherwise
+  /// [`run_blocking`](CustomOperation::run_blocking) runs instead.
+  #[cfg(linux)]
+  fn entry_supported(probe: &io_uring::Probe) -> bool {
+    probe.is_supported(Self::OPCODE)
+  }
+
+  #[cfg(linux)]
+  fn create_entry(&mut self) -> io_uring::squeue::Entry;
+
+  /// Readiness to wait for on `fd` before calling
+  /// [`run_blocking`](CustomOperation::run_blocking). If `None`, it runs
+  /// right away.
+  #[cfg(not(linux))]
+  const EVENT_TYPE: Option<EventType> = None;
+
+  /// The file descriptor [`EVENT_TYPE`](CustomOperation::EVENT_TYPE) is
+  /// waited for on.
+  #[cfg(not(linux))]
+  fn fd(&self) -> Option<RawFd> {
+    None
+  }
+
+  /// Runs the operation without io_uring, on the thread submitting it or,
+  /// with [`EVENT_TYPE`](CustomOperation::EVENT_TYPE), the one polling once
+  /// the file descriptor is ready.
+  fn run_blocking(&self) -> io::Result<i32> {
+    Err(io::Error::from_raw_os_error(libc::ENOSYS))
+  }
+}
+
+impl<C: CustomOperation> Operation for C {
+  type Result = C::Result;
+
+  fn result(&mut self, res: io::Result<i32>) -> Self::Result {
+    <C as CustomOperation>::result(self, res)
+  }
+
+  fn release(result: Self::Result) {
+    <C as CustomOperation>::release(result)
+  }
+
+  #[cfg(linux)]
+  const OPCODE: u8 = C::OPCODE;
+
+  #[cfg(linux)]
+  fn entry_supported(probe: &io_uring::Probe) -> bool {
+    <C as CustomOperation>::entry_supported(probe)
+  }
+
+  #[cfg(linux)]
+  fn create_entry(&mut self) -> io_uring::squeue::Entry {
+    <C as CustomOperation>::create_entry(self)
+  }
+
+  #[cfg(not(linux))]
+  const EVENT_TYPE: Option<EventType> = C::EVENT_TYPE;
+
+  #[cfg(not(linux))]
+  fn fd(&self) -> Option<RawFd> {
+    <C as CustomOperation>::fd(self)
+  }
+
+  fn run_blocking(&self) -> io::Result<i32> {
+    <C as CustomOperation>::run_blocking(self)
+  }
+}
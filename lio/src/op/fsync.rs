@@ -1,8 +1,9 @@
 use std::os::fd::RawFd;
 
 #[cfg(linux)]
-use io_uring::types::Fd;
+use io_uring::types::{Fd, FsyncFlags};
 
+use crate::BorrowFd;
 use crate::op::DetachSafe;
 
 use super::Operation;
@@ -12,14 +13,16 @@ pub struct Fsync {
 }
 
 impl Fsync {
-  pub(crate) fn new(fd: RawFd) -> Self {
-    Self { fd }
+  pub(crate) fn new(fd: impl BorrowFd) -> Self {
+    Self { fd: fd.raw_fd() }
   }
 }
 
 unsafe impl DetachSafe for Fsync {}
 
 impl Operation for Fsync {
+  impl_trace!(fd);
+
   impl_result!(());
 
   #[cfg(linux)]
@@ -30,6 +33,14 @@ impl Operation for Fsync {
     io_uring::opcode::Fsync::new(Fd(self.fd)).build()
   }
 
+  #[cfg(feature = "sim")]
+  fn simulate(
+    &mut self,
+    sim: &mut crate::backends::SimState,
+  ) -> std::task::Poll<std::io::Result<i32>> {
+    std::task::Poll::Ready(sim.check_fd(self.fd))
+  }
+
   impl_no_readyness!();
 
   #[cfg(not(linux))]
@@ -37,3 +48,52 @@ impl Operation for Fsync {
     syscall!(fsync(self.fd))
   }
 }
+
+/// Like [`Fsync`], but only flushes the metadata needed to read the data back.
+pub struct Fdatasync {
+  fd: RawFd,
+}
+
+impl Fdatasync {
+  pub(crate) fn new(fd: impl BorrowFd) -> Self {
+    Self { fd: fd.raw_fd() }
+  }
+}
+
+unsafe impl DetachSafe for Fdatasync {}
+
+impl Operation for Fdatasync {
+  impl_trace!(fd);
+
+  impl_result!(());
+
+  #[cfg(linux)]
+  const OPCODE: u8 = 3;
+
+  #[cfg(linux)]
+  fn create_entry(&mut self) -> io_uring::squeue::Entry {
+    io_uring::opcode::Fsync::new(Fd(self.fd))
+      .flags(FsyncFlags::DATASYNC)
+      .build()
+  }
+
+  #[cfg(feature = "sim")]
+  fn simulate(
+    &mut self,
+    sim: &mut crate::backends::SimState,
+  ) -> std::task::Poll<std::io::Result<i32>> {
+    std::task::Poll::Ready(sim.check_fd(self.fd))
+  }
+
+  impl_no_readyness!();
+
+  #[cfg(not(linux))]
+  fn run_blocking(&self) -> std::io::Result<i32> {
+    // Apple platforms don't expose fdatasync, fsync is the closest equivalent.
+    #[cfg(apple)]
+    return syscall!(fsync(self.fd));
+
+    #[cfg(not(apple))]
+    syscall!(fdatasync(self.fd))
+  }
+}
@@ -0,0 +1,198 @@
+use std::{
+  io,
+  os::fd::{AsRawFd, FromRawFd, OwnedFd},
+  sync::Arc,
+  thread,
+};
+
+#[cfg(linux)]
+use io_uring::{opcode, squeue, types::Fd};
+
+use crate::op::DetachSafe;
+
+use super::Operation;
+
+/// `futex2(2)` flag for a 32-bit futex word, not exported by libc.
+const FUTEX2_SIZE_U32: u32 = 0x02;
+/// Match any waiter, regardless of the bitset it waits with.
+const FUTEX_BITSET_MATCH_ANY: u32 = libc::FUTEX_BITSET_MATCH_ANY as u32;
+
+/// Waits on a futex word until woken by [`FutexWake`] (or `FUTEX_WAKE` from another process).
+///
+/// The futex isn't process-private, so waiters and wakers can live in different
+/// processes sharing the same memory mapping.
+pub struct FutexWait {
+  futex: *const u32,
+  expected: u32,
+  /// eventfd of the helper thread waiting in `futex(2)`, when the kernel has
+  /// no futex ops, see `fallback_entry`.
+  helper: Option<io::Result<Arc<OwnedFd>>>,
+  /// Read from the helper's eventfd: 1 once woken, or 1 + errno.
+  count: u64,
+}
+
+// The pointer is only passed to the kernel, see `futex_wait`.
+unsafe impl Send for FutexWait {}
+// Nothing is allocated, the wait is just forgotten. A helper thread keeps
+// waiting until the word is woken, and closes its eventfd then.
+unsafe impl DetachSafe for FutexWait {}
+
+impl FutexWait {
+  pub(crate) fn new(futex: *const u32, expected: u32) -> Self {
+    Self { futex, expected, helper: None, count: 0 }
+  }
+
+  /// Waits in `futex(2)` on a new thread, which writes the outcome to the
+  /// returned eventfd.
+  fn spawn_helper(&self) -> io::Result<Arc<OwnedFd>> {
+    let fd = syscall!(eventfd(0, libc::EFD_CLOEXEC))?;
+    // SAFETY: Just created, and owned by nobody else.
+    let event = Arc::new(unsafe { OwnedFd::from_raw_fd(fd) });
+
+    let helper_event = event.clone();
+    let futex = self.futex as usize;
+    let expected = self.expected;
+    thread::Builder::new().name("lio-futex-wait".into()).spawn(move || {
+      let count = match wait_blocking(futex as *const u32, expected) {
+        Ok(()) => 1,
+        Err(err) => 1 + err.raw_os_error().unwrap_or(libc::EIO) as u64,
+      };
+      // Can't fail: the counter is only written once.
+      let _ = syscall!(write(
+        helper_event.as_raw_fd(),
+        (&count as *const u64).cast(),
+        8
+      ));
+    })?;
+    Ok(event)
+  }
+}
+
+/// `FUTEX_WAIT_BITSET` without a timeout, blocking the calling thread.
+fn wait_blocking(futex: *const u32, expected: u32) -> io::Result<()> {
+  loop {
+    let res = syscall!(syscall(
+      libc::SYS_futex,
+      futex,
+      libc::FUTEX_WAIT_BITSET,
+      expected,
+      std::ptr::null::<libc::timespec>(),
+      std::ptr::null::<u32>(),
+      FUTEX_BITSET_MATCH_ANY
+    ));
+    match res {
+      Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+      res => return res.map(drop),
+    }
+  }
+}
+
+impl Operation for FutexWait {
+  type Result = io::Result<()>;
+
+  fn result(&mut self, res: io::Result<i32>) -> Self::Result {
+    let res = match self.helper.take() {
+      None => res,
+      Some(Err(err)) => Err(err),
+      Some(Ok(_event)) => res.and_then(|_| match self.count {
+        1 => Ok(0),
+        count => Err(io::Error::from_raw_os_error((count - 1) as i32)),
+      }),
+    };
+    match res {
+      Ok(_) => Ok(()),
+      // The futex word didn't contain `expected` when the wait started.
+      Err(err) if err.raw_os_error() == Some(libc::EAGAIN) => Ok(()),
+      Err(err) => Err(err),
+    }
+  }
+
+  #[cfg(linux)]
+  const OPCODE: u8 = 51;
+
+  #[cfg(linux)]
+  fn create_entry(&mut self) -> squeue::Entry {
+    opcode::FutexWait::new(
+      self.futex,
+      self.expected as u64,
+      FUTEX_BITSET_MATCH_ANY as u64,
+      FUTEX2_SIZE_U32,
+    )
+    .build()
+  }
+
+  // A `futex(2)` wait would block the thread polling it, so it runs on a
+  // helper thread instead, and the entry waits for that one to finish.
+  #[cfg(linux)]
+  fn fallback_entry(&mut self) -> Option<squeue::Entry> {
+    let helper = self.spawn_helper();
+    let entry = match &helper {
+      Ok(event) => opcode::Read::new(
+        Fd(event.as_raw_fd()),
+        (&mut self.count as *mut u64).cast(),
+        8,
+      )
+      .build(),
+      // Fails in `result`.
+      Err(_) => opcode::Nop::new().build(),
+    };
+    self.helper = Some(helper);
+    Some(entry)
+  }
+
+  impl_no_readyness!();
+}
+
+/// Wakes at most `count` waiters of a futex word.
+pub struct FutexWake {
+  futex: *const u32,
+  count: u32,
+}
+
+// The pointer is only passed to the kernel, see `futex_wake`.
+unsafe impl Send for FutexWake {}
+unsafe impl DetachSafe for FutexWake {}
+
+impl FutexWake {
+  pub(crate) fn new(futex: *const u32, count: u32) -> Self {
+    Self { futex, count }
+  }
+}
+
+impl Operation for FutexWake {
+  /// Number of waiters woken.
+  type Result = io::Result<u32>;
+
+  fn result(&mut self, res: io::Result<i32>) -> Self::Result {
+    res.map(|woken| woken as u32)
+  }
+
+  #[cfg(linux)]
+  const OPCODE: u8 = 52;
+
+  #[cfg(linux)]
+  fn create_entry(&mut self) -> squeue::Entry {
+    opcode::FutexWake::new(
+      self.futex,
+      self.count as u64,
+      FUTEX_BITSET_MATCH_ANY as u64,
+      FUTEX2_SIZE_U32,
+    )
+    .build()
+  }
+
+  impl_no_readyness!();
+
+  fn run_blocking(&self) -> io::Result<i32> {
+    syscall!(syscall(
+      libc::SYS_futex,
+      self.futex,
+      libc::FUTEX_WAKE_BITSET,
+      self.count,
+      std::ptr::null::<libc::timespec>(),
+      std::ptr::null::<u32>(),
+      FUTEX_BITSET_MATCH_ANY
+    ))
+    .map(|ret| ret as i32)
+  }
+}
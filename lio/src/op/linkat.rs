@@ -7,6 +7,7 @@ use std::{
 #[cfg(linux)]
 use io_uring::types::Fd;
 
+use crate::BorrowFd;
 use crate::op::DetachSafe;
 
 use super::Operation;
@@ -23,17 +24,17 @@ unsafe impl DetachSafe for LinkAt {}
 // TODO: test
 impl LinkAt {
   pub(crate) fn new(
-    old_dir_fd: RawFd,
+    old_dir_fd: impl BorrowFd,
     old_path: impl AsRef<Path>,
-    new_dir_fd: RawFd,
+    new_dir_fd: impl BorrowFd,
     new_path: impl AsRef<Path>,
   ) -> Result<Self, NulError> {
     let old_path_osstr = old_path.as_ref().as_os_str().to_os_string();
     let new_path_osstr = new_path.as_ref().as_os_str().to_os_string();
     Ok(Self {
-      old_dir_fd,
+      old_dir_fd: old_dir_fd.raw_fd(),
       old_path: CString::new(old_path_osstr.into_vec())?,
-      new_dir_fd,
+      new_dir_fd: new_dir_fd.raw_fd(),
       new_path: CString::new(new_path_osstr.into_vec())?,
     })
   }
@@ -58,6 +59,19 @@ impl Operation for LinkAt {
     .build()
   }
 
+  #[cfg(feature = "sim")]
+  fn simulate(
+    &mut self,
+    sim: &mut crate::backends::SimState,
+  ) -> std::task::Poll<std::io::Result<i32>> {
+    std::task::Poll::Ready(sim.link(
+      self.old_dir_fd,
+      &self.old_path,
+      self.new_dir_fd,
+      &self.new_path,
+    ))
+  }
+
   #[cfg(not(linux))]
   fn run_blocking(&self) -> std::io::Result<i32> {
     syscall!(linkat(
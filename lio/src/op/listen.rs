@@ -3,6 +3,7 @@ use std::os::fd::RawFd;
 #[cfg(linux)]
 use io_uring::types::Fd;
 
+use crate::BorrowFd;
 use crate::op::DetachSafe;
 
 use super::Operation;
@@ -15,12 +16,14 @@ pub struct Listen {
 unsafe impl DetachSafe for Listen {}
 
 impl Listen {
-  pub(crate) fn new(fd: RawFd, backlog: i32) -> Self {
-    Self { fd, backlog }
+  pub(crate) fn new(fd: impl BorrowFd, backlog: i32) -> Self {
+    Self { fd: fd.raw_fd(), backlog }
   }
 }
 
 impl Operation for Listen {
+  impl_trace!(fd);
+
   impl_result!(());
 
   #[cfg(linux)]
@@ -33,6 +36,14 @@ impl Operation for Listen {
     io_uring::opcode::Listen::new(Fd(self.fd), self.backlog).build()
   }
 
+  #[cfg(feature = "sim")]
+  fn simulate(
+    &mut self,
+    sim: &mut crate::backends::SimState,
+  ) -> std::task::Poll<std::io::Result<i32>> {
+    std::task::Poll::Ready(sim.listen(self.fd))
+  }
+
   #[cfg(not(linux))]
   fn run_blocking(&self) -> std::io::Result<i32> {
     syscall!(listen(self.fd, self.backlog))
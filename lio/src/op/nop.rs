@@ -17,4 +17,12 @@ impl Operation for Nop {
   fn create_entry(&mut self) -> io_uring::squeue::Entry {
     io_uring::opcode::Nop::new().build()
   }
+
+  #[cfg(feature = "sim")]
+  fn simulate(
+    &mut self,
+    _sim: &mut crate::backends::SimState,
+  ) -> std::task::Poll<std::io::Result<i32>> {
+    std::task::Poll::Ready(Ok(0))
+  }
 }
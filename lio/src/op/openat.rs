@@ -3,17 +3,35 @@ use std::{ffi::CString, os::fd::RawFd};
 #[cfg(linux)]
 use io_uring::types::Fd;
 
+use crate::BorrowFd;
+use crate::OpenFlags;
+
 use super::Operation;
 
 pub struct OpenAt {
   fd: RawFd,
   pathname: CString,
   flags: i32,
+  mode: libc::mode_t,
 }
 
 impl OpenAt {
-  pub(crate) fn new(fd: RawFd, pathname: CString, flags: i32) -> Self {
-    Self { fd, pathname, flags }
+  pub(crate) fn new(
+    fd: impl BorrowFd,
+    pathname: CString,
+    flags: OpenFlags,
+  ) -> Self {
+    Self::with_mode(fd, pathname, flags, 0)
+  }
+
+  /// Same as [`OpenAt::new`] but with a creation mode, used with `O_CREAT`.
+  pub(crate) fn with_mode(
+    fd: impl BorrowFd,
+    pathname: CString,
+    flags: OpenFlags,
+    mode: libc::mode_t,
+  ) -> Self {
+    Self { fd: fd.raw_fd(), pathname, flags: flags.bits(), mode }
   }
 }
 
@@ -29,10 +47,24 @@ impl Operation for OpenAt {
   fn create_entry(&mut self) -> io_uring::squeue::Entry {
     io_uring::opcode::OpenAt::new(Fd(self.fd), self.pathname.as_ptr())
       .flags(self.flags)
+      .mode(self.mode)
       .build()
   }
+
+  #[cfg(feature = "sim")]
+  fn simulate(
+    &mut self,
+    sim: &mut crate::backends::SimState,
+  ) -> std::task::Poll<std::io::Result<i32>> {
+    std::task::Poll::Ready(sim.open(self.fd, &self.pathname, self.flags))
+  }
   #[cfg(not(linux))]
   fn run_blocking(&self) -> std::io::Result<i32> {
-    syscall!(openat(self.fd, self.pathname.as_ptr(), self.flags))
+    syscall!(openat(
+      self.fd,
+      self.pathname.as_ptr(),
+      self.flags,
+      self.mode as libc::c_uint
+    ))
   }
 }
@@ -0,0 +1,161 @@
+use std::{
+  ffi::{CString, NulError},
+  mem,
+  ops::BitOr,
+  os::{fd::RawFd, unix::ffi::OsStringExt},
+  path::Path,
+};
+
+use io_uring::types::Fd;
+
+use crate::BorrowFd;
+use crate::OpenFlags;
+
+use super::Operation;
+
+/// Restricts how the path given to [`openat2`](crate::openat2) is resolved.
+///
+/// Flags can be combined with `|`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Resolve(u64);
+
+impl Resolve {
+  /// No restrictions, same path resolution as `openat`.
+  pub const NONE: Resolve = Resolve(0);
+  /// Don't cross mount points, including bind mounts.
+  pub const NO_XDEV: Resolve = Resolve(libc::RESOLVE_NO_XDEV);
+  /// Don't follow "magic links", like `/proc/[pid]/fd/*`.
+  pub const NO_MAGICLINKS: Resolve = Resolve(libc::RESOLVE_NO_MAGICLINKS);
+  /// Don't follow any symbolic links.
+  pub const NO_SYMLINKS: Resolve = Resolve(libc::RESOLVE_NO_SYMLINKS);
+  /// Fail with `EXDEV` if resolution would escape the directory, for example with `..`,
+  /// absolute paths or symlinks.
+  pub const BENEATH: Resolve = Resolve(libc::RESOLVE_BENEATH);
+  /// Treat the directory as the root (like `chroot`), so `..` and absolute paths
+  /// can't escape it.
+  pub const IN_ROOT: Resolve = Resolve(libc::RESOLVE_IN_ROOT);
+  /// Only succeed if the lookup can be done from cached data, without blocking.
+  pub const CACHED: Resolve = Resolve(libc::RESOLVE_CACHED);
+
+  /// From raw `RESOLVE_*` bits, as used by the C API.
+  #[cfg(feature = "unstable_ffi")]
+  pub(crate) const fn from_bits(bits: u64) -> Resolve {
+    Resolve(bits)
+  }
+
+  /// Raw `RESOLVE_*` bits.
+  pub const fn bits(self) -> u64 {
+    self.0
+  }
+
+  pub const fn contains(self, other: Resolve) -> bool {
+    self.0 & other.0 == other.0
+  }
+}
+
+impl BitOr for Resolve {
+  type Output = Resolve;
+
+  fn bitor(self, rhs: Resolve) -> Resolve {
+    Resolve(self.0 | rhs.0)
+  }
+}
+
+/// How [`openat2`](crate::openat2) opens a file. Equivalent to `struct open_how`.
+///
+/// # Examples
+///
+/// ```rust
+/// use lio::{
+///   OpenFlags,
+///   op::{OpenHow, Resolve},
+/// };
+///
+/// let how = OpenHow::new(OpenFlags::CREAT | OpenFlags::WRONLY)
+///   .mode(0o600)
+///   .resolve(Resolve::BENEATH | Resolve::NO_SYMLINKS);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpenHow {
+  flags: OpenFlags,
+  mode: libc::mode_t,
+  resolve: Resolve,
+}
+
+impl OpenHow {
+  /// Opens with `flags`, like [`openat`](crate::openat).
+  pub fn new(flags: OpenFlags) -> Self {
+    Self { flags, mode: 0, resolve: Resolve::NONE }
+  }
+
+  /// Permissions for a newly created file. Only allowed with `O_CREAT` or `O_TMPFILE`.
+  pub fn mode(mut self, mode: libc::mode_t) -> Self {
+    self.mode = mode;
+    self
+  }
+
+  pub fn resolve(mut self, resolve: Resolve) -> Self {
+    self.resolve = resolve;
+    self
+  }
+
+  fn into_libc(self) -> libc::open_how {
+    let mut how: libc::open_how = unsafe { mem::zeroed() };
+    how.flags = self.flags.bits() as u64;
+    how.mode = self.mode as u64;
+    how.resolve = self.resolve.bits();
+    how
+  }
+}
+
+pub struct OpenAt2 {
+  dir_fd: RawFd,
+  path: CString,
+  how: libc::open_how,
+}
+
+impl OpenAt2 {
+  pub(crate) fn new(
+    dir_fd: impl BorrowFd,
+    path: impl AsRef<Path>,
+    how: OpenHow,
+  ) -> Result<Self, NulError> {
+    let path = path.as_ref().as_os_str().to_os_string();
+    Ok(Self {
+      dir_fd: dir_fd.raw_fd(),
+      path: CString::new(path.into_vec())?,
+      how: how.into_libc(),
+    })
+  }
+}
+
+impl Operation for OpenAt2 {
+  impl_result!(fd);
+
+  #[cfg(linux)]
+  const OPCODE: u8 = 28;
+
+  impl_no_readyness!();
+
+  #[cfg(linux)]
+  fn create_entry(&mut self) -> io_uring::squeue::Entry {
+    io_uring::opcode::OpenAt2::new(
+      Fd(self.dir_fd),
+      self.path.as_ptr(),
+      // Both are the kernel's `struct open_how`.
+      (&self.how as *const libc::open_how).cast(),
+    )
+    .build()
+  }
+
+  fn run_blocking(&self) -> std::io::Result<i32> {
+    syscall!(syscall(
+      libc::SYS_openat2,
+      self.dir_fd,
+      self.path.as_ptr(),
+      &self.how as *const libc::open_how,
+      mem::size_of::<libc::open_how>()
+    ))
+    .map(|fd| fd as i32)
+  }
+}
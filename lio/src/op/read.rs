@@ -3,36 +3,59 @@ use std::{io, os::fd::RawFd};
 #[cfg(linux)]
 use io_uring::types::Fd;
 
-use crate::{BufResult, op::DetachSafe};
+use crate::{BorrowFd, BufResult, IoBufMut, op::DetachSafe};
 
 use super::Operation;
 
-pub struct Read {
+/// Reads into the spare capacity of `B`, see [`IoBufMut`].
+pub struct Read<B = Vec<u8>> {
   fd: RawFd,
-  buf: Option<Vec<u8>>,
+  buf: Option<B>,
+  /// Injected cap on the transfer size, see [`Operation::shorten`].
+  #[cfg(feature = "fault_injection")]
+  max_len: usize,
   offset: i64,
 }
 
-unsafe impl DetachSafe for Read {}
+unsafe impl<B: IoBufMut> DetachSafe for Read<B> {}
 
-impl Read {
+impl<B: IoBufMut> Read<B> {
   /// Will return errn 22 "EINVAL" if offset < 0
-  pub(crate) fn new(fd: RawFd, mem: Vec<u8>, offset: i64) -> Self {
-    Self { fd, buf: Some(mem), offset }
+  pub(crate) fn new(fd: impl BorrowFd, mem: B, offset: i64) -> Self {
+    Self {
+      fd: fd.raw_fd(),
+      buf: Some(mem),
+      #[cfg(feature = "fault_injection")]
+      max_len: usize::MAX,
+      offset,
+    }
+  }
+
+  /// Bytes handed to the kernel: the spare capacity after `bytes_init`.
+  fn len(&self) -> usize {
+    let buf = self.buf.as_ref().unwrap();
+    let len = buf.bytes_total() - buf.bytes_init();
+    #[cfg(feature = "fault_injection")]
+    let len = len.min(self.max_len);
+    len
   }
 }
 
-impl Operation for Read {
+impl<B: IoBufMut> Operation for Read<B> {
+  impl_trace!(fd, bytes_total);
+
   #[cfg(linux)]
   const OPCODE: u8 = 22;
 
   #[cfg(linux)]
   fn create_entry(&mut self) -> io_uring::squeue::Entry {
+    let len = self.len();
     if let Some(ref mut buf) = self.buf {
       io_uring::opcode::Read::new(
         Fd(self.fd),
-        buf.as_mut_ptr(),
-        buf.len() as u32,
+        // SAFETY: `bytes_init` is within the buffer.
+        unsafe { buf.stable_mut_ptr().add(buf.bytes_init()) },
+        len.min(u32::MAX as usize) as u32,
       )
       .offset(self.offset as u64)
       .build()
@@ -40,18 +63,46 @@ impl Operation for Read {
       unreachable!()
     }
   }
-  type Result = BufResult<i32, Vec<u8>>;
+
+  #[cfg(feature = "fault_injection")]
+  fn shorten(&mut self, len: usize) {
+    self.max_len = len;
+  }
+
+  #[cfg(feature = "sim")]
+  fn simulate(
+    &mut self,
+    sim: &mut crate::backends::SimState,
+  ) -> std::task::Poll<std::io::Result<i32>> {
+    let len = self.len();
+    let buf = self.buf.as_mut().unwrap();
+    sim
+      .read(self.fd, self.offset, len)
+      .map_ok(|data| crate::backends::fill(buf, &data))
+  }
+  type Result = BufResult<i32, B>;
 
   impl_no_readyness!();
 
   #[cfg(not(linux))]
   fn run_blocking(&self) -> io::Result<i32> {
     let buf = self.buf.as_ref().unwrap();
-    syscall!(pread(self.fd, buf.as_ptr() as *mut _, buf.len(), self.offset))
-      .map(|t| t as i32)
+    syscall!(pread(
+      self.fd,
+      // SAFETY: `bytes_init` is within the buffer.
+      unsafe { buf.stable_ptr().add(buf.bytes_init()) } as *mut _,
+      self.len(),
+      self.offset
+    ))
+    .map(|t| t as i32)
   }
   fn result(&mut self, _ret: io::Result<i32>) -> Self::Result {
-    let buf = self.buf.take().expect("ran Recv::result more than once.");
+    let mut buf = self.buf.take().expect("ran Recv::result more than once.");
+
+    if let Ok(n) = _ret {
+      // SAFETY: The kernel initialized `n` bytes after the initialized ones.
+      unsafe { buf.set_init(buf.bytes_init() + n as usize) };
+    }
 
     (_ret, buf)
   }
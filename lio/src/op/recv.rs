@@ -5,37 +5,64 @@ use io_uring::types::Fd;
 
 #[cfg(not(linux))]
 use crate::op::EventType;
-use crate::{BufResult, op::DetachSafe};
+use crate::{BorrowFd, BufResult, IoBufMut, MsgFlags, op::DetachSafe};
 
 use super::Operation;
 
-pub struct Recv {
+/// Receives into the spare capacity of `B`, see [`IoBufMut`].
+pub struct Recv<B = Vec<u8>> {
   fd: RawFd,
-  buf: Option<Vec<u8>>,
+  buf: Option<B>,
+  /// Injected cap on the transfer size, see [`Operation::shorten`].
+  #[cfg(feature = "fault_injection")]
+  max_len: usize,
   flags: i32,
 }
 
-unsafe impl DetachSafe for Recv {}
+unsafe impl<B: IoBufMut> DetachSafe for Recv<B> {}
 
-impl Recv {
-  pub(crate) fn new(fd: RawFd, buf: Vec<u8>, flags: Option<i32>) -> Self {
-    Self { fd, buf: Some(buf), flags: flags.unwrap_or(0) }
+impl<B: IoBufMut> Recv<B> {
+  pub(crate) fn new(
+    fd: impl BorrowFd,
+    buf: B,
+    flags: Option<MsgFlags>,
+  ) -> Self {
+    Self {
+      fd: fd.raw_fd(),
+      buf: Some(buf),
+      #[cfg(feature = "fault_injection")]
+      max_len: usize::MAX,
+      flags: flags.unwrap_or_default().bits(),
+    }
+  }
+
+  /// Bytes handed to the kernel: the spare capacity after `bytes_init`.
+  fn len(&self) -> usize {
+    let buf = self.buf.as_ref().unwrap();
+    let len = buf.bytes_total() - buf.bytes_init();
+    #[cfg(feature = "fault_injection")]
+    let len = len.min(self.max_len);
+    len
   }
 }
 
-impl Operation for Recv {
-  type Result = BufResult<i32, Vec<u8>>;
+impl<B: IoBufMut> Operation for Recv<B> {
+  impl_trace!(fd, bytes_total);
+
+  type Result = BufResult<i32, B>;
 
   #[cfg(linux)]
   const OPCODE: u8 = 27;
 
   #[cfg(linux)]
   fn create_entry(&mut self) -> io_uring::squeue::Entry {
+    let len = self.len();
     if let Some(ref mut buf) = self.buf {
       io_uring::opcode::Recv::new(
         Fd(self.fd),
-        buf.as_mut_ptr(),
-        buf.len() as u32,
+        // SAFETY: `bytes_init` is within the buffer.
+        unsafe { buf.stable_mut_ptr().add(buf.bytes_init()) },
+        len.min(u32::MAX as usize) as u32,
       )
       .flags(self.flags)
       .build()
@@ -44,6 +71,21 @@ impl Operation for Recv {
     }
   }
 
+  #[cfg(feature = "fault_injection")]
+  fn shorten(&mut self, len: usize) {
+    self.max_len = len;
+  }
+
+  #[cfg(feature = "sim")]
+  fn simulate(
+    &mut self,
+    sim: &mut crate::backends::SimState,
+  ) -> std::task::Poll<std::io::Result<i32>> {
+    let len = self.len();
+    let buf = self.buf.as_mut().unwrap();
+    sim.recv(self.fd, len).map_ok(|data| crate::backends::fill(buf, &data))
+  }
+
   #[cfg(not(linux))]
   const EVENT_TYPE: Option<EventType> = Some(EventType::Read);
 
@@ -55,12 +97,19 @@ impl Operation for Recv {
   #[cfg(not(linux))]
   fn run_blocking(&self) -> io::Result<i32> {
     let buf = self.buf.as_ref().unwrap();
-    syscall!(recv(self.fd, buf.as_ptr() as *mut _, buf.len(), self.flags))
+    // SAFETY: `bytes_init` is within the buffer.
+    let ptr = unsafe { buf.stable_ptr().add(buf.bytes_init()) };
+    syscall!(recv(self.fd, ptr as *mut _, self.len(), self.flags))
       .map(|t| t as i32)
   }
 
   fn result(&mut self, _ret: io::Result<i32>) -> Self::Result {
-    let buf = self.buf.take().expect("ran Recv::result more than once.");
+    let mut buf = self.buf.take().expect("ran Recv::result more than once.");
+
+    if let Ok(n) = _ret {
+      // SAFETY: The kernel initialized `n` bytes after the initialized ones.
+      unsafe { buf.set_init(buf.bytes_init() + n as usize) };
+    }
 
     (_ret, buf)
   }
@@ -5,27 +5,51 @@ use io_uring::types::Fd;
 
 #[cfg(not(linux))]
 use crate::op::EventType;
-use crate::{BufResult, op::DetachSafe};
+use crate::{BorrowFd, BufResult, IoBuf, MsgFlags, op::DetachSafe};
 
 use super::Operation;
 
-pub struct Send {
+/// Sends the initialized bytes of `B`, see [`IoBuf`].
+pub struct Send<B = Vec<u8>> {
   fd: RawFd,
-  buf: Option<Vec<u8>>,
+  buf: Option<B>,
+  /// Injected cap on the transfer size, see [`Operation::shorten`].
+  #[cfg(feature = "fault_injection")]
+  max_len: usize,
   flags: i32,
 }
 
-unsafe impl DetachSafe for Send {}
+unsafe impl<B: IoBuf> DetachSafe for Send<B> {}
 
-impl Send {
-  pub(crate) fn new(fd: RawFd, buf: Vec<u8>, flags: Option<i32>) -> Self {
-    assert!((buf.len()) <= u32::MAX as usize);
-    Self { fd, buf: Some(buf), flags: flags.unwrap_or(0) }
+impl<B: IoBuf> Send<B> {
+  pub(crate) fn new(
+    fd: impl BorrowFd,
+    buf: B,
+    flags: Option<MsgFlags>,
+  ) -> Self {
+    assert!((buf.bytes_init()) <= u32::MAX as usize);
+    Self {
+      fd: fd.raw_fd(),
+      buf: Some(buf),
+      #[cfg(feature = "fault_injection")]
+      max_len: usize::MAX,
+      flags: flags.unwrap_or_default().bits(),
+    }
+  }
+
+  /// Bytes handed to the kernel.
+  fn len(&self) -> usize {
+    let len = self.buf.as_ref().unwrap().bytes_init();
+    #[cfg(feature = "fault_injection")]
+    let len = len.min(self.max_len);
+    len
   }
 }
 
-impl Operation for Send {
-  type Result = BufResult<i32, Vec<u8>>;
+impl<B: IoBuf> Operation for Send<B> {
+  impl_trace!(fd, bytes_init);
+
+  type Result = BufResult<i32, B>;
 
   #[cfg(linux)]
   const OPCODE: u8 = 26;
@@ -33,9 +57,27 @@ impl Operation for Send {
   #[cfg(linux)]
   fn create_entry(&mut self) -> io_uring::squeue::Entry {
     let buf = self.buf.as_ref().unwrap();
-    io_uring::opcode::Send::new(Fd(self.fd), buf.as_ptr(), buf.len() as u32)
-      .flags(self.flags)
-      .build()
+    io_uring::opcode::Send::new(
+      Fd(self.fd),
+      buf.stable_ptr(),
+      self.len() as u32,
+    )
+    .flags(self.flags)
+    .build()
+  }
+
+  #[cfg(feature = "fault_injection")]
+  fn shorten(&mut self, len: usize) {
+    self.max_len = len;
+  }
+
+  #[cfg(feature = "sim")]
+  fn simulate(
+    &mut self,
+    sim: &mut crate::backends::SimState,
+  ) -> std::task::Poll<std::io::Result<i32>> {
+    let buf = self.buf.as_ref().unwrap();
+    sim.send(self.fd, &crate::backends::bytes(buf)[..self.len()])
   }
 
   #[cfg(not(linux))]
@@ -49,7 +91,7 @@ impl Operation for Send {
   #[cfg(not(linux))]
   fn run_blocking(&self) -> io::Result<i32> {
     let buf = self.buf.as_ref().unwrap();
-    syscall!(send(self.fd, buf.as_ptr() as *mut _, buf.len(), self.flags))
+    syscall!(send(self.fd, buf.stable_ptr() as *mut _, self.len(), self.flags))
       .map(|t| t as i32)
   }
   fn result(&mut self, _ret: std::io::Result<i32>) -> Self::Result {
@@ -1,8 +1,9 @@
-use std::{io, os::fd::RawFd};
+use std::{io, net, os::fd::RawFd};
 
 #[cfg(linux)]
 use io_uring::types::Fd;
 
+use crate::BorrowFd;
 use crate::op::DetachSafe;
 
 use super::Operation;
@@ -13,14 +14,21 @@ pub struct Shutdown {
 }
 
 impl Shutdown {
-  pub(crate) fn new(fd: RawFd, how: i32) -> Self {
-    Self { fd, how }
+  pub(crate) fn new(fd: impl BorrowFd, how: net::Shutdown) -> Self {
+    let how = match how {
+      net::Shutdown::Read => libc::SHUT_RD,
+      net::Shutdown::Write => libc::SHUT_WR,
+      net::Shutdown::Both => libc::SHUT_RDWR,
+    };
+    Self { fd: fd.raw_fd(), how }
   }
 }
 
 unsafe impl DetachSafe for Shutdown {}
 
 impl Operation for Shutdown {
+  impl_trace!(fd);
+
   impl_result!(());
 
   #[cfg(linux)]
@@ -31,6 +39,14 @@ impl Operation for Shutdown {
     io_uring::opcode::Shutdown::new(Fd(self.fd), self.how).build()
   }
 
+  #[cfg(feature = "sim")]
+  fn simulate(
+    &mut self,
+    sim: &mut crate::backends::SimState,
+  ) -> std::task::Poll<std::io::Result<i32>> {
+    std::task::Poll::Ready(sim.shutdown(self.fd, self.how))
+  }
+
   // NOTE: Not sure here, kqueue can prob be used with flags.
   impl_no_readyness!();
 
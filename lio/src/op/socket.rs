@@ -1,18 +1,19 @@
 use std::io;
-use std::os::fd::RawFd;
+use std::os::fd::{FromRawFd, OwnedFd, RawFd};
 
 #[cfg(not(linux))]
 use crate::op::EventType;
 
-use super::Operation;
+use super::{Close, DetachSafe, Operation};
 
-// Not detach safe.
 pub struct Socket {
   domain: socket2::Domain,
   ty: socket2::Type,
   proto: Option<socket2::Protocol>,
 }
 
+unsafe impl DetachSafe for Socket {}
+
 impl Socket {
   pub(crate) fn new(
     domain: socket2::Domain,
@@ -181,6 +182,13 @@ impl Socket {
 impl Operation for Socket {
   impl_result!(fd);
 
+  fn release(result: Self::Result) {
+    if let Ok(fd) = result {
+      // SAFETY: Nobody else will see the new descriptor.
+      Close::detached(unsafe { OwnedFd::from_raw_fd(fd) });
+    }
+  }
+
   #[cfg(linux)]
   const OPCODE: u8 = 45;
 
@@ -194,6 +202,14 @@ impl Operation for Socket {
     .build()
   }
 
+  #[cfg(feature = "sim")]
+  fn simulate(
+    &mut self,
+    sim: &mut crate::backends::SimState,
+  ) -> std::task::Poll<std::io::Result<i32>> {
+    std::task::Poll::Ready(sim.socket(self.domain.into(), self.ty.into()))
+  }
+
   #[cfg(not(linux))]
   const EVENT_TYPE: Option<EventType> = None;
 
@@ -0,0 +1,84 @@
+use std::{cell::UnsafeCell, io, mem, os::fd::RawFd};
+
+use crate::{BorrowFd, SocketAddress, op::DetachSafe};
+
+use super::Operation;
+
+macro_rules! sockname_op {
+  ($(#[$doc:meta])* $name:ident, $syscall:ident, $sim:ident) => {
+    $(#[$doc])*
+    pub struct $name {
+      fd: RawFd,
+      addr: UnsafeCell<libc::sockaddr_storage>,
+      len: UnsafeCell<libc::socklen_t>,
+    }
+
+    unsafe impl DetachSafe for $name {}
+
+    impl $name {
+      pub(crate) fn new(fd: impl BorrowFd) -> Self {
+        Self {
+          fd: fd.raw_fd(),
+          addr: UnsafeCell::new(unsafe { mem::zeroed() }),
+          len: UnsafeCell::new(
+            mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t
+          ),
+        }
+      }
+    }
+
+    impl Operation for $name {
+      impl_trace!(fd);
+
+      type Result = io::Result<SocketAddress>;
+
+      fn result(&mut self, res: io::Result<i32>) -> Self::Result {
+        res?;
+        SocketAddress::from_libc(self.addr.get_mut(), *self.len.get_mut())
+      }
+
+      // io_uring has no opcode for this, so it always runs `run_blocking`,
+      // which doesn't block.
+      #[cfg(linux)]
+      const OPCODE: u8 = u8::MAX;
+
+      #[cfg(linux)]
+      fn entry_supported(_probe: &io_uring::Probe) -> bool {
+        false
+      }
+
+      #[cfg(linux)]
+      fn create_entry(&mut self) -> io_uring::squeue::Entry {
+        unreachable!("not supported by io_uring")
+      }
+
+      #[cfg(feature = "sim")]
+      fn simulate(
+        &mut self,
+        sim: &mut crate::backends::SimState,
+      ) -> std::task::Poll<io::Result<i32>> {
+        std::task::Poll::Ready(sim.$sim(self.fd).map(|addr| {
+          (*self.addr.get_mut(), *self.len.get_mut()) =
+            SocketAddress::from(addr).to_libc();
+          0
+        }))
+      }
+
+      impl_no_readyness!();
+
+      fn run_blocking(&self) -> io::Result<i32> {
+        syscall!($syscall(self.fd, self.addr.get().cast(), self.len.get()))
+      }
+    }
+  };
+}
+
+sockname_op!(
+  /// Reads the address a socket is bound to, see [`local_addr`](crate::local_addr).
+  LocalAddr, getsockname, local_addr
+);
+
+sockname_op!(
+  /// Reads the address a socket is connected to, see [`peer_addr`](crate::peer_addr).
+  PeerAddr, getpeername, peer_addr
+);
@@ -0,0 +1,194 @@
+use std::{cell::UnsafeCell, io, mem, os::fd::RawFd};
+
+#[cfg(linux)]
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[cfg(linux)]
+use io_uring::{opcode, squeue, types::Fd};
+
+use crate::BorrowFd;
+use crate::op::DetachSafe;
+use crate::sockopt::SockOpt;
+
+use super::Operation;
+
+/// Cleared once the kernel turns out not to support the socket command
+/// `SOCKET_URING_OP_SETSOCKOPT` (Linux 6.7), which older kernels reject with
+/// `EOPNOTSUPP`. Options are then set with the syscall.
+#[cfg(linux)]
+static SOCKET_COMMANDS_SUPPORTED: AtomicBool = AtomicBool::new(true);
+
+/// How a socket option operation reached the kernel.
+#[cfg(linux)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Via {
+  /// `run_blocking`, the syscall already ran.
+  Syscall,
+  /// A socket command, which may not be supported.
+  Command,
+}
+
+#[cfg(linux)]
+fn socket_commands_supported(probe: &io_uring::Probe) -> bool {
+  SOCKET_COMMANDS_SUPPORTED.load(Ordering::Relaxed)
+    && probe.is_supported(opcode::SetSockOpt::CODE)
+}
+
+/// Runs `syscall` if the socket command failed with `EOPNOTSUPP`, and stops
+/// using socket commands if the syscall works.
+#[cfg(linux)]
+fn retry_unsupported(
+  res: io::Result<i32>,
+  syscall: impl FnOnce() -> io::Result<i32>,
+) -> io::Result<i32> {
+  match res {
+    Err(err) if err.raw_os_error() == Some(libc::EOPNOTSUPP) => {
+      let res = syscall();
+      if res.is_ok() {
+        SOCKET_COMMANDS_SUPPORTED.store(false, Ordering::Relaxed);
+      }
+      res
+    }
+    res => res,
+  }
+}
+
+/// Sets a socket option, see [`setsockopt`](crate::setsockopt).
+pub struct SetSockOpt<O: SockOpt> {
+  fd: RawFd,
+  opt: O,
+  value: O::Repr,
+  #[cfg(linux)]
+  via: Via,
+}
+
+unsafe impl<O: SockOpt> DetachSafe for SetSockOpt<O> {}
+
+impl<O: SockOpt> SetSockOpt<O> {
+  pub(crate) fn new(fd: impl BorrowFd, opt: O, value: O::Value) -> Self {
+    Self {
+      fd: fd.raw_fd(),
+      opt,
+      value: O::to_repr(value),
+      #[cfg(linux)]
+      via: Via::Syscall,
+    }
+  }
+
+  fn setsockopt(&self) -> io::Result<i32> {
+    syscall!(setsockopt(
+      self.fd,
+      self.opt.level(),
+      self.opt.name(),
+      (&self.value as *const O::Repr).cast(),
+      mem::size_of::<O::Repr>() as libc::socklen_t
+    ))
+  }
+}
+
+impl<O: SockOpt> Operation for SetSockOpt<O> {
+  impl_trace!(fd);
+
+  type Result = io::Result<()>;
+
+  fn result(&mut self, res: io::Result<i32>) -> Self::Result {
+    #[cfg(linux)]
+    let res = match self.via {
+      Via::Command => retry_unsupported(res, || self.setsockopt()),
+      Via::Syscall => res,
+    };
+    res.map(drop)
+  }
+
+  #[cfg(linux)]
+  const OPCODE: u8 = opcode::UringCmd16::CODE;
+
+  #[cfg(linux)]
+  fn entry_supported(probe: &io_uring::Probe) -> bool {
+    socket_commands_supported(probe)
+  }
+
+  #[cfg(linux)]
+  fn create_entry(&mut self) -> squeue::Entry {
+    self.via = Via::Command;
+    opcode::SetSockOpt::new(
+      Fd(self.fd),
+      self.opt.level() as u32,
+      self.opt.name() as u32,
+      (&self.value as *const O::Repr).cast(),
+      mem::size_of::<O::Repr>() as u32,
+    )
+    .build()
+  }
+
+  impl_no_readyness!();
+
+  fn run_blocking(&self) -> io::Result<i32> {
+    self.setsockopt()
+  }
+}
+
+/// Reads a socket option, see [`getsockopt`](crate::getsockopt).
+pub struct GetSockOpt<O: SockOpt> {
+  fd: RawFd,
+  opt: O,
+  value: UnsafeCell<O::Repr>,
+  len: UnsafeCell<libc::socklen_t>,
+}
+
+unsafe impl<O: SockOpt> DetachSafe for GetSockOpt<O> {}
+
+impl<O: SockOpt> GetSockOpt<O> {
+  pub(crate) fn new(fd: impl BorrowFd, opt: O) -> Self {
+    Self {
+      fd: fd.raw_fd(),
+      opt,
+      // SAFETY: Any bit pattern is a valid `Repr`, see `SockOpt`.
+      value: UnsafeCell::new(unsafe { mem::zeroed() }),
+      len: UnsafeCell::new(mem::size_of::<O::Repr>() as libc::socklen_t),
+    }
+  }
+
+  fn getsockopt(&self) -> io::Result<i32> {
+    unsafe { *self.len.get() = mem::size_of::<O::Repr>() as libc::socklen_t };
+    syscall!(getsockopt(
+      self.fd,
+      self.opt.level(),
+      self.opt.name(),
+      self.value.get().cast(),
+      self.len.get()
+    ))
+  }
+}
+
+impl<O: SockOpt> Operation for GetSockOpt<O> {
+  impl_trace!(fd);
+
+  type Result = io::Result<O::Value>;
+
+  fn result(&mut self, res: io::Result<i32>) -> Self::Result {
+    res?;
+    Ok(O::from_repr(*self.value.get_mut()))
+  }
+
+  #[cfg(linux)]
+  const OPCODE: u8 = opcode::UringCmd16::CODE;
+
+  // io-uring has no builder for the getsockopt socket command, so options
+  // are read with the syscall, which doesn't block.
+  #[cfg(linux)]
+  fn entry_supported(_probe: &io_uring::Probe) -> bool {
+    false
+  }
+
+  #[cfg(linux)]
+  fn create_entry(&mut self) -> squeue::Entry {
+    unreachable!("getsockopt runs through run_blocking")
+  }
+
+  impl_no_readyness!();
+
+  fn run_blocking(&self) -> io::Result<i32> {
+    self.getsockopt()
+  }
+}
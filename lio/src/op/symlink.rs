@@ -10,6 +10,8 @@ use io_uring::types::Fd;
 #[cfg(not(linux))]
 use crate::op::EventType;
 
+use crate::BorrowFd;
+
 use super::Operation;
 
 pub struct SymlinkAt {
@@ -23,14 +25,14 @@ pub struct SymlinkAt {
 // TODO: test
 impl SymlinkAt {
   pub(crate) fn new(
-    new_dir_fd: RawFd,
+    new_dir_fd: impl BorrowFd,
     target: impl AsRef<Path>,
     linkpath: impl AsRef<Path>,
   ) -> Result<Self, NulError> {
     let target = target.as_ref().as_os_str().to_os_string();
     let linkpath = linkpath.as_ref().as_os_str().to_os_string();
     Ok(Self {
-      fd: new_dir_fd,
+      fd: new_dir_fd.raw_fd(),
       target: CString::new(target.into_vec())?,
       linkpath: CString::new(linkpath.into_vec())?,
     })
@@ -0,0 +1,55 @@
+use std::os::fd::RawFd;
+
+use io_uring::types::Fd;
+
+use crate::BorrowFd;
+use crate::op::DetachSafe;
+
+use super::Operation;
+
+pub struct SyncFileRange {
+  fd: RawFd,
+  offset: u64,
+  nbytes: u32,
+  flags: u32,
+}
+
+unsafe impl DetachSafe for SyncFileRange {}
+
+impl SyncFileRange {
+  pub(crate) fn new(
+    fd: impl BorrowFd,
+    offset: u64,
+    nbytes: u32,
+    flags: u32,
+  ) -> Self {
+    Self { fd: fd.raw_fd(), offset, nbytes, flags }
+  }
+}
+
+impl Operation for SyncFileRange {
+  impl_trace!(fd);
+
+  impl_result!(());
+
+  #[cfg(linux)]
+  const OPCODE: u8 = 8;
+
+  #[cfg(linux)]
+  fn create_entry(&mut self) -> io_uring::squeue::Entry {
+    io_uring::opcode::SyncFileRange::new(Fd(self.fd), self.nbytes)
+      .offset(self.offset)
+      .flags(self.flags)
+      .build()
+  }
+
+  #[cfg(feature = "sim")]
+  fn simulate(
+    &mut self,
+    sim: &mut crate::backends::SimState,
+  ) -> std::task::Poll<std::io::Result<i32>> {
+    std::task::Poll::Ready(sim.check_fd(self.fd))
+  }
+
+  impl_no_readyness!();
+}
@@ -4,6 +4,8 @@ use std::{io, os::fd::RawFd};
 
 use io_uring::types::Fd;
 
+use crate::BorrowFd;
+
 use super::Operation;
 
 // TODO: not sure detach safe.
@@ -14,8 +16,12 @@ pub struct Tee {
 }
 
 impl Tee {
-  pub(crate) fn new(fd_in: RawFd, fd_out: RawFd, size: u32) -> Self {
-    Self { fd_in, fd_out, size }
+  pub(crate) fn new(
+    fd_in: impl BorrowFd,
+    fd_out: impl BorrowFd,
+    size: u32,
+  ) -> Self {
+    Self { fd_in: fd_in.raw_fd(), fd_out: fd_out.raw_fd(), size }
   }
 }
 
@@ -13,6 +13,11 @@ use super::Operation;
 
 pub struct Timeout {
   timespec: Timespec,
+  #[cfg(feature = "sim")]
+  duration: Duration,
+  /// Virtual time at which the simulated timeout fires, set on first run.
+  #[cfg(feature = "sim")]
+  deadline: Option<Duration>,
 }
 
 // wtf are you doing waiting and then not waiting??
@@ -24,6 +29,10 @@ impl Timeout {
       timespec: Timespec::new()
         .sec(duration.as_secs())
         .nsec(duration.subsec_nanos()),
+      #[cfg(feature = "sim")]
+      duration,
+      #[cfg(feature = "sim")]
+      deadline: None,
     }
   }
 }
@@ -57,6 +66,15 @@ impl Operation for Timeout {
     opcode::Timeout::new(&self.timespec as *const _).build()
   }
 
+  #[cfg(feature = "sim")]
+  fn simulate(
+    &mut self,
+    sim: &mut crate::backends::SimState,
+  ) -> std::task::Poll<std::io::Result<i32>> {
+    let deadline = *self.deadline.get_or_insert(sim.now() + self.duration);
+    sim.sleep_until(deadline)
+  }
+
   #[cfg(not(linux))]
   const EVENT_TYPE: Option<EventType> = None;
 
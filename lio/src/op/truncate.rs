@@ -3,6 +3,7 @@ use std::os::fd::RawFd;
 #[cfg(linux)]
 use io_uring::{opcode, squeue, types::Fd};
 
+use crate::BorrowFd;
 use crate::op::DetachSafe;
 #[cfg(not(linux))]
 use crate::op::EventType;
@@ -17,12 +18,14 @@ pub struct Truncate {
 unsafe impl DetachSafe for Truncate {}
 
 impl Truncate {
-  pub(crate) fn new(fd: RawFd, size: u64) -> Self {
-    Self { fd, size }
+  pub(crate) fn new(fd: impl BorrowFd, size: u64) -> Self {
+    Self { fd: fd.raw_fd(), size }
   }
 }
 
 impl Operation for Truncate {
+  impl_trace!(fd);
+
   impl_result!(());
 
   #[cfg(linux)]
@@ -33,6 +36,14 @@ impl Operation for Truncate {
     opcode::Ftruncate::new(Fd(self.fd), self.size).build()
   }
 
+  #[cfg(feature = "sim")]
+  fn simulate(
+    &mut self,
+    sim: &mut crate::backends::SimState,
+  ) -> std::task::Poll<std::io::Result<i32>> {
+    std::task::Poll::Ready(sim.truncate(self.fd, self.size))
+  }
+
   #[cfg(not(linux))]
   const EVENT_TYPE: Option<EventType> = None;
 
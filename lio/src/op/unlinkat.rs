@@ -0,0 +1,57 @@
+use std::{
+  ffi::{CString, NulError},
+  os::{fd::RawFd, unix::ffi::OsStringExt},
+  path::Path,
+};
+
+#[cfg(linux)]
+use io_uring::types::Fd;
+
+use crate::BorrowFd;
+use crate::op::DetachSafe;
+
+use super::Operation;
+
+pub struct UnlinkAt {
+  dir_fd: RawFd,
+  path: CString,
+}
+
+unsafe impl DetachSafe for UnlinkAt {}
+
+impl UnlinkAt {
+  pub(crate) fn new(
+    dir_fd: impl BorrowFd,
+    path: impl AsRef<Path>,
+  ) -> Result<Self, NulError> {
+    let path = path.as_ref().as_os_str().to_os_string();
+    Ok(Self { dir_fd: dir_fd.raw_fd(), path: CString::new(path.into_vec())? })
+  }
+}
+
+impl Operation for UnlinkAt {
+  impl_result!(());
+
+  #[cfg(linux)]
+  const OPCODE: u8 = 36;
+
+  impl_no_readyness!();
+
+  #[cfg(linux)]
+  fn create_entry(&mut self) -> io_uring::squeue::Entry {
+    io_uring::opcode::UnlinkAt::new(Fd(self.dir_fd), self.path.as_ptr()).build()
+  }
+
+  #[cfg(feature = "sim")]
+  fn simulate(
+    &mut self,
+    sim: &mut crate::backends::SimState,
+  ) -> std::task::Poll<std::io::Result<i32>> {
+    std::task::Poll::Ready(sim.unlink(self.dir_fd, &self.path))
+  }
+
+  #[cfg(not(linux))]
+  fn run_blocking(&self) -> std::io::Result<i32> {
+    syscall!(unlinkat(self.dir_fd, self.path.as_ptr(), 0))
+  }
+}
@@ -0,0 +1,175 @@
+use std::{
+  cell::UnsafeCell, io, os::unix::process::ExitStatusExt, process::ExitStatus,
+};
+
+#[cfg(linux)]
+use std::{mem, os::fd::RawFd};
+
+#[cfg(linux)]
+use io_uring::{opcode, squeue, types::Fd};
+
+use crate::op::DetachSafe;
+
+use super::Operation;
+
+/// Waits for a child process to exit and reaps it.
+///
+/// On Linux this uses `IORING_OP_WAITID` (Linux 6.7). On older kernels a pidfd
+/// is polled for readability through io_uring instead, and the child is reaped
+/// once it is readable.
+pub struct WaitId {
+  pid: libc::pid_t,
+  #[cfg(linux)]
+  info: UnsafeCell<libc::siginfo_t>,
+  #[cfg(linux)]
+  fallback: Option<PidfdFallback>,
+  #[cfg(not(linux))]
+  status: UnsafeCell<libc::c_int>,
+}
+
+/// State of the pidfd based fallback, used when `IORING_OP_WAITID` isn't supported.
+/// Set up when submitting, see [`Operation::fallback_entry`].
+#[cfg(linux)]
+enum PidfdFallback {
+  /// Child is still running, pidfd becomes readable once it exits.
+  Polling(RawFd),
+  /// Child had already exited, and was reaped before submitting.
+  Reaped,
+  /// Opening or checking the pidfd failed, for example because `pid` isn't a child.
+  Failed(io::Error),
+}
+
+// Only the kernel writes through the cells, while the operation is in flight.
+// Needed since `siginfo_t` isn't `Send` in newer libc versions.
+unsafe impl Send for WaitId {}
+// The pidfd, if any, is closed on drop.
+unsafe impl DetachSafe for WaitId {}
+
+impl WaitId {
+  pub(crate) fn new(pid: u32) -> Self {
+    Self {
+      pid: pid as libc::pid_t,
+      #[cfg(linux)]
+      info: UnsafeCell::new(unsafe { mem::zeroed() }),
+      #[cfg(linux)]
+      fallback: None,
+      #[cfg(not(linux))]
+      status: UnsafeCell::new(0),
+    }
+  }
+
+  #[cfg(linux)]
+  fn exit_status(&self) -> ExitStatus {
+    let info = unsafe { &*self.info.get() };
+    let status = unsafe { info.si_status() };
+    let raw = match info.si_code {
+      libc::CLD_EXITED => (status & 0xff) << 8,
+      libc::CLD_DUMPED => status | 0x80,
+      // CLD_KILLED, stopped or continued children aren't waited for.
+      _ => status,
+    };
+    ExitStatus::from_raw(raw)
+  }
+
+  /// Reaps the child behind `pidfd` if it has exited, without blocking.
+  #[cfg(linux)]
+  fn try_reap(&self, pidfd: RawFd) -> io::Result<bool> {
+    syscall!(waitid(
+      libc::P_PIDFD,
+      pidfd as libc::id_t,
+      self.info.get(),
+      libc::WEXITED | libc::WNOHANG
+    ))?;
+    // si_pid stays zero if no child has changed state yet.
+    Ok(unsafe { (*self.info.get()).si_pid() } != 0)
+  }
+
+  #[cfg(linux)]
+  fn start_fallback(&self) -> PidfdFallback {
+    let pidfd = match syscall!(syscall(libc::SYS_pidfd_open, self.pid, 0)) {
+      Ok(fd) => fd as RawFd,
+      Err(err) => return PidfdFallback::Failed(err),
+    };
+
+    let state = match self.try_reap(pidfd) {
+      Ok(false) => return PidfdFallback::Polling(pidfd),
+      Ok(true) => PidfdFallback::Reaped,
+      Err(err) => PidfdFallback::Failed(err),
+    };
+    unsafe { libc::close(pidfd) };
+    state
+  }
+}
+
+#[cfg(linux)]
+impl Drop for WaitId {
+  fn drop(&mut self) {
+    if let Some(PidfdFallback::Polling(pidfd)) = self.fallback {
+      unsafe { libc::close(pidfd) };
+    }
+  }
+}
+
+impl Operation for WaitId {
+  type Result = io::Result<ExitStatus>;
+
+  #[cfg(linux)]
+  fn result(&mut self, res: io::Result<i32>) -> Self::Result {
+    match self.fallback.take() {
+      None => {
+        res?;
+      }
+      Some(PidfdFallback::Reaped) => {}
+      Some(PidfdFallback::Failed(err)) => return Err(err),
+      Some(PidfdFallback::Polling(pidfd)) => {
+        let reaped = res.and_then(|_| self.try_reap(pidfd));
+        unsafe { libc::close(pidfd) };
+        if !reaped? {
+          return Err(io::Error::other("pidfd readable before child exited"));
+        }
+      }
+    };
+    Ok(self.exit_status())
+  }
+
+  #[cfg(not(linux))]
+  fn result(&mut self, res: io::Result<i32>) -> Self::Result {
+    res?;
+    Ok(ExitStatus::from_raw(unsafe { *self.status.get() }))
+  }
+
+  #[cfg(linux)]
+  const OPCODE: u8 = 50;
+
+  #[cfg(linux)]
+  fn create_entry(&mut self) -> squeue::Entry {
+    opcode::WaitId::new(libc::P_PID, self.pid as libc::id_t, libc::WEXITED)
+      .infop(self.info.get().cast_const())
+      .build()
+  }
+
+  // A blocking `waitid` would block the thread polling it, so a pidfd is
+  // polled instead, and the child reaped in `result`.
+  #[cfg(linux)]
+  fn fallback_entry(&mut self) -> Option<squeue::Entry> {
+    let fallback = self.start_fallback();
+    let entry = match fallback {
+      PidfdFallback::Polling(pidfd) => {
+        opcode::PollAdd::new(Fd(pidfd), libc::POLLIN as u32).build()
+      }
+      // Outcome is already known and reported from Self::result.
+      PidfdFallback::Reaped | PidfdFallback::Failed(_) => {
+        opcode::Nop::new().build()
+      }
+    };
+    self.fallback = Some(fallback);
+    Some(entry)
+  }
+
+  impl_no_readyness!();
+
+  #[cfg(not(linux))]
+  fn run_blocking(&self) -> io::Result<i32> {
+    syscall!(waitpid(self.pid, self.status.get(), 0))
+  }
+}
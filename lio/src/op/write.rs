@@ -1,5 +1,5 @@
 use super::Operation;
-use crate::{BufResult, op::DetachSafe};
+use crate::{BorrowFd, BufResult, IoBuf, op::DetachSafe};
 
 #[cfg(not(linux))]
 use crate::op::EventType;
@@ -9,23 +9,43 @@ use io_uring::types::Fd;
 
 use std::os::fd::RawFd;
 
-pub struct Write {
+/// Writes the initialized bytes of `B`, see [`IoBuf`].
+pub struct Write<B = Vec<u8>> {
   fd: RawFd,
-  buf: Option<Vec<u8>>,
+  buf: Option<B>,
+  /// Injected cap on the transfer size, see [`Operation::shorten`].
+  #[cfg(feature = "fault_injection")]
+  max_len: usize,
   offset: i64,
 }
 
-unsafe impl DetachSafe for Write {}
+unsafe impl<B: IoBuf> DetachSafe for Write<B> {}
 
-impl Write {
-  pub(crate) fn new(fd: RawFd, buf: Vec<u8>, offset: i64) -> Write {
-    assert!((buf.len()) <= u32::MAX as usize);
-    Self { fd, buf: Some(buf), offset }
+impl<B: IoBuf> Write<B> {
+  pub(crate) fn new(fd: impl BorrowFd, buf: B, offset: i64) -> Self {
+    assert!((buf.bytes_init()) <= u32::MAX as usize);
+    Self {
+      fd: fd.raw_fd(),
+      buf: Some(buf),
+      #[cfg(feature = "fault_injection")]
+      max_len: usize::MAX,
+      offset,
+    }
+  }
+
+  /// Bytes handed to the kernel.
+  fn len(&self) -> usize {
+    let len = self.buf.as_ref().unwrap().bytes_init();
+    #[cfg(feature = "fault_injection")]
+    let len = len.min(self.max_len);
+    len
   }
 }
 
-impl Operation for Write {
-  type Result = BufResult<i32, Vec<u8>>;
+impl<B: IoBuf> Operation for Write<B> {
+  impl_trace!(fd, bytes_init);
+
+  type Result = BufResult<i32, B>;
 
   #[cfg(linux)]
   const OPCODE: u8 = 23;
@@ -33,9 +53,27 @@ impl Operation for Write {
   #[cfg(linux)]
   fn create_entry(&mut self) -> io_uring::squeue::Entry {
     let buf = self.buf.as_ref().unwrap();
-    io_uring::opcode::Write::new(Fd(self.fd), buf.as_ptr(), buf.len() as u32)
-      .offset(self.offset as u64)
-      .build()
+    io_uring::opcode::Write::new(
+      Fd(self.fd),
+      buf.stable_ptr(),
+      self.len() as u32,
+    )
+    .offset(self.offset as u64)
+    .build()
+  }
+
+  #[cfg(feature = "fault_injection")]
+  fn shorten(&mut self, len: usize) {
+    self.max_len = len;
+  }
+
+  #[cfg(feature = "sim")]
+  fn simulate(
+    &mut self,
+    sim: &mut crate::backends::SimState,
+  ) -> std::task::Poll<std::io::Result<i32>> {
+    let buf = self.buf.as_ref().unwrap();
+    sim.write(self.fd, self.offset, &crate::backends::bytes(buf)[..self.len()])
   }
 
   #[cfg(not(linux))]
@@ -50,8 +88,8 @@ impl Operation for Write {
   fn run_blocking(&self) -> std::io::Result<i32> {
     syscall!(pwrite(
       self.fd,
-      self.buf.as_ref().unwrap().as_ptr() as *const _,
-      self.buf.as_ref().unwrap().len(),
+      self.buf.as_ref().unwrap().stable_ptr() as *const _,
+      self.len(),
       self.offset
     ))
     .map(|u| u as i32)
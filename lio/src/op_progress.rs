@@ -1,6 +1,6 @@
 use crate::op::Operation;
 
-#[cfg(linux)]
+#[cfg(any(linux, feature = "sim"))]
 use std::marker::PhantomData;
 #[cfg(feature = "high")]
 use std::{
@@ -8,11 +8,14 @@ use std::{
   pin::Pin,
   task::{Context, Poll},
 };
-use std::{io, thread};
+use std::{io, thread, time::Instant};
 
+#[cfg(feature = "high")]
+use crate::op_registration::OpCallback;
 use crate::{
   Driver,
   op::{self, DetachSafe},
+  stats,
 };
 
 /// Represents the progress of an I/O operation across different platforms.
@@ -34,7 +37,7 @@ use crate::{
 ///
 /// async fn example() -> std::io::Result<()> {
 ///     let fd: RawFd = 0; // stdin
-///     let buffer = vec![0u8; 1024];
+///     let buffer = Vec::with_capacity(1024);
 ///     
 ///     let progress: OperationProgress<lio::op::Read> = read(fd, buffer, 0);
 ///     let (result_bytes_read, buf) = progress.await;
@@ -46,19 +49,33 @@ use crate::{
 pub enum OperationProgress<T> {
   #[cfg(not(linux))]
   #[cfg_attr(docsrs, doc(cfg(not(linux))))]
-  Poll { id: u64 },
+  Poll {
+    id: u64,
+  },
 
   #[cfg(linux)]
   #[cfg_attr(docsrs, doc(cfg(linux)))]
-  IoUring { id: u64, _m: PhantomData<T> },
+  IoUring {
+    id: u64,
+    _m: PhantomData<T>,
+  },
 
-  #[cfg(not(linux))]
-  #[cfg_attr(docsrs, doc(cfg(not(linux))))]
-  Blocking { operation: Option<T> },
+  /// Registered with the [`Simulated`](crate::Simulated) backend.
+  #[cfg(feature = "sim")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "sim")))]
+  Simulated {
+    id: u64,
+    _m: PhantomData<T>,
+  },
 
-  #[cfg(not(linux))]
-  #[cfg_attr(docsrs, doc(cfg(not(linux))))]
-  FromResult { res: Option<io::Result<i32>>, operation: T },
+  Blocking {
+    operation: Option<T>,
+  },
+
+  FromResult {
+    res: Option<io::Result<i32>>,
+    operation: T,
+  },
 }
 
 unsafe impl<T> Send for OperationProgress<T> where T: Send {}
@@ -78,7 +95,7 @@ impl<T: op::Operation> OperationProgress<T> {
   ///
   /// async fn detach_example() -> std::io::Result<()> {
   ///     let fd: RawFd = 0;
-  ///     let buffer = vec![0u8; 1024];
+  ///     let buffer = Vec::with_capacity(1024);
   ///     let progress: OperationProgress<lio::op::Read> = read(fd, buffer, 0);
   ///     
   ///     // Detach without waiting for completion
@@ -91,7 +108,20 @@ impl<T: op::Operation> OperationProgress<T> {
   where
     T: DetachSafe + Send + 'static,
   {
-    self.when_done(drop);
+    #[cfg(feature = "tracing")]
+    match self {
+      #[cfg(linux)]
+      OperationProgress::IoUring { id, .. } => Driver::get().span(id),
+      #[cfg(not(linux))]
+      OperationProgress::Poll { id } => Driver::get().span(id),
+      #[cfg(feature = "sim")]
+      OperationProgress::Simulated { id, .. } => Driver::get().span(id),
+      _ => None,
+    }
+    .unwrap_or_else(tracing::Span::current)
+    .in_scope(|| tracing::debug!("detached"));
+
+    self.when_done(T::release);
   }
 
   /// Registers a callback to be invoked when the operation completes.
@@ -120,7 +150,8 @@ impl<T: op::Operation> OperationProgress<T> {
   ///
   /// - **Blocking operations**: The callback is invoked immediately (synchronously)
   /// - **Async operations** (io_uring/polling): The callback is invoked asynchronously
-  ///   on the background I/O thread when the operation completes
+  ///   when the operation completes, on the background I/O thread unless
+  ///   [`set_callback_dispatch`](crate::set_callback_dispatch) says otherwise
   ///
   /// # Examples
   ///
@@ -132,7 +163,7 @@ impl<T: op::Operation> OperationProgress<T> {
   ///
   /// async fn example() -> std::io::Result<()> {
   ///     # let fd = 0;
-  ///     let buffer = vec![0u8; 1024];
+  ///     let buffer = Vec::with_capacity(1024);
   ///     let (tx, rx) = channel();
   ///
   ///     // Use callback instead of awaiting
@@ -191,17 +222,20 @@ impl<T: op::Operation> OperationProgress<T> {
         Driver::get().set_callback::<T, F>(id, callback);
         std::mem::forget(self); // Prevent Drop from cancelling the operation
       }
-      #[cfg(not(linux))]
+      #[cfg(feature = "sim")]
+      OperationProgress::Simulated { id, .. } => {
+        Driver::get().set_callback::<T, F>(id, callback);
+        std::mem::forget(self); // Prevent Drop from cancelling the operation
+      }
       OperationProgress::Blocking { ref mut operation } => {
         let mut op = operation.take().expect("no operation found");
         // For now.
         thread::spawn(move || {
-          let result = op.run_blocking();
+          let result = Self::run_blocking(&op);
           let output = op.result(result);
           callback(output);
         });
       }
-      #[cfg(not(linux))]
       OperationProgress::FromResult { ref mut res, ref mut operation } => {
         let res = res.take().expect("Blocking operation already consumed");
         let output = operation.result(res);
@@ -223,7 +257,7 @@ impl<T: op::Operation> OperationProgress<T> {
   /// # let fd = 0;
   /// // Some fd defined.
   /// // ...
-  /// let buf = vec![0; 10];
+  /// let buf = Vec::with_capacity(10);
   /// let receiver = lio::read(fd, buf, 0).get_receiver();
   /// let (result, buffer) = receiver.recv().unwrap();
   /// # }
@@ -281,6 +315,84 @@ where
   }
 }
 
+impl<T> OperationProgress<T>
+where
+  T: op::Operation,
+{
+  /// Id of the operation in the driver, if it's registered there.
+  #[cfg(feature = "unstable_ffi")]
+  pub(crate) fn id(&self) -> Option<u64> {
+    match self {
+      #[cfg(linux)]
+      Self::IoUring { id, .. } => Some(*id),
+      #[cfg(not(linux))]
+      Self::Poll { id } => Some(*id),
+      #[cfg(feature = "sim")]
+      Self::Simulated { id, .. } => Some(*id),
+      Self::Blocking { .. } | Self::FromResult { .. } => None,
+    }
+  }
+
+  /// Drops the progress like [`Drop`], but keeps `guard` alive until the
+  /// operation completed.
+  #[cfg(feature = "high")]
+  pub(crate) fn drop_with<G: Send + 'static>(self, guard: G) {
+    let id = match self {
+      #[cfg(linux)]
+      Self::IoUring { id, .. } => id,
+      #[cfg(not(linux))]
+      Self::Poll { id } => id,
+      #[cfg(feature = "sim")]
+      Self::Simulated { id, .. } => id,
+      // Ran when polled, so nothing is in flight.
+      Self::Blocking { .. } | Self::FromResult { .. } => return,
+    };
+    std::mem::forget(self);
+    if let Some(driver) = Driver::try_get() {
+      driver.detach_with(
+        id,
+        OpCallback::new::<T, _>(move |res| {
+          T::release(res);
+          drop(guard);
+        }),
+      );
+    }
+  }
+
+  pub(crate) fn new_blocking(operation: T) -> Self {
+    Self::Blocking { operation: Some(operation) }
+  }
+
+  pub(crate) fn new_from_result(operation: T, result: io::Result<i32>) -> Self {
+    stats::counters::<T>().completed(Instant::now(), &result);
+    Self::FromResult { operation, res: Some(result) }
+  }
+
+  fn run_blocking(op: &T) -> io::Result<i32> {
+    #[cfg(feature = "tracing")]
+    let span = crate::driver::op_span(op);
+    #[cfg(feature = "tracing")]
+    let _enter = span.enter();
+
+    let started = Instant::now();
+    let result = op.run_blocking();
+    stats::counters::<T>().completed(started, &result);
+    #[cfg(feature = "tracing")]
+    crate::driver::record_result(&span, &result, started.elapsed());
+    result
+  }
+}
+
+#[cfg(feature = "sim")]
+impl<T> OperationProgress<T>
+where
+  T: op::Operation,
+{
+  pub(crate) fn new_simulated(id: u64) -> Self {
+    Self::Simulated { id, _m: PhantomData }
+  }
+}
+
 #[cfg(not(linux))]
 impl<T> OperationProgress<T>
 where
@@ -298,14 +410,6 @@ where
 
     Self::Poll { id }
   }
-
-  pub(crate) fn new_blocking(operation: T) -> Self {
-    Self::Blocking { operation: Some(operation) }
-  }
-
-  pub(crate) fn new_from_result(operation: T, result: io::Result<i32>) -> Self {
-    Self::FromResult { operation, res: Some(result) }
-  }
 }
 
 /// Implements `Future` for polling-based operations on non-Linux platforms.
@@ -342,13 +446,13 @@ where
       OperationProgress::IoUring { id, _m: _ } => check_done::<T>(id, cx),
       #[cfg(not(linux))]
       OperationProgress::Poll { id } => check_done::<T>(id, cx),
-      #[cfg(not(linux))]
+      #[cfg(feature = "sim")]
+      OperationProgress::Simulated { id, _m: _ } => check_done::<T>(id, cx),
       OperationProgress::Blocking { ref mut operation } => {
         let mut op = operation.take().expect("no operation found");
-        let result = op.run_blocking();
+        let result = Self::run_blocking(&op);
         Poll::Ready(op.result(result))
       }
-      #[cfg(not(linux))]
       OperationProgress::FromResult { ref mut res, ref mut operation } => {
         let result = operation.result(res.take().expect("Already awaited."));
         Poll::Ready(result)
@@ -361,19 +465,23 @@ impl<T> Drop for OperationProgress<T> {
   fn drop(&mut self) {
     match self {
       #[cfg(not(linux))]
-      OperationProgress::Poll { id: _, .. } => {
-        // Driver::get().detach(*id);
-      }
+      OperationProgress::Poll { id } => detach(*id),
       #[cfg(linux)]
-      OperationProgress::IoUring { id: _, _m, .. } => {
-        // Driver::get().detach(*id);
-      }
-      #[cfg(not(linux))]
+      OperationProgress::IoUring { id, .. } => detach(*id),
+      #[cfg(feature = "sim")]
+      OperationProgress::Simulated { id, .. } => detach(*id),
       OperationProgress::Blocking { .. } => {
         // Blocking operations don't need cleanup
       }
-      #[cfg(not(linux))]
       OperationProgress::FromResult { res: _, operation: _ } => {}
     }
   }
 }
+
+/// Detaches a dropped operation, so its registration is freed and its result
+/// released on completion. Nothing is left to do after the driver exited.
+fn detach(id: u64) {
+  if let Some(driver) = Driver::try_get() {
+    driver.detach(id);
+  }
+}
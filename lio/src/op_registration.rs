@@ -1,14 +1,22 @@
 // NOTE: OpRegistration should **NEVER** impl Sync.gg
-use std::io;
+use std::{io, mem, time::Instant};
 
 #[cfg(feature = "high")]
 use std::task::Waker;
 
-use crate::op::Operation;
+#[cfg(feature = "sim")]
+use std::task::Poll;
+
+#[cfg(feature = "sim")]
+use crate::backends::SimState;
+#[cfg(feature = "fault_injection")]
+use crate::fault::Hold;
+use crate::{op::Operation, stats::OpCounters};
 
 pub struct OpCallback {
   callback: *const (),
   call_callback_fn: fn(*const (), &mut OpRegistration),
+  drop_callback_fn: fn(*const ()),
 }
 
 impl OpCallback {
@@ -17,14 +25,20 @@ impl OpCallback {
     T: Operation,
     F: FnOnce(T::Result) + Send,
   {
+    fn drop_callback<F>(ptr: *const ()) {
+      drop(unsafe { Box::from_raw(ptr as *mut F) })
+    }
+
     OpCallback {
       callback: Box::into_raw(Box::new(callback)) as *const (),
       call_callback_fn: Self::call_callback::<T, F>,
+      drop_callback_fn: drop_callback::<F>,
     }
   }
 
   pub fn call(self, reg: &mut OpRegistration) {
-    (self.call_callback_fn)(self.callback, reg);
+    let this = mem::ManuallyDrop::new(self);
+    (this.call_callback_fn)(this.callback, reg);
   }
 
   fn call_callback<T, F>(callback_ptr: *const (), reg: &mut OpRegistration)
@@ -42,6 +56,13 @@ impl OpCallback {
   }
 }
 
+/// Only reached if the callback is never called, for example on driver shutdown.
+impl Drop for OpCallback {
+  fn drop(&mut self) {
+    (self.drop_callback_fn)(self.callback);
+  }
+}
+
 unsafe impl Send for OpCallback {}
 
 pub struct OpRegistration {
@@ -50,8 +71,21 @@ pub struct OpRegistration {
   // Fields common to both platforms
   op: Option<*const ()>,
   op_fn_drop: fn(*const ()), // Function to properly drop the operation
+  op_fn_release: fn() -> OpCallback, // Callback passing the result to Operation::release
   #[cfg(not(linux))]
   op_fn_run_blocking: fn(*const ()) -> std::io::Result<i32>, // Function to properly drop the operation
+  #[cfg(feature = "sim")]
+  op_fn_simulate: fn(*const (), &mut SimState) -> Poll<io::Result<i32>>,
+
+  counters: &'static OpCounters,
+  submitted_at: Instant,
+  #[cfg(feature = "tracing")]
+  span: tracing::Span,
+  /// Injected delay, the result waits in `held` until it expires.
+  #[cfg(feature = "fault_injection")]
+  hold: Option<Hold>,
+  #[cfg(feature = "fault_injection")]
+  held: Option<io::Result<i32>>,
 }
 
 impl Drop for OpRegistration {
@@ -134,6 +168,10 @@ impl OpRegistration {
       drop(unsafe { Box::from_raw(ptr as *mut T) })
     }
 
+    fn release<T: Operation>() -> OpCallback {
+      OpCallback::new::<T, _>(T::release)
+    }
+
     #[cfg(not(linux))]
     fn op_fn_run_blocking<T>(ptr: *const ()) -> std::io::Result<i32>
     where
@@ -143,20 +181,71 @@ impl OpRegistration {
       op.run_blocking()
     }
 
+    #[cfg(feature = "sim")]
+    fn op_fn_simulate<T>(
+      ptr: *const (),
+      sim: &mut SimState,
+    ) -> Poll<io::Result<i32>>
+    where
+      T: Operation,
+    {
+      let op: &mut T = unsafe { &mut *(ptr as *mut T) };
+      op.simulate(sim)
+    }
+
     OpRegistration {
       op: Some(Box::into_raw(op) as *const ()),
       op_fn_drop: drop_op::<T>,
+      op_fn_release: release::<T>,
       #[cfg(not(linux))]
       op_fn_run_blocking: op_fn_run_blocking::<T>,
+      #[cfg(feature = "sim")]
+      op_fn_simulate: op_fn_simulate::<T>,
       status: OpRegistrationStatus::Waiting { notifier: None },
+      counters: crate::stats::counters::<T>(),
+      submitted_at: Instant::now(),
+      // Registrations are created while the span from Driver::submit is entered.
+      #[cfg(feature = "tracing")]
+      span: tracing::Span::current(),
+      // Set by Driver::submit, like the span.
+      #[cfg(feature = "fault_injection")]
+      hold: Hold::take(),
+      #[cfg(feature = "fault_injection")]
+      held: None,
     }
   }
 
+  #[cfg(feature = "tracing")]
+  pub fn span(&self) -> &tracing::Span {
+    &self.span
+  }
+
+  /// If the operation is still owned by the kernel.
+  pub fn is_pending(&self) -> bool {
+    matches!(self.status, OpRegistrationStatus::Waiting { .. })
+  }
+
+  /// Forgets the operation without dropping it, for when the kernel might still
+  /// access it. The notifier is dropped without being notified.
+  pub fn leak(&mut self) {
+    #[cfg(feature = "tracing")]
+    self.span.in_scope(|| tracing::warn!("leaked on shutdown"));
+
+    self.op = None;
+    self.status = OpRegistrationStatus::Waiting { notifier: None };
+  }
+
   #[cfg(not(linux))]
   pub fn run_blocking(&mut self) -> io::Result<i32> {
     (self.op_fn_run_blocking)(self.op_ptr())
   }
 
+  /// Tries to run the operation against the simulation, see [`Operation::simulate`].
+  #[cfg(feature = "sim")]
+  pub fn simulate(&mut self, sim: &mut SimState) -> Poll<io::Result<i32>> {
+    (self.op_fn_simulate)(self.op_ptr(), sim)
+  }
+
   pub fn try_extract<T>(&mut self) -> TryExtractOutcome<T::Result>
   where
     T: Operation,
@@ -206,12 +295,16 @@ impl OpRegistration {
       *notifier = Some(OpNotification::Waker(Some(waker)));
     };
   }
-  pub fn set_callback(&mut self, callback: OpCallback) {
+  /// Returns the callback back if the operation already completed, it should
+  /// then be called with [`OpStore::run_callback`](crate::driver::OpStore::run_callback).
+  pub fn set_callback(&mut self, callback: OpCallback) -> Option<OpCallback> {
+    #[cfg(feature = "tracing")]
+    self.span.in_scope(|| tracing::trace!("callback registered"));
+
     let notifier = match self.status {
       OpRegistrationStatus::Done { ref before_notifier, .. } => {
         if *before_notifier {
-          callback.call(self);
-          return;
+          return Some(callback);
         } else {
           unreachable!("internal lio: not allowed.");
         };
@@ -241,13 +334,57 @@ impl OpRegistration {
     } else {
       *notifier = Some(OpNotification::Callback(Some(callback)));
     };
+    None
+  }
+
+  /// Hands the result to [`Operation::release`] instead of whoever was waiting
+  /// for it. Returns the callback if the operation already completed, like
+  /// [`OpRegistration::set_callback`].
+  pub fn detach(&mut self) -> Option<OpCallback> {
+    let callback = (self.op_fn_release)();
+    self.detach_with(callback)
+  }
+
+  /// Like [`OpRegistration::detach`], handing the result to `callback` instead.
+  pub fn detach_with(&mut self, callback: OpCallback) -> Option<OpCallback> {
+    match self.status {
+      OpRegistrationStatus::Done { .. } => Some(callback),
+      OpRegistrationStatus::Waiting { ref mut notifier } => {
+        // A registered callback already owns the result.
+        if !matches!(notifier, Some(OpNotification::Callback(Some(_)))) {
+          *notifier = Some(OpNotification::Callback(Some(callback)));
+        }
+        None
+      }
+    }
+  }
+
+  /// If a held completion is waiting for [`OpRegistration::release`].
+  #[cfg(feature = "fault_injection")]
+  pub fn is_held(&self) -> bool {
+    self.held.is_some()
+  }
+
+  /// Completes the operation with its held result once the hold expires.
+  #[cfg(feature = "fault_injection")]
+  pub fn release(&mut self) -> Option<Option<ExtractedOpNotification>> {
+    if self.held.is_none() || !self.hold.as_mut()?.expired() {
+      return None;
+    }
+    self.hold = None;
+    let res = self.held.take().expect("checked above");
+    Some(self.set_done(res))
   }
 
   pub fn set_done(
     &mut self,
     res: io::Result<i32>,
   ) -> Option<ExtractedOpNotification> {
-    use std::mem;
+    #[cfg(feature = "fault_injection")]
+    if self.hold.is_some() {
+      self.held = Some(res);
+      return None;
+    }
 
     let before_notifier = match &self.status {
       OpRegistrationStatus::Waiting { notifier } => notifier.is_none(),
@@ -256,6 +393,12 @@ impl OpRegistration {
       }
     };
 
+    self.counters.completed(self.submitted_at, &res);
+    #[cfg(feature = "tracing")]
+    crate::driver::record_result(&self.span, &res, self.submitted_at.elapsed());
+    #[cfg(feature = "tracing")]
+    let _enter = self.span.enter();
+
     let old = mem::replace(
       &mut self.status,
       OpRegistrationStatus::Done { ret: Some(res), before_notifier },
@@ -0,0 +1,260 @@
+use std::{
+  collections::VecDeque,
+  fmt,
+  future::{Future, poll_fn},
+  pin::Pin,
+  sync::{
+    Arc, Weak,
+    atomic::{AtomicBool, Ordering},
+  },
+  task::{Context, Poll, Wake, Waker},
+};
+
+use parking_lot::Mutex;
+
+use crate::{OperationProgress, op::Operation};
+
+type Member<R> = Pin<Box<dyn Future<Output = R> + Send>>;
+
+/// Most members [`OpSet::poll_next`] polls before yielding to the executor,
+/// like `FuturesUnordered`.
+const POLL_BUDGET: usize = 32;
+
+/// Many operations, whose results are yielded in the order they complete.
+///
+/// Each operation is woken on its own, so [`next`](OpSet::next) only polls the
+/// ones that completed instead of every member. Operations of different types
+/// can share a set if their results convert into `R`, see
+/// [`push_map`](OpSet::push_map).
+///
+/// Dropping the set drops the operations still in it, see
+/// [`OperationProgress`].
+///
+/// # Examples
+///
+/// ```rust
+/// use lio::{BufResult, OpSet};
+///
+/// async fn read_segments(fd: std::os::fd::RawFd) -> std::io::Result<()> {
+///     let mut set: OpSet<BufResult<i32, Vec<u8>>> = (0..200)
+///         .map(|segment| lio::read(fd, Vec::with_capacity(4096), segment * 4096))
+///         .collect();
+///
+///     while let Some((res, buf)) = set.next().await {
+///         println!("read {} bytes", res?);
+///         # drop(buf);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub struct OpSet<R> {
+  members: Vec<Option<(Member<R>, Arc<Slot>)>>,
+  /// Indices of `members` that are `None`.
+  free: Vec<usize>,
+  len: usize,
+  shared: Arc<Shared>,
+}
+
+/// State the members' wakers share with the set.
+struct Shared {
+  /// Members woken since they were last polled.
+  ready: Mutex<VecDeque<usize>>,
+  /// The task waiting in [`OpSet::poll_next`].
+  waker: Mutex<Option<Waker>>,
+}
+
+/// Waker of a single member.
+struct Slot {
+  index: usize,
+  /// Set while `index` is in `Shared::ready`.
+  queued: AtomicBool,
+  shared: Weak<Shared>,
+}
+
+impl Wake for Slot {
+  fn wake(self: Arc<Self>) {
+    self.wake_by_ref()
+  }
+
+  fn wake_by_ref(self: &Arc<Self>) {
+    if self.queued.swap(true, Ordering::AcqRel) {
+      return;
+    }
+    let Some(shared) = self.shared.upgrade() else { return };
+    shared.ready.lock().push_back(self.index);
+    if let Some(waker) = shared.waker.lock().take() {
+      waker.wake();
+    }
+  }
+}
+
+impl<R> OpSet<R> {
+  pub fn new() -> Self {
+    Self {
+      members: Vec::new(),
+      free: Vec::new(),
+      len: 0,
+      shared: Arc::new(Shared {
+        ready: Mutex::new(VecDeque::new()),
+        waker: Mutex::new(None),
+      }),
+    }
+  }
+
+  /// Number of operations that haven't been yielded yet.
+  pub fn len(&self) -> usize {
+    self.len
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+
+  /// Adds an operation, whose result is converted into `R`.
+  pub fn push<T>(&mut self, op: OperationProgress<T>)
+  where
+    T: Operation + Unpin + Send + 'static,
+    T::Result: Into<R>,
+    R: 'static,
+  {
+    self.push_map(op, Into::into)
+  }
+
+  /// Adds an operation, whose result is turned into `R` with `map`. Useful to
+  /// mix operation types through an enum.
+  ///
+  /// # Examples
+  ///
+  /// ```rust
+  /// use lio::OpSet;
+  ///
+  /// enum Done {
+  ///     Read(lio::BufResult<i32, Vec<u8>>),
+  ///     Write(lio::BufResult<i32, Vec<u8>>),
+  ///     Fsync(std::io::Result<()>),
+  /// }
+  ///
+  /// async fn copy(from: std::os::fd::RawFd, to: std::os::fd::RawFd) {
+  ///     let mut set = OpSet::new();
+  ///     set.push_map(lio::read(from, Vec::with_capacity(64), 0), Done::Read);
+  ///     set.push_map(lio::write(to, b"header".to_vec(), 0), Done::Write);
+  ///     set.push_map(lio::fsync(to), Done::Fsync);
+  ///
+  ///     while let Some(done) = set.next().await {
+  ///         match done {
+  ///             Done::Read((res, _)) | Done::Write((res, _)) => drop(res),
+  ///             Done::Fsync(res) => drop(res),
+  ///         }
+  ///     }
+  /// }
+  /// ```
+  pub fn push_map<T, F>(&mut self, op: OperationProgress<T>, map: F)
+  where
+    T: Operation + Unpin + Send + 'static,
+    F: FnOnce(T::Result) -> R + Send + 'static,
+    R: 'static,
+  {
+    let member: Member<R> = Box::pin(async move { map(op.await) });
+
+    let index = self.free.pop().unwrap_or(self.members.len());
+    // Polled once by the next `poll_next`, which registers its waker.
+    let slot = Arc::new(Slot {
+      index,
+      queued: AtomicBool::new(true),
+      shared: Arc::downgrade(&self.shared),
+    });
+    self.shared.ready.lock().push_back(index);
+
+    if index == self.members.len() {
+      self.members.push(Some((member, slot)));
+    } else {
+      self.members[index] = Some((member, slot));
+    }
+    self.len += 1;
+  }
+
+  /// Polls for the next completed operation. `Ready(None)` once the set is
+  /// empty.
+  pub fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<R>> {
+    if self.len == 0 {
+      return Poll::Ready(None);
+    }
+    // Registered before looking at `ready`, so no wake is missed.
+    *self.shared.waker.lock() = Some(cx.waker().clone());
+
+    let budget = POLL_BUDGET.min(self.len);
+    let mut polled = 0;
+    loop {
+      let Some(index) = self.shared.ready.lock().pop_front() else {
+        return Poll::Pending;
+      };
+      // Members yielded since they were queued are gone.
+      let Some((member, slot)) = &mut self.members[index] else { continue };
+
+      // Out of budget, so a member waking itself can't starve other tasks.
+      // The rest is polled the next time.
+      if polled == budget {
+        self.shared.ready.lock().push_front(index);
+        cx.waker().wake_by_ref();
+        return Poll::Pending;
+      }
+      polled += 1;
+
+      // Cleared before polling, so a wake from here on queues it again.
+      slot.queued.store(false, Ordering::Release);
+      let waker = Waker::from(slot.clone());
+      if let Poll::Ready(result) =
+        member.as_mut().poll(&mut Context::from_waker(&waker))
+      {
+        self.members[index] = None;
+        self.free.push(index);
+        self.len -= 1;
+        return Poll::Ready(Some(result));
+      }
+    }
+  }
+
+  /// Waits for the next operation to complete, and returns its result.
+  /// Returns `None` once the set is empty.
+  pub async fn next(&mut self) -> Option<R> {
+    poll_fn(|cx| self.poll_next(cx)).await
+  }
+}
+
+impl<R> Default for OpSet<R> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<R> fmt::Debug for OpSet<R> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("OpSet").field("len", &self.len).finish_non_exhaustive()
+  }
+}
+
+impl<T, R> Extend<OperationProgress<T>> for OpSet<R>
+where
+  T: Operation + Unpin + Send + 'static,
+  T::Result: Into<R>,
+  R: 'static,
+{
+  fn extend<I: IntoIterator<Item = OperationProgress<T>>>(&mut self, ops: I) {
+    for op in ops {
+      self.push(op);
+    }
+  }
+}
+
+impl<T, R> FromIterator<OperationProgress<T>> for OpSet<R>
+where
+  T: Operation + Unpin + Send + 'static,
+  T::Result: Into<R>,
+  R: 'static,
+{
+  fn from_iter<I: IntoIterator<Item = OperationProgress<T>>>(ops: I) -> Self {
+    let mut set = OpSet::new();
+    set.extend(ops);
+    set
+  }
+}
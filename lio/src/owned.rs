@@ -0,0 +1,120 @@
+//! File descriptor ownership shared by [`fs::File`](crate::fs::File) and
+//! [`net::Socket`](crate::net::Socket).
+
+use std::{
+  future::Future,
+  io,
+  mem::ManuallyDrop,
+  os::fd::{AsFd, AsRawFd, BorrowedFd, IntoRawFd, OwnedFd, RawFd},
+  pin::Pin,
+  sync::Arc,
+  task::{Context, Poll, ready},
+};
+
+use crate::{
+  Exact, OperationProgress,
+  exact::Step,
+  op::{Close, Operation},
+};
+
+/// An [`OwnedFd`] that is closed through the driver when dropped, instead of
+/// with a blocking `close`.
+#[derive(Debug)]
+pub(crate) struct Owned(ManuallyDrop<OwnedFd>);
+
+impl Owned {
+  pub(crate) fn new(fd: OwnedFd) -> Arc<Self> {
+    Arc::new(Self(ManuallyDrop::new(fd)))
+  }
+
+  fn take(self) -> OwnedFd {
+    let mut this = ManuallyDrop::new(self);
+    // SAFETY: `this` is never used or dropped again.
+    unsafe { ManuallyDrop::take(&mut this.0) }
+  }
+
+  /// Takes the descriptor out, or hands `this` back if an operation whose
+  /// future was dropped still uses it, since that one closes it later.
+  pub(crate) fn try_into_owned(this: Arc<Self>) -> Result<OwnedFd, Arc<Self>> {
+    Arc::try_unwrap(this).map(Self::take)
+  }
+
+  /// Closes the descriptor and waits for it, to see errors from `close`. If an
+  /// operation whose future was dropped still uses it, it's closed once that
+  /// completed instead, like when dropped.
+  pub(crate) async fn close(this: Arc<Self>) -> io::Result<()> {
+    match Arc::try_unwrap(this) {
+      Ok(this) => crate::close(this.take().into_raw_fd()).await,
+      Err(_) => Ok(()),
+    }
+  }
+
+  /// Ties `progress` to the descriptor, so it stays open until the operation
+  /// completed even if the future is dropped first. Otherwise it could be
+  /// closed, and its number reused, under the running operation.
+  pub(crate) fn hold<P: InFlight>(self: &Arc<Self>, progress: P) -> Held<P> {
+    Held { progress: Some(progress), fd: self.clone() }
+  }
+}
+
+impl Drop for Owned {
+  fn drop(&mut self) {
+    // SAFETY: Dropped only once, and never used afterwards.
+    Close::detached(unsafe { ManuallyDrop::take(&mut self.0) });
+  }
+}
+
+impl AsFd for Owned {
+  fn as_fd(&self) -> BorrowedFd<'_> {
+    self.0.as_fd()
+  }
+}
+
+impl AsRawFd for Owned {
+  fn as_raw_fd(&self) -> RawFd {
+    self.0.as_raw_fd()
+  }
+}
+
+/// An operation, or transfer, that may still be running when dropped.
+pub(crate) trait InFlight: Future + Unpin {
+  /// Drops it, keeping `fd` open until it completed.
+  fn drop_with(self, fd: Arc<Owned>);
+}
+
+impl<T: Operation + Unpin> InFlight for OperationProgress<T> {
+  fn drop_with(self, fd: Arc<Owned>) {
+    OperationProgress::drop_with(self, fd);
+  }
+}
+
+impl<O: Step> InFlight for Exact<O> {
+  fn drop_with(self, fd: Arc<Owned>) {
+    Exact::drop_with(self, fd);
+  }
+}
+
+/// Future from [`Owned::hold`].
+pub(crate) struct Held<P: InFlight> {
+  progress: Option<P>,
+  fd: Arc<Owned>,
+}
+
+impl<P: InFlight> Future for Held<P> {
+  type Output = P::Output;
+
+  fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<P::Output> {
+    let progress = self.progress.as_mut().expect("polled after completion");
+    let res = ready!(Pin::new(progress).poll(cx));
+    self.progress = None;
+    Poll::Ready(res)
+  }
+}
+
+impl<P: InFlight> Drop for Held<P> {
+  fn drop(&mut self) {
+    if let Some(progress) = self.progress.take() {
+      progress.drop_with(self.fd.clone());
+    }
+  }
+}
@@ -0,0 +1,227 @@
+//! Socket options for [`setsockopt`](crate::setsockopt) and
+//! [`getsockopt`](crate::getsockopt).
+//!
+//! Each option is a type implementing [`SockOpt`], which knows its level, its
+//! name and how to convert the value to the C type the kernel expects. Options
+//! missing here can be set through [`Raw`].
+//!
+//! # Examples
+//!
+//! ```rust
+//! use lio::sockopt::{Linger, ReuseAddr, TcpNoDelay};
+//! use std::time::Duration;
+//!
+//! async fn configure(fd: std::os::fd::RawFd) -> std::io::Result<()> {
+//!     lio::setsockopt(fd, ReuseAddr, true).await?;
+//!     lio::setsockopt(fd, TcpNoDelay, true).await?;
+//!     lio::setsockopt(fd, Linger, Some(Duration::from_secs(5))).await?;
+//!     assert!(lio::getsockopt(fd, TcpNoDelay).await?);
+//!     Ok(())
+//! }
+//! ```
+
+use std::{marker::PhantomData, time::Duration};
+
+/// A socket option, see the [module docs](self).
+///
+/// # Safety
+///
+/// The kernel reads and writes the option as a [`SockOpt::Repr`], so it must
+/// be the C type the option expects, and any bit pattern of it must be valid.
+pub unsafe trait SockOpt: Send + Unpin + 'static {
+  /// Value as seen by the caller.
+  type Value;
+  /// Value as seen by the kernel.
+  type Repr: Copy + Send + Unpin + 'static;
+
+  /// Protocol level, for example `SOL_SOCKET` or `IPPROTO_TCP`.
+  fn level(&self) -> libc::c_int;
+  /// Option name, for example `SO_REUSEADDR`.
+  fn name(&self) -> libc::c_int;
+
+  fn to_repr(value: Self::Value) -> Self::Repr;
+  fn from_repr(repr: Self::Repr) -> Self::Value;
+}
+
+macro_rules! sockopt {
+  (
+    $(#[$doc:meta])*
+    $name:ident, $level:expr, $opt:expr, bool
+  ) => {
+    sockopt!(
+      $(#[$doc])*
+      $name, $level, $opt, bool, libc::c_int,
+      |value| value as libc::c_int,
+      |repr| repr != 0
+    );
+  };
+
+  (
+    $(#[$doc:meta])*
+    $name:ident, $level:expr, $opt:expr, $value:ty, $repr:ty,
+    $to:expr, $from:expr
+  ) => {
+    $(#[$doc])*
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct $name;
+
+    unsafe impl SockOpt for $name {
+      type Value = $value;
+      type Repr = $repr;
+
+      fn level(&self) -> libc::c_int {
+        $level
+      }
+
+      fn name(&self) -> libc::c_int {
+        $opt
+      }
+
+      fn to_repr(value: $value) -> $repr {
+        let to: fn($value) -> $repr = $to;
+        to(value)
+      }
+
+      fn from_repr(repr: $repr) -> $value {
+        let from: fn($repr) -> $value = $from;
+        from(repr)
+      }
+    }
+  };
+}
+
+sockopt!(
+  /// `SO_REUSEADDR`: allows binding to an address still in `TIME_WAIT`.
+  ReuseAddr, libc::SOL_SOCKET, libc::SO_REUSEADDR, bool
+);
+
+sockopt!(
+  /// `SO_REUSEPORT`: allows several sockets to bind to the same address and
+  /// port.
+  ReusePort, libc::SOL_SOCKET, libc::SO_REUSEPORT, bool
+);
+
+sockopt!(
+  /// `SO_KEEPALIVE`: sends keepalive probes on idle connections.
+  KeepAlive, libc::SOL_SOCKET, libc::SO_KEEPALIVE, bool
+);
+
+sockopt!(
+  /// `SO_RCVBUF`: size of the receive buffer in bytes. Linux doubles the value
+  /// set, and reports the doubled value.
+  RecvBufferSize, libc::SOL_SOCKET, libc::SO_RCVBUF, usize, libc::c_int,
+  |size| size.min(libc::c_int::MAX as usize) as libc::c_int,
+  |repr| repr.max(0) as usize
+);
+
+sockopt!(
+  /// `SO_SNDBUF`: size of the send buffer in bytes. Linux doubles the value
+  /// set, and reports the doubled value.
+  SendBufferSize, libc::SOL_SOCKET, libc::SO_SNDBUF, usize, libc::c_int,
+  |size| size.min(libc::c_int::MAX as usize) as libc::c_int,
+  |repr| repr.max(0) as usize
+);
+
+sockopt!(
+  /// `SO_LINGER`: with `Some(timeout)`, closing the socket waits up to
+  /// `timeout` (in whole seconds) for unsent data to be sent.
+  Linger, libc::SOL_SOCKET, libc::SO_LINGER, Option<Duration>, libc::linger,
+  |timeout| libc::linger {
+    l_onoff: timeout.is_some() as libc::c_int,
+    l_linger: timeout.map_or(0, secs_to_repr),
+  },
+  |repr| (repr.l_onoff != 0).then(|| secs_from_repr(repr.l_linger))
+);
+
+sockopt!(
+  /// `TCP_NODELAY`: disables Nagle's algorithm, sending small writes right
+  /// away.
+  TcpNoDelay, libc::IPPROTO_TCP, libc::TCP_NODELAY, bool
+);
+
+sockopt!(
+  /// `TCP_KEEPIDLE` (`TCP_KEEPALIVE` on Apple platforms): idle time before the
+  /// first keepalive probe, in whole seconds. See [`KeepAlive`].
+  TcpKeepIdle, libc::IPPROTO_TCP, TCP_KEEPIDLE, Duration, libc::c_int,
+  secs_to_repr,
+  secs_from_repr
+);
+
+sockopt!(
+  /// `TCP_KEEPINTVL`: time between keepalive probes, in whole seconds. See
+  /// [`KeepAlive`].
+  TcpKeepInterval, libc::IPPROTO_TCP, libc::TCP_KEEPINTVL, Duration, libc::c_int,
+  secs_to_repr,
+  secs_from_repr
+);
+
+sockopt!(
+  /// `TCP_KEEPCNT`: number of unanswered keepalive probes before the
+  /// connection is dropped. See [`KeepAlive`].
+  TcpKeepCount, libc::IPPROTO_TCP, libc::TCP_KEEPCNT, u32, libc::c_int,
+  |count| count.min(libc::c_int::MAX as u32) as libc::c_int,
+  |repr| repr.max(0) as u32
+);
+
+#[cfg(not(target_vendor = "apple"))]
+const TCP_KEEPIDLE: libc::c_int = libc::TCP_KEEPIDLE;
+#[cfg(target_vendor = "apple")]
+const TCP_KEEPIDLE: libc::c_int = libc::TCP_KEEPALIVE;
+
+fn secs_to_repr(duration: Duration) -> libc::c_int {
+  duration.as_secs().min(libc::c_int::MAX as u64) as libc::c_int
+}
+
+fn secs_from_repr(secs: libc::c_int) -> Duration {
+  Duration::from_secs(secs.max(0) as u64)
+}
+
+/// Any option, passed to the kernel as a `T` unchanged.
+///
+/// # Examples
+///
+/// ```rust
+/// use lio::sockopt::Raw;
+///
+/// async fn set_low_water_mark(fd: std::os::fd::RawFd) -> std::io::Result<()> {
+///     // SAFETY: SO_RCVLOWAT takes a C int.
+///     let lowat = unsafe { Raw::<libc::c_int>::new(libc::SOL_SOCKET, libc::SO_RCVLOWAT) };
+///     lio::setsockopt(fd, lowat, 64).await
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Raw<T> {
+  level: libc::c_int,
+  name: libc::c_int,
+  _repr: PhantomData<fn() -> T>,
+}
+
+impl<T> Raw<T> {
+  /// # Safety
+  ///
+  /// The option must take a `T`, and any bit pattern of `T` must be valid.
+  pub unsafe fn new(level: libc::c_int, name: libc::c_int) -> Raw<T> {
+    Raw { level, name, _repr: PhantomData }
+  }
+}
+
+unsafe impl<T: Copy + Send + Unpin + 'static> SockOpt for Raw<T> {
+  type Value = T;
+  type Repr = T;
+
+  fn level(&self) -> libc::c_int {
+    self.level
+  }
+
+  fn name(&self) -> libc::c_int {
+    self.name
+  }
+
+  fn to_repr(value: T) -> T {
+    value
+  }
+
+  fn from_repr(repr: T) -> T {
+    repr
+  }
+}
@@ -0,0 +1,242 @@
+//! Driver metrics, see [`stats`](crate::stats()).
+
+use std::{
+  any,
+  collections::HashMap,
+  io,
+  sync::{
+    LazyLock,
+    atomic::{AtomicU64, Ordering},
+  },
+  time::{Duration, Instant},
+};
+
+use parking_lot::RwLock;
+
+use crate::{driver::Driver, op::Operation};
+
+/// Number of latency histogram buckets, the last one catches everything slower.
+#[cfg(feature = "histogram")]
+const BUCKETS: usize = 24;
+
+/// Snapshot of the driver's metrics, returned by [`stats`](crate::stats()).
+///
+/// Counters are process wide and keep counting across [`exit`](crate::exit) and
+/// [`init`](crate::init).
+#[derive(Debug, Clone)]
+pub struct Stats {
+  /// Operations registered in the driver: submitted and not yet completed, or
+  /// completed but not yet awaited.
+  pub in_flight: usize,
+  /// Submission and completion queue occupancy. `None` if the backend isn't
+  /// io_uring, or the driver isn't initialized.
+  pub ring: Option<RingStats>,
+  /// Counters per operation type, like `Read` or `Accept`.
+  pub ops: Vec<OpStats>,
+}
+
+impl Stats {
+  /// Counters for the operations called `name`, for example `"Read"`.
+  ///
+  /// Types sharing the name, like `Read` with different buffer types, are
+  /// added up. The result's [`type_name`](OpStats::type_name) is then `name`.
+  pub fn op(&self, name: &str) -> Option<OpStats> {
+    let mut ops = self.ops.iter().filter(|op| op.name == name);
+    let mut total = ops.next()?.clone();
+    for op in ops {
+      total.add(op);
+      total.type_name = total.name;
+    }
+    Some(total)
+  }
+}
+
+/// Occupancy of the io_uring submission and completion queues.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RingStats {
+  pub sq_len: usize,
+  pub sq_capacity: usize,
+  pub cq_len: usize,
+  pub cq_capacity: usize,
+  /// Completions dropped by the kernel because the completion queue was full.
+  pub cq_overflow: u32,
+}
+
+/// Counters for one operation type.
+#[derive(Debug, Clone)]
+pub struct OpStats {
+  /// Name of the operation type, without module path or generics. Can be the
+  /// same for different types, see [`type_name`](OpStats::type_name).
+  pub name: &'static str,
+  /// Full name of the operation type, like
+  /// `lio::op::read::Read<alloc::vec::Vec<u8>>`. Each type has its own
+  /// counters.
+  pub type_name: &'static str,
+  pub submitted: u64,
+  /// Completed operations, including failed ones.
+  pub completed: u64,
+  /// Completed operations that returned an error.
+  pub failed: u64,
+  /// Sum of the time from submission to completion.
+  pub total_latency: Duration,
+  pub max_latency: Duration,
+  #[cfg(feature = "histogram")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "histogram")))]
+  pub latency: Histogram,
+}
+
+impl OpStats {
+  /// Operations submitted but not completed, which includes leaked ones.
+  pub fn outstanding(&self) -> u64 {
+    self.submitted.saturating_sub(self.completed)
+  }
+
+  pub fn mean_latency(&self) -> Option<Duration> {
+    let completed = u32::try_from(self.completed).ok()?;
+    self.total_latency.checked_div(completed)
+  }
+
+  fn add(&mut self, other: &OpStats) {
+    self.submitted += other.submitted;
+    self.completed += other.completed;
+    self.failed += other.failed;
+    self.total_latency += other.total_latency;
+    self.max_latency = self.max_latency.max(other.max_latency);
+    #[cfg(feature = "histogram")]
+    for (count, other) in
+      self.latency.counts.iter_mut().zip(other.latency.counts)
+    {
+      *count += other;
+    }
+  }
+}
+
+/// Latency histogram with power of two buckets, starting at 1µs.
+#[cfg(feature = "histogram")]
+#[cfg_attr(docsrs, doc(cfg(feature = "histogram")))]
+#[derive(Debug, Clone)]
+pub struct Histogram {
+  counts: [u64; BUCKETS],
+}
+
+#[cfg(feature = "histogram")]
+impl Histogram {
+  /// Non-cumulative count per bucket, with the bucket's exclusive upper bound.
+  /// The last bucket's bound is `None`, it holds everything slower.
+  pub fn buckets(&self) -> impl Iterator<Item = (Option<Duration>, u64)> + '_ {
+    self.counts.iter().enumerate().map(|(i, count)| {
+      let bound = (i + 1 < BUCKETS).then(|| Duration::from_micros(1 << i));
+      (bound, *count)
+    })
+  }
+}
+
+pub(crate) struct OpCounters {
+  type_name: &'static str,
+  name: &'static str,
+  submitted: AtomicU64,
+  completed: AtomicU64,
+  failed: AtomicU64,
+  total_latency_ns: AtomicU64,
+  max_latency_ns: AtomicU64,
+  #[cfg(feature = "histogram")]
+  latency: [AtomicU64; BUCKETS],
+}
+
+impl OpCounters {
+  fn new(type_name: &'static str, name: &'static str) -> Self {
+    Self {
+      type_name,
+      name,
+      submitted: AtomicU64::new(0),
+      completed: AtomicU64::new(0),
+      failed: AtomicU64::new(0),
+      total_latency_ns: AtomicU64::new(0),
+      max_latency_ns: AtomicU64::new(0),
+      #[cfg(feature = "histogram")]
+      latency: std::array::from_fn(|_| AtomicU64::new(0)),
+    }
+  }
+
+  pub(crate) fn submitted(&self) {
+    self.submitted.fetch_add(1, Ordering::Relaxed);
+  }
+
+  pub(crate) fn completed(&self, submitted_at: Instant, res: &io::Result<i32>) {
+    let latency = submitted_at.elapsed();
+    let nanos = u64::try_from(latency.as_nanos()).unwrap_or(u64::MAX);
+
+    self.completed.fetch_add(1, Ordering::Relaxed);
+    if res.is_err() {
+      self.failed.fetch_add(1, Ordering::Relaxed);
+    }
+    self.total_latency_ns.fetch_add(nanos, Ordering::Relaxed);
+    self.max_latency_ns.fetch_max(nanos, Ordering::Relaxed);
+
+    #[cfg(feature = "histogram")]
+    {
+      let micros = u64::try_from(latency.as_micros()).unwrap_or(u64::MAX);
+      // Bucket i holds latencies below 2^i µs.
+      let bucket = (u64::BITS - micros.leading_zeros()) as usize;
+      self.latency[bucket.min(BUCKETS - 1)].fetch_add(1, Ordering::Relaxed);
+    }
+  }
+
+  fn snapshot(&self) -> OpStats {
+    OpStats {
+      name: self.name,
+      type_name: self.type_name,
+      submitted: self.submitted.load(Ordering::Relaxed),
+      completed: self.completed.load(Ordering::Relaxed),
+      failed: self.failed.load(Ordering::Relaxed),
+      total_latency: Duration::from_nanos(
+        self.total_latency_ns.load(Ordering::Relaxed),
+      ),
+      max_latency: Duration::from_nanos(
+        self.max_latency_ns.load(Ordering::Relaxed),
+      ),
+      #[cfg(feature = "histogram")]
+      latency: Histogram {
+        counts: std::array::from_fn(|i| {
+          self.latency[i].load(Ordering::Relaxed)
+        }),
+      },
+    }
+  }
+}
+
+/// Counters of each operation type, by full type name. Only the first count
+/// of a type takes the lock for writing.
+static COUNTERS: LazyLock<RwLock<HashMap<&'static str, &'static OpCounters>>> =
+  LazyLock::new(Default::default);
+
+/// `lio::op::read::Read<alloc::vec::Vec<u8>>` becomes `Read`.
+pub(crate) fn op_name<T>() -> &'static str {
+  let name = any::type_name::<T>();
+  let name = name.split('<').next().unwrap_or(name);
+  name.rsplit("::").next().unwrap_or(name)
+}
+
+/// Counters for operation type `T`. They are never freed.
+pub(crate) fn counters<T: Operation>() -> &'static OpCounters {
+  let type_name = any::type_name::<T>();
+  if let Some(counters) = COUNTERS.read().get(type_name) {
+    return counters;
+  }
+  COUNTERS.write().entry(type_name).or_insert_with(|| {
+    Box::leak(Box::new(OpCounters::new(type_name, op_name::<T>())))
+  })
+}
+
+pub(crate) fn snapshot() -> Stats {
+  let mut ops: Vec<OpStats> =
+    COUNTERS.read().values().map(|counters| counters.snapshot()).collect();
+  ops.sort_by_key(|op| (op.name, op.type_name));
+
+  let (in_flight, ring) = match Driver::try_get() {
+    Some(driver) => driver.stats(),
+    None => (0, None),
+  };
+
+  Stats { in_flight, ring, ops }
+}
@@ -1,4 +1,55 @@
-use std::ffi::CString;
+// Each test crate uses only some of these.
+#![allow(dead_code)]
+
+use std::{
+  ffi::CString,
+  future::Future,
+  pin::pin,
+  sync::{Mutex, MutexGuard, PoisonError, mpsc},
+  task::{Context, Poll},
+  time::{Duration, Instant},
+};
+
+use futures_task::noop_waker;
+
+/// Held by the test using the driver, which is global, so that the tests of a
+/// crate don't share it while running in parallel.
+static DRIVER: Mutex<()> = Mutex::new(());
+
+/// The calling test's driver, from [`init`]. Dropping it shuts the driver down
+/// and clears injected faults, so nothing one test sets up, like the callback
+/// dispatch or submit policy, leaks into the next.
+pub struct Driver {
+  _lock: MutexGuard<'static, ()>,
+}
+
+impl Drop for Driver {
+  fn drop(&mut self) {
+    #[cfg(feature = "fault_injection")]
+    lio::clear_faults();
+    lio::exit();
+  }
+}
+
+/// Starts the driver for the calling test, once the other tests of the crate
+/// are done with theirs.
+pub fn init() -> Driver {
+  init_with(lio::init)
+}
+
+/// Like [`init`], starting the driver with `start`, like `lio::init_simulated`.
+pub fn init_with(start: impl FnOnce()) -> Driver {
+  let lock = lock();
+  start();
+  Driver { _lock: lock }
+}
+
+/// Waits for the other tests of the crate to be done with the driver, for
+/// tests that start and shut it down themselves.
+pub fn lock() -> MutexGuard<'static, ()> {
+  // The driver of a test that failed was still shut down by its guard.
+  DRIVER.lock().unwrap_or_else(PoisonError::into_inner)
+}
 
 /// Utility function to create a unique temporary file path for proptest tests.
 /// Returns a CString path that includes the thread ID and a unique value to avoid conflicts.
@@ -11,3 +62,54 @@ pub fn make_temp_path(test_name: &str, unique_value: u64) -> CString {
   ))
   .expect("Failed to create CString path")
 }
+
+/// Polls `fut` to completion, ticking the driver in between.
+pub fn block_on<F: Future>(fut: F) -> F::Output {
+  let mut fut = pin!(fut);
+  let waker = noop_waker();
+  loop {
+    if let Poll::Ready(res) =
+      fut.as_mut().poll(&mut Context::from_waker(&waker))
+    {
+      return res;
+    }
+    lio::tick();
+  }
+}
+
+/// Ticks the driver until a callback sends on `receiver`.
+#[cfg(feature = "high")]
+pub fn wait<T>(receiver: oneshot::Receiver<T>) -> T {
+  let start = Instant::now();
+  loop {
+    lio::tick();
+    if let Ok(res) = receiver.try_recv() {
+      return res;
+    }
+    assert!(start.elapsed() < Duration::from_secs(5), "callback didn't run");
+  }
+}
+
+/// Like [`wait`], for callbacks that may send more than once.
+pub fn recv<T>(receiver: &mpsc::Receiver<T>) -> T {
+  let start = Instant::now();
+  loop {
+    if let Ok(value) = receiver.try_recv() {
+      return value;
+    }
+    assert!(start.elapsed() < Duration::from_secs(5), "callback didn't run");
+    lio::tick();
+  }
+}
+
+/// A connected pair of unix stream sockets.
+pub fn socketpair() -> [i32; 2] {
+  let mut fds = [0; 2];
+  unsafe {
+    assert_eq!(
+      libc::socketpair(libc::AF_UNIX, libc::SOCK_STREAM, 0, fds.as_mut_ptr()),
+      0
+    );
+  }
+  fds
+}
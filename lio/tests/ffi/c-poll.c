@@ -0,0 +1,92 @@
+#include <errno.h>
+#include <pthread.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/socket.h>
+#include <unistd.h>
+
+#include <lio.h>
+
+#define CHECK(expr)                                                   \
+    do {                                                              \
+        if (!(expr)) {                                                \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__,    \
+                    __LINE__, #expr);                                 \
+            exit(1);                                                  \
+        }                                                             \
+    } while (0)
+
+static pthread_t main_thread;
+static int callback_result = 1;
+
+static void on_done(void *userdata, int32_t result) {
+    (void)userdata;
+    /* Without a background thread, callbacks run inside lio_poll. */
+    CHECK(pthread_equal(pthread_self(), main_thread));
+    callback_result = result;
+}
+
+int main(void) {
+    main_thread = pthread_self();
+    lio_init_polled();
+
+    LioCompletion completions[4];
+    CHECK(lio_poll(NULL, 4, 0) == -EINVAL);
+    /* Nothing in flight. */
+    CHECK(lio_poll(completions, 4, 0) == 0);
+    CHECK(lio_poll(completions, 4, 10) == 0);
+
+    int fds[2];
+    CHECK(socketpair(AF_UNIX, SOCK_STREAM, 0, fds) == 0);
+
+    uint8_t *buf = malloc(4);
+    memcpy(buf, "ping", 4);
+    lio_send_tagged(fds[0], buf, 4, 0, 1);
+    lio_recv_tagged(fds[1], malloc(16), 16, 0, 2);
+    lio_close_tagged(999, 3);
+
+    int seen = 0;
+    while (seen != 7) {
+        int n = lio_poll(completions, 4, -1);
+        CHECK(n > 0);
+        for (int i = 0; i < n; i++) {
+            LioCompletion *c = &completions[i];
+            switch (c->tag) {
+            case 1:
+                CHECK(c->result == 4);
+                CHECK(c->len == 4);
+                break;
+            case 2:
+                CHECK(c->result == 4);
+                CHECK(memcmp(c->buf, "ping", 4) == 0);
+                break;
+            case 3:
+                CHECK(c->result == -EBADF);
+                CHECK(c->buf == NULL);
+                break;
+            default:
+                CHECK(0);
+            }
+            free(c->buf);
+            seen |= 1 << (c->tag - 1);
+        }
+    }
+
+    /* Callbacks of non-tagged operations run on this thread too. */
+    lio_fsync(fds[0], NULL, on_done);
+#ifdef __linux__
+    lio_timeout_tagged(5, 4);
+#else
+    lio_fsync_tagged(fds[0], 4);
+#endif
+    CHECK(lio_poll(completions, 4, -1) == 1);
+    CHECK(completions[0].tag == 4);
+    CHECK(callback_result != 1);
+
+    close(fds[0]);
+    close(fds[1]);
+    lio_exit();
+    printf("C polling is working\n");
+    return 0;
+}
@@ -1,16 +1,410 @@
+#define _GNU_SOURCE
+
+#include <arpa/inet.h>
+#include <errno.h>
+#include <fcntl.h>
+#include <netinet/in.h>
+#include <stdatomic.h>
 #include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
 #include <sys/socket.h>
+#include <sys/wait.h>
 #include <unistd.h>
 
+#ifdef __linux__
+#include <linux/openat2.h>
+#endif
+
 #include <lio.h>
 
-void test_callback(int32_t result) {
-    printf("Callback received: %d\nC bindings are is working\n", result);
+#define CHECK(expr)                                                   \
+    do {                                                              \
+        if (!(expr)) {                                                \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__,    \
+                    __LINE__, #expr);                                 \
+            exit(1);                                                  \
+        }                                                             \
+    } while (0)
+
+/* One in-flight operation, passed as userdata. */
+struct op {
+    atomic_int done;
+    int32_t result;
+    uint8_t *buf;
+    uintptr_t len;
+    struct sockaddr_storage addr;
+};
+
+static void on_done(void *userdata, int32_t result) {
+    struct op *op = userdata;
+    op->result = result;
+    atomic_store(&op->done, 1);
+}
+
+static void on_buf(void *userdata, int32_t result, uint8_t *buf, uintptr_t len) {
+    struct op *op = userdata;
+    op->result = result;
+    op->buf = buf;
+    op->len = len;
+    atomic_store(&op->done, 1);
+}
+
+static void on_accept(void *userdata, int32_t result, const struct sockaddr_storage *addr) {
+    struct op *op = userdata;
+    op->result = result;
+    if (addr != NULL) {
+        op->addr = *addr;
+        free((void *)addr);
+    }
+    atomic_store(&op->done, 1);
+}
+
+static int32_t wait_op(struct op *op) {
+    while (!atomic_load(&op->done)) {
+        usleep(100);
+    }
+    return op->result;
+}
+
+static uint8_t *copy(const char *data) {
+    size_t len = strlen(data);
+    uint8_t *buf = malloc(len);
+    memcpy(buf, data, len);
+    return buf;
+}
+
+static void test_files(const char *dir) {
+    char path[256];
+    snprintf(path, sizeof(path), "%s/file", dir);
+
+    struct op open_op = {0};
+    lio_openat(AT_FDCWD, path, O_CREAT | O_RDWR, 0644, &open_op, on_done);
+    int fd = wait_op(&open_op);
+    CHECK(fd >= 0);
+
+    struct op write_op = {0};
+    lio_write(fd, copy("hello"), 5, 0, &write_op, on_buf);
+    CHECK(wait_op(&write_op) == 5);
+    CHECK(write_op.len == 5);
+    free(write_op.buf);
+
+    struct op read_op = {0};
+    lio_read(fd, malloc(16), 16, 0, &read_op, on_buf);
+    CHECK(wait_op(&read_op) == 5);
+    CHECK(memcmp(read_op.buf, "hello", 5) == 0);
+    free(read_op.buf);
+
+    struct op write_all_op = {0};
+    lio_write_all(fd, copy(" world"), 6, 5, &write_all_op, on_buf);
+    CHECK(wait_op(&write_all_op) == 6);
+    free(write_all_op.buf);
+
+    struct op read_exact_op = {0};
+    lio_read_exact(fd, malloc(11), 11, 0, &read_exact_op, on_buf);
+    CHECK(wait_op(&read_exact_op) == 11);
+    CHECK(memcmp(read_exact_op.buf, "hello world", 11) == 0);
+    free(read_exact_op.buf);
+
+    /* Only 11 bytes in the file, so this ends early with the bytes read. */
+    struct op short_op = {0};
+    lio_read_exact(fd, malloc(32), 32, 0, &short_op, on_buf);
+    CHECK(wait_op(&short_op) == 11);
+    free(short_op.buf);
+
+    struct op truncate_op = {0};
+    lio_truncate(fd, 5, &truncate_op, on_done);
+    CHECK(wait_op(&truncate_op) == 0);
+    CHECK(lseek(fd, 0, SEEK_END) == 5);
+
+    struct op fsync_op = {0};
+    lio_fsync(fd, &fsync_op, on_done);
+    CHECK(wait_op(&fsync_op) == 0);
+
+    struct op fdatasync_op = {0};
+    lio_fdatasync(fd, &fdatasync_op, on_done);
+    CHECK(wait_op(&fdatasync_op) == 0);
+
+#ifdef __linux__
+    struct op sync_range_op = {0};
+    lio_sync_file_range(fd, 0, 0, SYNC_FILE_RANGE_WRITE, &sync_range_op, on_done);
+    CHECK(wait_op(&sync_range_op) == 0);
+#endif
+
+    struct op close_op = {0};
+    lio_close(fd, &close_op, on_done);
+    CHECK(wait_op(&close_op) == 0);
+
+    /* Errors are negative errno. */
+    struct op bad_close_op = {0};
+    lio_close(fd, &bad_close_op, on_done);
+    CHECK(wait_op(&bad_close_op) == -EBADF);
+
+    char link[256], hard[256], renamed[256];
+    snprintf(link, sizeof(link), "%s/link", dir);
+    snprintf(hard, sizeof(hard), "%s/hard", dir);
+    snprintf(renamed, sizeof(renamed), "%s/renamed", dir);
+
+    struct op symlink_op = {0};
+    lio_symlinkat(AT_FDCWD, path, link, &symlink_op, on_done);
+    CHECK(wait_op(&symlink_op) == 0);
+    char target[256] = {0};
+    CHECK(readlink(link, target, sizeof(target) - 1) > 0);
+    CHECK(strcmp(target, path) == 0);
+
+    struct op link_op = {0};
+    lio_linkat(AT_FDCWD, path, AT_FDCWD, hard, &link_op, on_done);
+    CHECK(wait_op(&link_op) == 0);
+    CHECK(access(hard, F_OK) == 0);
+
+    struct op rename_op = {0};
+    lio_renameat(AT_FDCWD, hard, AT_FDCWD, renamed, &rename_op, on_done);
+    CHECK(wait_op(&rename_op) == 0);
+    CHECK(access(hard, F_OK) != 0);
+    CHECK(access(renamed, F_OK) == 0);
+
+#ifdef __linux__
+    int dir_fd = open(dir, O_DIRECTORY | O_RDONLY);
+    CHECK(dir_fd >= 0);
+
+    struct op openat2_op = {0};
+    lio_openat2(dir_fd, "renamed", O_RDONLY, 0, RESOLVE_BENEATH, &openat2_op, on_done);
+    int fd2 = wait_op(&openat2_op);
+    CHECK(fd2 >= 0);
+    close(fd2);
+
+    struct op escape_op = {0};
+    lio_openat2(dir_fd, "../", O_RDONLY, 0, RESOLVE_BENEATH, &escape_op, on_done);
+    CHECK(wait_op(&escape_op) == -EXDEV);
+    close(dir_fd);
+#endif
+
+    const char *files[] = {path, link, renamed};
+    for (size_t i = 0; i < 3; i++) {
+        struct op unlink_op = {0};
+        lio_unlinkat(AT_FDCWD, files[i], &unlink_op, on_done);
+        CHECK(wait_op(&unlink_op) == 0);
+    }
+    CHECK(access(path, F_OK) != 0);
+}
+
+static void test_sockets(void) {
+    struct op socket_op = {0};
+    lio_socket(AF_INET, SOCK_STREAM, 0, &socket_op, on_done);
+    int listener = wait_op(&socket_op);
+    CHECK(listener >= 0);
+
+    struct sockaddr_in addr = {0};
+    addr.sin_family = AF_INET;
+    addr.sin_addr.s_addr = htonl(INADDR_LOOPBACK);
+    socklen_t addr_len = sizeof(addr);
+
+    struct op bind_op = {0};
+    lio_bind(listener, (struct sockaddr *)&addr, &addr_len, &bind_op, on_done);
+    CHECK(wait_op(&bind_op) == 0);
+    CHECK(getsockname(listener, (struct sockaddr *)&addr, &addr_len) == 0);
+
+    struct op listen_op = {0};
+    lio_listen(listener, 16, &listen_op, on_done);
+    CHECK(wait_op(&listen_op) == 0);
+
+    struct op client_op = {0};
+    lio_socket(AF_INET, SOCK_STREAM, 0, &client_op, on_done);
+    int client = wait_op(&client_op);
+    CHECK(client >= 0);
+
+    struct op accept_op = {0};
+    lio_accept(listener, &accept_op, on_accept);
+
+    struct op connect_op = {0};
+    lio_connect(client, (struct sockaddr *)&addr, addr_len, &connect_op, on_done);
+    CHECK(wait_op(&connect_op) == 0);
+    int server = wait_op(&accept_op);
+    CHECK(server >= 0);
+    CHECK(accept_op.addr.ss_family == AF_INET);
+
+    struct op send_op = {0};
+    lio_send(client, copy("ping"), 4, 0, &send_op, on_buf);
+    CHECK(wait_op(&send_op) == 4);
+    free(send_op.buf);
+
+    struct op recv_op = {0};
+    lio_recv(server, malloc(16), 16, 0, &recv_op, on_buf);
+    CHECK(wait_op(&recv_op) == 4);
+    CHECK(memcmp(recv_op.buf, "ping", 4) == 0);
+    free(recv_op.buf);
+
+    struct op send_all_op = {0};
+    lio_send_all(server, copy("pong!"), 5, &send_all_op, on_buf);
+    CHECK(wait_op(&send_all_op) == 5);
+    free(send_all_op.buf);
+
+    struct op recv_exact_op = {0};
+    lio_recv_exact(client, malloc(5), 5, &recv_exact_op, on_buf);
+    CHECK(wait_op(&recv_exact_op) == 5);
+    CHECK(memcmp(recv_exact_op.buf, "pong!", 5) == 0);
+    free(recv_exact_op.buf);
+
+    struct op shutdown_op = {0};
+    lio_shutdown(client, SHUT_WR, &shutdown_op, on_done);
+    CHECK(wait_op(&shutdown_op) == 0);
+
+    struct op eof_op = {0};
+    lio_recv(server, malloc(16), 16, 0, &eof_op, on_buf);
+    CHECK(wait_op(&eof_op) == 0);
+    free(eof_op.buf);
+
+    /* Only IPv4 and IPv6 are supported. */
+    struct sockaddr unsupported = {0};
+    unsupported.sa_family = AF_UNSPEC;
+    struct op bad_connect_op = {0};
+    lio_connect(client, &unsupported, sizeof(unsupported), &bad_connect_op, on_done);
+    CHECK(wait_op(&bad_connect_op) == -EINVAL);
+
+    close(client);
+    close(server);
+    close(listener);
+}
+
+#ifdef __linux__
+static void test_linux(void) {
+    struct op timeout_op = {0};
+    lio_timeout(10, &timeout_op, on_done);
+    CHECK(wait_op(&timeout_op) == 0);
+
+    int in[2], out[2];
+    CHECK(pipe(in) == 0);
+    CHECK(pipe(out) == 0);
+    CHECK(write(in[1], "tee", 3) == 3);
+
+    struct op tee_op = {0};
+    lio_tee(in[0], out[1], 3, &tee_op, on_done);
+    CHECK(wait_op(&tee_op) == 3);
+    char teed[3];
+    CHECK(read(out[0], teed, 3) == 3);
+    CHECK(memcmp(teed, "tee", 3) == 0);
+    close(in[0]);
+    close(in[1]);
+    close(out[0]);
+    close(out[1]);
+
+    static uint32_t futex = 0;
+
+    struct op mismatch_op = {0};
+    lio_futex_wait(&futex, 1, &mismatch_op, on_done);
+    CHECK(wait_op(&mismatch_op) == 0);
+
+    struct op wait_futex_op = {0};
+    lio_futex_wait(&futex, 0, &wait_futex_op, on_done);
+    /* The wait might not be queued yet, so wake until it is. */
+    while (!atomic_load(&wait_futex_op.done)) {
+        struct op wake_op = {0};
+        lio_futex_wake(&futex, 1, &wake_op, on_done);
+        CHECK(wait_op(&wake_op) >= 0);
+    }
+    CHECK(wait_op(&wait_futex_op) == 0);
+}
+#endif
+
+/* Tagged operations can be mixed with callbacks, and pulled with lio_poll. */
+static void test_tagged(void) {
+    LioCompletion completions[2];
+    CHECK(lio_poll(completions, 2, 0) == 0);
+
+    lio_close_tagged(999, 7);
+    lio_write_tagged(999, copy("data"), 4, 0, 8);
+
+    int seen = 0;
+    while (seen != 3) {
+        int n = lio_poll(completions, 2, 1000);
+        CHECK(n > 0);
+        for (int i = 0; i < n; i++) {
+            LioCompletion *c = &completions[i];
+            CHECK(c->tag == 7 || c->tag == 8);
+            CHECK(c->result == -EBADF);
+            if (c->tag == 8) {
+                CHECK(c->len == 4);
+                CHECK(memcmp(c->buf, "data", 4) == 0);
+            }
+            free(c->buf);
+            seen |= 1 << (c->tag - 7);
+        }
+    }
+}
+
+static void test_cancel(void) {
+    int fds[2];
+    CHECK(socketpair(AF_UNIX, SOCK_STREAM, 0, fds) == 0);
+
+    /* Nothing to receive, so this waits until cancelled. */
+    struct op recv_op = {0};
+    LioHandle recv = lio_recv(fds[1], malloc(16), 16, 0, &recv_op, on_buf);
+    CHECK(recv != 0);
+    CHECK(lio_cancel(recv) == 0);
+    CHECK(wait_op(&recv_op) == -ECANCELED);
+    CHECK(recv_op.len == 16);
+    free(recv_op.buf);
+    CHECK(lio_cancel(recv) == -ENOENT);
+    CHECK(lio_cancel(0) == -ENOENT);
+
+    /* Transfers are cancelled whichever step they are at. */
+    struct op exact_op = {0};
+    LioHandle exact = lio_recv_exact(fds[1], malloc(8), 8, &exact_op, on_buf);
+    CHECK(write(fds[0], "abc", 3) == 3);
+    usleep(10000);
+    CHECK(!atomic_load(&exact_op.done));
+    CHECK(lio_cancel(exact) == 0);
+    CHECK(wait_op(&exact_op) == -ECANCELED);
+    CHECK(memcmp(exact_op.buf, "abc", 3) == 0);
+    free(exact_op.buf);
+
+#ifdef __linux__
+    struct op timeout_op = {0};
+    LioHandle timeout = lio_timeout(60000, &timeout_op, on_done);
+    CHECK(lio_cancel(timeout) == 0);
+    CHECK(wait_op(&timeout_op) == -ECANCELED);
+#endif
+
+    /* Completed operations can't be cancelled. */
+    struct op close_op = {0};
+    LioHandle close_handle = lio_close(fds[0], &close_op, on_done);
+    CHECK(wait_op(&close_op) == 0);
+    CHECK(lio_cancel(close_handle) == -ENOENT);
+    close(fds[1]);
+}
+
+static void test_waitid(void) {
+    pid_t child = fork();
+    CHECK(child >= 0);
+    if (child == 0) {
+        _exit(3);
+    }
+
+    struct op waitid_op = {0};
+    lio_waitid((uint32_t)child, &waitid_op, on_done);
+    int status = wait_op(&waitid_op);
+    CHECK(status >= 0);
+    CHECK(WIFEXITED(status));
+    CHECK(WEXITSTATUS(status) == 3);
 }
 
 int main(void) {
-    lio_close(999, test_callback);
-    sleep(1);
+    lio_init();
+
+    char dir[] = "/tmp/lio_ffi_XXXXXX";
+    CHECK(mkdtemp(dir) != NULL);
+
+    test_files(dir);
+    test_sockets();
+#ifdef __linux__
+    test_linux();
+#endif
+    test_waitid();
+    test_tagged();
+    test_cancel();
+
+    CHECK(rmdir(dir) == 0);
+    lio_exit();
+    printf("C bindings are working\n");
     return 0;
 }
-
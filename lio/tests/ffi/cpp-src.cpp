@@ -1,16 +1,36 @@
 #include <sys/socket.h>
 #include <unistd.h>
+#include <atomic>
+#include <cerrno>
 #include <cstdio>
 
 #include <lio.h>
 
-void test_callback(int32_t result) {
-    printf("Callback received: %d\nC bindings are is working\n", result);
-    (void)result;
+struct Op {
+    std::atomic<bool> done{false};
+    int32_t result = 0;
+};
+
+void test_callback(void *userdata, int32_t result) {
+    auto *op = static_cast<Op *>(userdata);
+    op->result = result;
+    op->done.store(true);
 }
 
 int main() {
-    lio_close(999, test_callback);
-    sleep(1);
+    lio_init();
+
+    Op op;
+    lio_close(999, &op, test_callback);
+    while (!op.done.load()) {
+        usleep(100);
+    }
+    if (op.result != -EBADF) {
+        std::fprintf(stderr, "expected -EBADF, got %d\n", op.result);
+        return 1;
+    }
+
+    lio_exit();
+    std::printf("C++ bindings are working\n");
     return 0;
 }
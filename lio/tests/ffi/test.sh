@@ -12,7 +12,7 @@ make lio-cbuild
 echo "lio C compilation successful"
 
 # Compile C test
-gcc "$SCRIPT_DIR/c-src.c" \
+gcc -Wall -Wextra -Werror "$SCRIPT_DIR/c-src.c" \
     -L"$TARGET_DIR" \
     -Wl,-rpath,"$TARGET_DIR" \
     -o "$TARGET_DIR/test_ffi_c" \
@@ -26,6 +26,18 @@ $TARGET_DIR/test_ffi_c
 
 echo "C test passed"
 
+# Compile and run the polling test, which needs its own process
+gcc -Wall -Wextra -Werror "$SCRIPT_DIR/c-poll.c" \
+    -L"$TARGET_DIR" \
+    -Wl,-rpath,"$TARGET_DIR" \
+    -o "$TARGET_DIR/test_ffi_c_poll" \
+    -llio \
+    -I"$PROJECT_ROOT/lio/include"
+
+$TARGET_DIR/test_ffi_c_poll
+
+echo "C polling test passed"
+
 # Compile C++ test
 g++ "$SCRIPT_DIR/cpp-src.cpp" \
     -L"$TARGET_DIR" \
@@ -3,7 +3,8 @@
 
 #include <lio.h>
 
-void call(int32_t verynice) {
+void call(void *userdata, int32_t verynice) {
+  (void)userdata;
   printf("yay %d", verynice);
 }
 
@@ -11,8 +12,9 @@ int main() {
     printf("Testing lio library via pkg-config\n");
 
     // Initialize lio runtime
+    lio_init();
     // struct lio_runtime *runtime = lio_runtime_new();
-    lio_close(2, call);
+    lio_close(2, NULL, call);
     // if (runtime == NULL) {
     //     fprintf(stderr, "Failed to create lio runtime\n");
     //     return 1;
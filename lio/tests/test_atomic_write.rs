@@ -0,0 +1,100 @@
+#![cfg(feature = "high")]
+mod common;
+
+use common::block_on;
+use lio::atomic_write;
+use std::fs;
+
+#[test]
+fn test_atomic_write_creates_file() {
+  let _driver = common::init();
+  block_on(async {
+    let path = "/tmp/lio_test_atomic_write_create.txt";
+    let _ = fs::remove_file(path);
+
+    atomic_write(path, b"hello atomic".to_vec())
+      .await
+      .expect("Failed to atomically write file");
+
+    assert_eq!(fs::read(path).unwrap(), b"hello atomic");
+    fs::remove_file(path).unwrap();
+  });
+}
+
+#[test]
+fn test_atomic_write_replaces_file() {
+  let _driver = common::init();
+  block_on(async {
+    let path = "/tmp/lio_test_atomic_write_replace.txt";
+    fs::write(path, b"old contents that are longer than the new ones").unwrap();
+
+    atomic_write(path, b"new".to_vec())
+      .await
+      .expect("Failed to atomically replace file");
+
+    assert_eq!(fs::read(path).unwrap(), b"new");
+    fs::remove_file(path).unwrap();
+  });
+}
+
+#[test]
+fn test_atomic_write_large_buffer() {
+  let _driver = common::init();
+  block_on(async {
+    let path = "/tmp/lio_test_atomic_write_large.txt";
+    let data: Vec<u8> = (0..4 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+
+    atomic_write(path, data.clone())
+      .await
+      .expect("Failed to atomically write large file");
+
+    assert_eq!(fs::read(path).unwrap(), data);
+    fs::remove_file(path).unwrap();
+  });
+}
+
+#[test]
+fn test_atomic_write_leaves_no_temp_files() {
+  let _driver = common::init();
+  block_on(async {
+    let dir = "/tmp/lio_test_atomic_write_dir";
+    let _ = fs::remove_dir_all(dir);
+    fs::create_dir(dir).unwrap();
+
+    let path = format!("{dir}/config.toml");
+    atomic_write(&path, b"a = 1".to_vec()).await.unwrap();
+    atomic_write(&path, b"a = 2".to_vec()).await.unwrap();
+
+    let entries: Vec<_> = fs::read_dir(dir)
+      .unwrap()
+      .map(|entry| entry.unwrap().file_name())
+      .collect();
+    assert_eq!(entries, vec!["config.toml"]);
+    assert_eq!(fs::read(&path).unwrap(), b"a = 2");
+
+    fs::remove_dir_all(dir).unwrap();
+  });
+}
+
+#[test]
+fn test_atomic_write_missing_directory() {
+  let _driver = common::init();
+  block_on(async {
+    let result = atomic_write(
+      "/tmp/lio_test_atomic_write_no_such_dir/file.txt",
+      b"data".to_vec(),
+    )
+    .await;
+
+    assert!(result.is_err());
+  });
+}
+
+#[test]
+fn test_atomic_write_no_file_name() {
+  let _driver = common::init();
+  block_on(async {
+    let result = atomic_write("/", b"data".to_vec()).await;
+    assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+  });
+}
@@ -0,0 +1,74 @@
+#![cfg(all(feature = "high", target_os = "linux"))]
+mod common;
+
+use common::wait;
+use lio::SubmitPolicy;
+
+/// Entries pushed, but not yet consumed by the kernel.
+fn pending() -> usize {
+  lio::stats().ring.unwrap().sq_len
+}
+
+fn open_null() -> i32 {
+  unsafe { libc::open(c"/dev/null".as_ptr(), libc::O_WRONLY) }
+}
+
+fn write(fd: i32) -> oneshot::Receiver<lio::BufResult<i32, Vec<u8>>> {
+  lio::write(fd, b"x".to_vec(), 0).get_receiver()
+}
+
+#[test]
+fn test_batch_scope() {
+  let _driver = common::init();
+  let null = open_null();
+  let receivers = lio::batch(|| {
+    let receivers: Vec<_> = (0..4).map(|_| write(null)).collect();
+    // Nested scopes don't submit.
+    lio::batch(|| {
+      drop(write(null));
+    });
+    assert_eq!(pending(), 5);
+    receivers
+  });
+  assert_eq!(pending(), 0);
+  for receiver in receivers {
+    wait(receiver).0.unwrap();
+  }
+  unsafe { libc::close(null) };
+}
+
+#[test]
+fn test_batch_on_tick() {
+  let _driver = common::init();
+  let null = open_null();
+  lio::set_submit_policy(SubmitPolicy::OnTick);
+  let first = write(null);
+  let second = write(null);
+  assert_eq!(pending(), 2);
+  wait(first).0.unwrap();
+  wait(second).0.unwrap();
+  assert_eq!(pending(), 0);
+
+  // Switching back submits what's left.
+  let third = write(null);
+  lio::set_submit_policy(SubmitPolicy::Immediate);
+  assert_eq!(pending(), 0);
+  wait(third).0.unwrap();
+  unsafe { libc::close(null) };
+}
+
+#[test]
+fn test_batch_threshold() {
+  let _driver = common::init();
+  let null = open_null();
+  lio::set_submit_policy(SubmitPolicy::Threshold(3));
+  let receivers: Vec<_> = (0..2).map(|_| write(null)).collect();
+  assert_eq!(pending(), 2);
+  let last = write(null);
+  assert_eq!(pending(), 0);
+  for receiver in receivers {
+    wait(receiver).0.unwrap();
+  }
+  wait(last).0.unwrap();
+  unsafe { libc::close(null) };
+}
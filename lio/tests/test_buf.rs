@@ -0,0 +1,203 @@
+#![cfg(feature = "high")]
+mod common;
+
+use common::block_on;
+use lio::IoBuf;
+use std::ffi::CString;
+
+fn temp_file(name: &str, data: &[u8]) -> (CString, i32) {
+  let path = CString::new(format!("/tmp/lio_test_buf_{}.txt", name)).unwrap();
+  let fd = unsafe {
+    let fd = libc::open(
+      path.as_ptr(),
+      libc::O_CREAT | libc::O_RDWR | libc::O_TRUNC,
+      0o644,
+    );
+    assert!(fd >= 0);
+    libc::write(fd, data.as_ptr() as *const libc::c_void, data.len());
+    fd
+  };
+  (path, fd)
+}
+
+fn cleanup(path: CString, fd: i32) {
+  unsafe {
+    libc::close(fd);
+    libc::unlink(path.as_ptr());
+  }
+}
+
+#[test]
+fn test_buf_read_fills_spare_capacity() {
+  let _driver = common::init();
+  block_on(async {
+    let (path, fd) = temp_file("spare_capacity", b"Hello, World!");
+
+    let buf = Vec::with_capacity(64);
+    let (res, buf) = lio::read(fd, buf, 0).await;
+
+    assert_eq!(res.unwrap(), 13);
+    assert_eq!(buf, b"Hello, World!");
+
+    cleanup(path, fd);
+  });
+}
+
+#[test]
+fn test_buf_read_appends_to_initialized_bytes() {
+  let _driver = common::init();
+  block_on(async {
+    let (path, fd) = temp_file("append", b"ab");
+
+    let mut buf = Vec::with_capacity(8);
+    buf.extend_from_slice(b"hello");
+    let (res, buf) = lio::read(fd, buf, 0).await;
+
+    assert_eq!(res.unwrap(), 2);
+    assert_eq!(buf.len(), 7);
+    assert_eq!(buf, b"helloab");
+
+    cleanup(path, fd);
+  });
+}
+
+#[test]
+fn test_buf_read_without_spare_capacity() {
+  let _driver = common::init();
+  block_on(async {
+    let (path, fd) = temp_file("no_spare", b"abc");
+
+    let (res, buf) = lio::read(fd, vec![b'x'; 8], 0).await;
+
+    assert_eq!(res.unwrap(), 0);
+    assert_eq!(buf, b"xxxxxxxx");
+
+    cleanup(path, fd);
+  });
+}
+
+#[test]
+fn test_buf_boxed_slice_and_array() {
+  let _driver = common::init();
+  block_on(async {
+    let (path, fd) = temp_file("box_array", b"");
+
+    let data: Box<[u8]> = Box::from(&b"boxed"[..]);
+    let (res, _) = lio::write(fd, data, 0).await;
+    assert_eq!(res.unwrap(), 5);
+
+    let (res, _) = lio::write(fd, Box::new(*b" array"), 5).await;
+    assert_eq!(res.unwrap(), 6);
+
+    let (res, buf) = lio::read(fd, Vec::with_capacity(11), 0).await;
+    assert_eq!(res.unwrap(), 11);
+    assert_eq!(buf, b"boxed array");
+
+    cleanup(path, fd);
+  });
+}
+
+#[test]
+fn test_buf_write_slice() {
+  let _driver = common::init();
+  block_on(async {
+    let (path, fd) = temp_file("write_slice", b"");
+
+    let buf = b"Hello, World!".to_vec();
+    let (res, slice) = lio::write(fd, buf.slice(7..12), 0).await;
+    assert_eq!(res.unwrap(), 5);
+    assert_eq!(slice.into_inner(), b"Hello, World!");
+
+    let (res, buf) = lio::read(fd, Vec::with_capacity(16), 0).await;
+    assert_eq!(res.unwrap(), 5);
+    assert_eq!(buf, b"World");
+
+    cleanup(path, fd);
+  });
+}
+
+#[test]
+fn test_buf_read_into_slice() {
+  let _driver = common::init();
+  block_on(async {
+    let (path, fd) = temp_file("read_slice", b"1234");
+
+    // Only the spare part of the view, after "ab", is read into.
+    let mut buf = Vec::with_capacity(8);
+    buf.extend_from_slice(b"ab");
+    let (res, slice) = lio::read(fd, buf.slice(0..4), 0).await;
+    assert_eq!(res.unwrap(), 2);
+    assert_eq!(&slice[..], b"ab12");
+    assert_eq!(slice.into_inner(), b"ab12");
+
+    cleanup(path, fd);
+  });
+}
+
+#[test]
+fn test_buf_read_into_slice_extends_vec() {
+  let _driver = common::init();
+  block_on(async {
+    let (path, fd) = temp_file("slice_extend", b"world");
+
+    let mut buf = Vec::with_capacity(16);
+    buf.extend_from_slice(b"hello ");
+    let (res, slice) = lio::read(fd, buf.slice(6..), 0).await;
+    assert_eq!(res.unwrap(), 5);
+    assert_eq!(slice.into_inner(), b"hello world");
+
+    cleanup(path, fd);
+  });
+}
+
+#[test]
+#[should_panic]
+fn test_slice_out_of_bounds() {
+  let _ = vec![0u8; 4].slice(2..8);
+}
+
+#[test]
+fn test_buf_send_recv_generic_buffers() {
+  let _driver = common::init();
+  block_on(async {
+    let mut fds = [0; 2];
+    unsafe {
+      assert_eq!(
+        libc::socketpair(libc::AF_UNIX, libc::SOCK_STREAM, 0, fds.as_mut_ptr()),
+        0
+      );
+    }
+
+    let (res, _) = lio::send(fds[0], &b"ping"[..], None).await;
+    assert_eq!(res.unwrap(), 4);
+
+    let (res, buf) = lio::recv(fds[1], Vec::with_capacity(32), None).await;
+    assert_eq!(res.unwrap(), 4);
+    assert_eq!(buf, b"ping");
+
+    unsafe {
+      libc::close(fds[0]);
+      libc::close(fds[1]);
+    }
+  });
+}
+
+#[cfg(feature = "bytes")]
+#[test]
+fn test_buf_bytes_mut() {
+  let _driver = common::init();
+  use bytes::{Bytes, BytesMut};
+
+  block_on(async {
+    let (path, fd) = temp_file("bytes_mut", b"");
+
+    let (res, _) = lio::write(fd, Bytes::from_static(b"bytes!"), 0).await;
+    assert_eq!(res.unwrap(), 6);
+
+    let (res, buf) = lio::read(fd, BytesMut::with_capacity(16), 0).await;
+    assert_eq!(res.unwrap(), 6);
+    assert_eq!(&buf[..], b"bytes!");
+
+    cleanup(path, fd);
+  });
+}
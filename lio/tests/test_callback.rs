@@ -29,7 +29,7 @@ fn test_callback_basic_read() {
     // Use callback instead of awaiting
     let (tx, rx) = sync_channel(1);
 
-    let buf = vec![0u8; 100];
+    let buf = Vec::with_capacity(100);
     read(fd, buf, 0).when_done(move |(bytes_read, buffer)| {
       tx.send((bytes_read, buffer)).unwrap();
     });
@@ -103,7 +103,7 @@ fn test_callback_error_handling() {
     let invalid_fd = -1;
     let (tx, rx) = sync_channel(1);
 
-    let buf = vec![0u8; 100];
+    let buf = Vec::with_capacity(100);
     read(invalid_fd, buf, 0).when_done(move |(bytes_read, _buffer)| {
       tx.send(bytes_read).unwrap();
     });
@@ -147,7 +147,7 @@ fn test_callback_concurrent() {
             fd
           };
 
-          let buf = vec![0u8; 100];
+          let buf = Vec::with_capacity(100);
           let expected_data = data.clone();
           let path_clone = path.clone();
 
@@ -206,7 +206,7 @@ fn test_callback_vs_future_mutually_exclusive() {
     };
 
     // Create an operation progress
-    let buf = vec![0u8; 100];
+    let buf = Vec::with_capacity(100);
     let progress = read(fd, buf, 0);
 
     // Use callback - this takes ownership, so future can't be polled
@@ -257,7 +257,7 @@ fn test_callback_with_detach() {
 
     let (tx, rx) = sync_channel(1);
 
-    let buf = vec![0u8; 100];
+    let buf = Vec::with_capacity(100);
     read(fd, buf, 0).when_done(move |_result| {
       // Just mark as invoked, don't care about result
       tx.send(()).unwrap();
@@ -297,7 +297,7 @@ fn test_callback_preserves_buffer_ownership() {
 
     let (tx, rx) = sync_channel(1);
 
-    let buf = vec![0u8; 100];
+    let buf = Vec::with_capacity(100);
     read(fd, buf, 0).when_done(move |(bytes_read, buffer)| {
       // Verify we got ownership of the buffer
       let bytes_read = bytes_read.expect("Read failed") as usize;
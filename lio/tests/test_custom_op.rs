@@ -0,0 +1,116 @@
+#![cfg(target_os = "linux")]
+mod common;
+
+use common::recv;
+use lio::{
+  io_uring::{opcode, squeue, types::Fd},
+  op::{CustomOperation, DetachSafe},
+};
+use std::{
+  io::{self, Write},
+  os::{fd::AsRawFd, fd::RawFd, unix::net::UnixStream},
+  sync::mpsc,
+};
+
+/// A read lio doesn't know about, which owns its buffer.
+struct CustomRead {
+  fd: RawFd,
+  buf: Vec<u8>,
+}
+
+unsafe impl CustomOperation for CustomRead {
+  type Result = io::Result<Vec<u8>>;
+
+  fn result(&mut self, res: io::Result<i32>) -> Self::Result {
+    let len = res? as usize;
+    let mut buf = std::mem::take(&mut self.buf);
+    buf.truncate(len);
+    Ok(buf)
+  }
+
+  const OPCODE: u8 = opcode::Read::CODE;
+
+  fn create_entry(&mut self) -> squeue::Entry {
+    opcode::Read::new(Fd(self.fd), self.buf.as_mut_ptr(), self.buf.len() as u32)
+      .build()
+  }
+}
+
+/// Never runs through io_uring.
+struct Pid;
+
+unsafe impl CustomOperation for Pid {
+  type Result = io::Result<u32>;
+
+  fn result(&mut self, res: io::Result<i32>) -> Self::Result {
+    res.map(|pid| pid as u32)
+  }
+
+  const OPCODE: u8 = u8::MAX;
+
+  fn entry_supported(_probe: &lio::io_uring::Probe) -> bool {
+    false
+  }
+
+  fn create_entry(&mut self) -> squeue::Entry {
+    unreachable!()
+  }
+
+  fn run_blocking(&self) -> io::Result<i32> {
+    Ok(unsafe { libc::getpid() })
+  }
+}
+
+struct Nop;
+
+unsafe impl CustomOperation for Nop {
+  type Result = ();
+
+  fn result(&mut self, res: io::Result<i32>) -> Self::Result {
+    res.unwrap();
+  }
+
+  const OPCODE: u8 = opcode::Nop::CODE;
+
+  fn create_entry(&mut self) -> squeue::Entry {
+    opcode::Nop::new().build()
+  }
+}
+
+unsafe impl DetachSafe for Nop {}
+
+#[test]
+fn test_custom_op_read() {
+  let _driver = common::init();
+  let (sender, receiver) = mpsc::channel();
+
+  let (mut writer, reader) = UnixStream::pair().unwrap();
+  let read = CustomRead { fd: reader.as_raw_fd(), buf: vec![0; 16] };
+  lio::submit(read).when_done(move |res| sender.send(res.unwrap()).unwrap());
+  writer.write_all(b"custom").unwrap();
+  assert_eq!(recv(&receiver), b"custom");
+
+  let stats = lio::stats();
+  assert!(
+    stats.ops.iter().any(|op| op.name == "CustomRead" && op.submitted == 1)
+  );
+}
+
+#[test]
+fn test_custom_op_blocking() {
+  let _driver = common::init();
+
+  let (sender, receiver) = mpsc::channel();
+  lio::submit(Pid).when_done(move |res| sender.send(res.unwrap()).unwrap());
+  assert_eq!(recv(&receiver), std::process::id());
+}
+
+#[test]
+fn test_custom_op_detach() {
+  let _driver = common::init();
+
+  lio::submit(Nop).detach();
+  let (sender, receiver) = mpsc::channel();
+  lio::submit(Nop).when_done(move |()| sender.send(()).unwrap());
+  recv(&receiver);
+}
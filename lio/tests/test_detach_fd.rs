@@ -0,0 +1,79 @@
+#![cfg(feature = "high")]
+mod common;
+
+use futures_task::noop_waker;
+use std::{
+  future::Future,
+  io::{ErrorKind, Read},
+  net::{TcpListener, TcpStream},
+  os::fd::AsRawFd,
+  pin::Pin,
+  task::Context,
+  time::{Duration, Instant},
+};
+
+fn closes() -> u64 {
+  lio::stats().op("Close").map_or(0, |op| op.completed)
+}
+
+/// Ticks until the server side of `client` is closed.
+fn wait_eof(client: &mut TcpStream) {
+  client.set_nonblocking(true).unwrap();
+  let start = Instant::now();
+  loop {
+    lio::tick();
+    match client.read(&mut [0; 16]) {
+      Ok(0) => return,
+      Ok(_) => panic!("unexpected data"),
+      Err(err) if err.kind() == ErrorKind::WouldBlock => {}
+      Err(err) => panic!("{err}"),
+    }
+    assert!(start.elapsed() < Duration::from_secs(5), "accepted fd leaked");
+  }
+}
+
+#[test]
+fn test_accept_detached() {
+  let _driver = common::init();
+  let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+  lio::accept(listener.as_raw_fd()).detach();
+
+  let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+  wait_eof(&mut client);
+}
+
+// Like losing a select against a shutdown signal.
+#[test]
+fn test_accept_dropped() {
+  let _driver = common::init();
+  let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+  let mut accept = lio::accept(listener.as_raw_fd());
+  assert!(
+    Pin::new(&mut accept)
+      .poll(&mut Context::from_waker(&noop_waker()))
+      .is_pending()
+  );
+  drop(accept);
+
+  let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+  wait_eof(&mut client);
+}
+
+/// Ticks until no operations are registered.
+fn settle() {
+  let start = Instant::now();
+  while lio::stats().in_flight > 0 {
+    assert!(start.elapsed() < Duration::from_secs(5), "operations stuck");
+    lio::tick();
+  }
+}
+
+#[test]
+fn test_socket_detached() {
+  let _driver = common::init();
+  settle();
+  let before = closes();
+  lio::socket(socket2::Domain::IPV4, socket2::Type::STREAM, None).detach();
+  settle();
+  assert_eq!(closes(), before + 1);
+}
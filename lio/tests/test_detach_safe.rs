@@ -3,6 +3,7 @@ mod common;
 
 use lio::*;
 use std::ffi::CString;
+use std::net::SocketAddr;
 use std::sync::mpsc::sync_channel;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
@@ -67,7 +68,7 @@ fn test_bind_detach_safe() {
     let sock = unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
     assert!(sock >= 0);
 
-    let addr = "127.0.0.1:0".parse().unwrap();
+    let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
     bind(sock, addr).detach();
 
     std::thread::sleep(Duration::from_millis(50));
@@ -83,7 +84,7 @@ fn test_bind_detach_safe() {
 fn test_bind_when_done() {
   liten::block_on(async {
     let sock = unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
-    let addr = "127.0.0.1:0".parse().unwrap();
+    let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
 
     let (tx, rx) = sync_channel(1);
     bind(sock, addr).when_done(move |result| {
@@ -105,7 +106,7 @@ fn test_connect_detach_safe() {
   liten::block_on(async {
     let listen_sock =
       unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
-    let addr = "127.0.0.1:0".parse().unwrap();
+    let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
     bind(listen_sock, addr).await.unwrap();
     listen(listen_sock, 5).await.unwrap();
 
@@ -120,7 +121,8 @@ fn test_connect_detach_safe() {
       );
     }
     let port = u16::from_be(sockaddr.sin_port);
-    let connect_addr = format!("127.0.0.1:{}", port).parse().unwrap();
+    let connect_addr: SocketAddr =
+      format!("127.0.0.1:{}", port).parse().unwrap();
 
     let client_sock =
       unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
@@ -142,7 +144,7 @@ fn test_connect_when_done() {
   liten::block_on(async {
     let listen_sock =
       unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
-    let addr = "127.0.0.1:0".parse().unwrap();
+    let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
     bind(listen_sock, addr).await.unwrap();
     listen(listen_sock, 5).await.unwrap();
 
@@ -157,7 +159,8 @@ fn test_connect_when_done() {
       );
     }
     let port = u16::from_be(sockaddr.sin_port);
-    let connect_addr = format!("127.0.0.1:{}", port).parse().unwrap();
+    let connect_addr: SocketAddr =
+      format!("127.0.0.1:{}", port).parse().unwrap();
 
     let client_sock =
       unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
@@ -255,7 +258,7 @@ fn test_read_when_done_not_detach_safe() {
     };
 
     let (tx, rx) = sync_channel(1);
-    read(fd, vec![0u8; 100], 0).when_done(move |(result, buf)| {
+    read(fd, Vec::with_capacity(100), 0).when_done(move |(result, buf)| {
       let bytes_read = result.expect("Read should succeed");
       assert_eq!(bytes_read, 5);
       assert_eq!(&buf[..5], b"hello");
@@ -300,14 +303,13 @@ fn test_write_when_done_not_detach_safe() {
   });
 }
 
-/// Test Accept (NOT DetachSafe) with .await instead of callback
-/// Note: Accept with callbacks requires complex async coordination
+/// Test Accept (DetachSafe, closes orphaned connections) with .await
 #[test]
-fn test_accept_with_await_not_detach_safe() {
+fn test_accept_with_await() {
   liten::block_on(async {
     let server_sock =
       unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
-    let addr = "127.0.0.1:0".parse().unwrap();
+    let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
     bind(server_sock, addr).await.unwrap();
     listen(server_sock, 5).await.unwrap();
 
@@ -322,7 +324,8 @@ fn test_accept_with_await_not_detach_safe() {
       );
     }
     let port = u16::from_be(sockaddr.sin_port);
-    let connect_addr = format!("127.0.0.1:{}", port).parse().unwrap();
+    let connect_addr: SocketAddr =
+      format!("127.0.0.1:{}", port).parse().unwrap();
 
     // Connect from client in background
     let client_sock =
@@ -345,7 +348,7 @@ fn test_accept_with_await_not_detach_safe() {
 fn test_listen_when_done_not_detach_safe() {
   liten::block_on(async {
     let sock = unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
-    let addr = "127.0.0.1:0".parse().unwrap();
+    let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
     bind(sock, addr).await.unwrap();
 
     let (tx, rx) = sync_channel(1);
@@ -369,7 +372,7 @@ fn test_recv_with_await_not_detach_safe() {
   liten::block_on(async {
     let server_sock =
       unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
-    let addr = "127.0.0.1:0".parse().unwrap();
+    let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
     bind(server_sock, addr).await.unwrap();
     listen(server_sock, 5).await.unwrap();
 
@@ -384,7 +387,8 @@ fn test_recv_with_await_not_detach_safe() {
       );
     }
     let port = u16::from_be(sockaddr.sin_port);
-    let connect_addr = format!("127.0.0.1:{}", port).parse().unwrap();
+    let connect_addr: SocketAddr =
+      format!("127.0.0.1:{}", port).parse().unwrap();
 
     let client_sock =
       unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
@@ -404,7 +408,8 @@ fn test_recv_with_await_not_detach_safe() {
     }
 
     // Recv with .await
-    let (bytes_received, buf) = recv(accepted_fd, vec![0u8; 100], None).await;
+    let (bytes_received, buf) =
+      recv(accepted_fd, Vec::with_capacity(100), None).await;
     assert_eq!(bytes_received.unwrap(), 5);
     assert_eq!(&buf[..5], b"hello");
 
@@ -423,7 +428,7 @@ fn test_send_with_await_not_detach_safe() {
   liten::block_on(async {
     let server_sock =
       unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
-    let addr = "127.0.0.1:0".parse().unwrap();
+    let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
     bind(server_sock, addr).await.unwrap();
     listen(server_sock, 5).await.unwrap();
 
@@ -438,7 +443,8 @@ fn test_send_with_await_not_detach_safe() {
       );
     }
     let port = u16::from_be(sockaddr.sin_port);
-    let connect_addr = format!("127.0.0.1:{}", port).parse().unwrap();
+    let connect_addr: SocketAddr =
+      format!("127.0.0.1:{}", port).parse().unwrap();
 
     let client_sock =
       unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
@@ -455,9 +461,9 @@ fn test_send_with_await_not_detach_safe() {
   });
 }
 
-/// Test Socket (NOT DetachSafe) with .when_done()
+/// Test Socket (DetachSafe, closes orphaned sockets) with .when_done()
 #[test]
-fn test_socket_when_done_not_detach_safe() {
+fn test_socket_when_done() {
   liten::block_on(async {
     let (tx, rx) = sync_channel(1);
     socket(socket2::Domain::IPV4, socket2::Type::STREAM, None).when_done(
@@ -484,7 +490,7 @@ fn test_openat_when_done_not_detach_safe() {
     openat(
       libc::AT_FDCWD,
       path.clone(),
-      libc::O_CREAT | libc::O_RDWR | libc::O_TRUNC,
+      OpenFlags::CREAT | OpenFlags::RDWR | OpenFlags::TRUNC,
     )
     .when_done(move |result| {
       let fd = result.expect("OpenAt should succeed");
@@ -505,7 +511,7 @@ fn test_shutdown_when_done_not_detach_safe() {
   liten::block_on(async {
     let server_sock =
       unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
-    let addr = "127.0.0.1:0".parse().unwrap();
+    let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
     bind(server_sock, addr).await.unwrap();
     listen(server_sock, 5).await.unwrap();
 
@@ -520,14 +526,15 @@ fn test_shutdown_when_done_not_detach_safe() {
       );
     }
     let port = u16::from_be(sockaddr.sin_port);
-    let connect_addr = format!("127.0.0.1:{}", port).parse().unwrap();
+    let connect_addr: SocketAddr =
+      format!("127.0.0.1:{}", port).parse().unwrap();
 
     let client_sock =
       unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0) };
     connect(client_sock, connect_addr).await.unwrap();
 
     let (tx, rx) = sync_channel(1);
-    shutdown(client_sock, libc::SHUT_WR).when_done(move |result| {
+    shutdown(client_sock, std::net::Shutdown::Write).when_done(move |result| {
       assert!(result.is_ok());
       tx.send(()).unwrap();
     });
@@ -0,0 +1,120 @@
+mod common;
+
+use common::recv;
+use lio::CallbackDispatch;
+use std::{
+  fs::File,
+  os::fd::{AsRawFd, RawFd},
+  sync::{
+    Arc, Mutex,
+    atomic::{AtomicBool, Ordering},
+    mpsc,
+  },
+  thread::{self, Thread},
+  time::{Duration, Instant},
+};
+
+fn open_null() -> File {
+  File::options().write(true).open("/dev/null").unwrap()
+}
+
+/// Writes one byte to `fd`, and sends the thread the callback runs on.
+fn write(fd: RawFd, sender: &mpsc::Sender<Thread>) {
+  let sender = sender.clone();
+  lio::write(fd, vec![0], -1).when_done(move |(res, _)| {
+    res.unwrap();
+    sender.send(thread::current()).unwrap();
+  });
+}
+
+#[test]
+fn test_dispatch_inline() {
+  let _driver = common::init();
+  let null = open_null();
+  let fd = null.as_raw_fd();
+  let (sender, receiver) = mpsc::channel();
+  write(fd, &sender);
+  assert_eq!(recv(&receiver).id(), thread::current().id());
+}
+
+#[test]
+fn test_dispatch_thread_pool() {
+  let _driver = common::init();
+  let null = open_null();
+  let fd = null.as_raw_fd();
+  lio::set_callback_dispatch(CallbackDispatch::ThreadPool(2));
+
+  let (sender, receiver) = mpsc::channel();
+  write(fd, &sender);
+  assert_eq!(recv(&receiver).name(), Some("lio-callback"));
+
+  // A slow callback doesn't hold up the next one.
+  let (unblock, blocked) = mpsc::channel::<()>();
+  lio::write(fd, vec![0], -1).when_done(move |_| blocked.recv().unwrap());
+  write(fd, &sender);
+  assert_eq!(recv(&receiver).name(), Some("lio-callback"));
+  unblock.send(()).unwrap();
+
+  // Callbacks can submit operations.
+  let nested = sender.clone();
+  lio::write(fd, vec![0], -1).when_done(move |_| write(fd, &nested));
+  assert_eq!(recv(&receiver).name(), Some("lio-callback"));
+
+  // Switching back runs the next callbacks on the ticking thread again.
+  lio::set_callback_dispatch(CallbackDispatch::Inline);
+  write(fd, &sender);
+  assert_eq!(recv(&receiver).id(), thread::current().id());
+}
+
+#[test]
+fn test_dispatch_executor() {
+  let _driver = common::init();
+  let null = open_null();
+  let fd = null.as_raw_fd();
+  let jobs = Arc::new(Mutex::new(Vec::<Box<dyn FnOnce() + Send>>::new()));
+  let queue = jobs.clone();
+  lio::set_callback_dispatch(CallbackDispatch::executor(move |job| {
+    queue.lock().unwrap().push(job);
+  }));
+
+  let (sender, receiver) = mpsc::channel();
+  write(fd, &sender);
+  let start = Instant::now();
+  while jobs.lock().unwrap().is_empty() {
+    assert!(start.elapsed() < Duration::from_secs(5), "callback not queued");
+    lio::tick();
+  }
+  assert!(receiver.try_recv().is_err());
+
+  let job = jobs.lock().unwrap().pop().unwrap();
+  thread::Builder::new()
+    .name("executor".into())
+    .spawn(job)
+    .unwrap()
+    .join()
+    .unwrap();
+  assert_eq!(receiver.recv().unwrap().name(), Some("executor"));
+}
+
+/// Exit waits for callbacks queued on the pool.
+#[test]
+fn test_dispatch_exit_waits() {
+  let _lock = common::lock();
+  lio::init();
+  let null = open_null();
+  let fd = null.as_raw_fd();
+  lio::set_callback_dispatch(CallbackDispatch::ThreadPool(1));
+
+  let done = Arc::new(AtomicBool::new(false));
+  let (started, receiver) = mpsc::channel();
+  let flag = done.clone();
+  lio::write(fd, vec![0], -1).when_done(move |_| {
+    started.send(()).unwrap();
+    thread::sleep(Duration::from_millis(50));
+    flag.store(true, Ordering::Release);
+  });
+  recv(&receiver);
+
+  lio::exit();
+  assert!(done.load(Ordering::Acquire));
+}
@@ -0,0 +1,126 @@
+#![cfg(feature = "high")]
+mod common;
+
+use common::{socketpair, wait};
+use std::{ffi::CString, io, sync::mpsc};
+
+#[test]
+fn test_exact_files() {
+  let _driver = common::init();
+  let path = CString::new("/tmp/lio_test_exact.txt").unwrap();
+  let fd = unsafe {
+    libc::open(
+      path.as_ptr(),
+      libc::O_CREAT | libc::O_RDWR | libc::O_TRUNC,
+      0o644,
+    )
+  };
+  assert!(fd >= 0);
+
+  let (res, buf) =
+    wait(lio::write_all(fd, b"Hello, World!".to_vec(), 0).get_receiver());
+  assert_eq!(res.unwrap(), 13);
+  assert_eq!(buf, b"Hello, World!");
+
+  let (res, buf) =
+    wait(lio::read_exact(fd, Vec::with_capacity(5), 7).get_receiver());
+  assert_eq!(res.unwrap(), 5);
+  assert_eq!(buf, b"World");
+
+  // Only 6 bytes left from offset 7.
+  let (res, buf) =
+    wait(lio::read_exact(fd, Vec::with_capacity(10), 7).get_receiver());
+  let err = res.unwrap_err();
+  assert_eq!(err.error.kind(), io::ErrorKind::UnexpectedEof);
+  assert_eq!(err.transferred, 6);
+  assert_eq!(buf, b"World!");
+
+  // Fills only the spare capacity, after what's already in the buffer.
+  let mut buf = Vec::with_capacity(11);
+  buf.extend_from_slice(b"Hello, ");
+  let (res, buf) = wait(lio::read_exact(fd, buf, 7).get_receiver());
+  assert_eq!(res.unwrap(), 4);
+  assert_eq!(buf, b"Hello, Worl");
+
+  let (res, _) = wait(lio::write_all(-1, b"x".to_vec(), 0).get_receiver());
+  let err = res.unwrap_err();
+  assert_eq!(err.error.raw_os_error(), Some(libc::EBADF));
+  assert_eq!(err.transferred, 0);
+
+  unsafe {
+    libc::close(fd);
+    libc::unlink(path.as_ptr());
+  }
+}
+
+#[test]
+fn test_exact_sockets() {
+  let _driver = common::init();
+  let fds = socketpair();
+
+  // Resubmitted as the data trickles in, using callbacks.
+  let (tx, received) = mpsc::channel();
+  lio::recv_exact(fds[1], Vec::with_capacity(8))
+    .when_done(move |res| tx.send(res).unwrap());
+
+  let (res, _) = wait(lio::send(fds[0], b"abc".to_vec(), None).get_receiver());
+  assert_eq!(res.unwrap(), 3);
+  for _ in 0..3 {
+    lio::tick();
+  }
+  assert!(received.try_recv().is_err());
+
+  let (res, _) = wait(lio::send_all(fds[0], b"defgh".to_vec()).get_receiver());
+  assert_eq!(res.unwrap(), 5);
+  let (res, buf) = loop {
+    lio::tick();
+    if let Ok(res) = received.try_recv() {
+      break res;
+    }
+  };
+  assert_eq!(res.unwrap(), 8);
+  assert_eq!(buf, b"abcdefgh");
+
+  // The peer shutting down ends it early.
+  let recv = lio::recv_exact(fds[1], Vec::with_capacity(8)).get_receiver();
+  wait(lio::send_all(fds[0], b"xy".to_vec()).get_receiver()).0.unwrap();
+  unsafe { libc::shutdown(fds[0], libc::SHUT_WR) };
+  let (res, buf) = wait(recv);
+  let err = res.unwrap_err();
+  assert_eq!(err.error.kind(), io::ErrorKind::UnexpectedEof);
+  assert_eq!(err.transferred, 2);
+  assert_eq!(buf, b"xy");
+
+  unsafe {
+    libc::close(fds[0]);
+    libc::close(fds[1]);
+  }
+}
+
+#[cfg(feature = "fault_injection")]
+#[test]
+fn test_exact_short_transfers() {
+  let _driver = common::init();
+  use lio::{Fault, FaultRule, Faults};
+
+  lio::inject_faults(
+    Faults::new()
+      .rule(FaultRule::new(Fault::Error(libc::EINTR)).op("Send").schedule([1]))
+      .rule(FaultRule::new(Fault::Short(3)).op("Send"))
+      .rule(FaultRule::new(Fault::Short(2)).op("Recv")),
+  );
+
+  let fds = socketpair();
+  let (res, _) =
+    wait(lio::send_all(fds[0], b"hello world".to_vec()).get_receiver());
+  assert_eq!(res.unwrap(), 11);
+  let (res, buf) =
+    wait(lio::recv_exact(fds[1], Vec::with_capacity(11)).get_receiver());
+  assert_eq!(res.unwrap(), 11);
+  assert_eq!(buf, b"hello world");
+
+  unsafe {
+    libc::close(fds[0]);
+    libc::close(fds[1]);
+  }
+}
@@ -0,0 +1,101 @@
+#![cfg(feature = "high")]
+mod common;
+
+use common::socketpair;
+use std::{
+  sync::mpsc,
+  time::{Duration, Instant},
+};
+
+fn open_null() -> i32 {
+  unsafe { libc::open(c"/dev/null".as_ptr(), libc::O_WRONLY) }
+}
+
+#[test]
+fn test_exit_drains_completed() {
+  let _lock = common::lock();
+  lio::init();
+
+  let null = open_null();
+  let (tx, written) = mpsc::channel();
+  lio::write(null, b"drained".to_vec(), 0)
+    .when_done(move |(res, _)| tx.send(res.unwrap()).unwrap());
+
+  lio::exit();
+  assert_eq!(written.try_recv().unwrap(), 7);
+
+  unsafe { libc::close(null) };
+}
+
+#[test]
+fn test_exit_cancels_pending() {
+  let _lock = common::lock();
+  lio::init();
+
+  // Never completes on its own, so it has to be cancelled.
+  let fds = socketpair();
+  let (tx, received) = mpsc::channel();
+  lio::recv(fds[0], Vec::with_capacity(16), None).when_done(move |(res, _)| {
+    // Submitting while shutting down completes straight away.
+    let (tx2, during_exit) = mpsc::channel();
+    lio::write(fds[1], b"late".to_vec(), 0)
+      .when_done(move |(res, _)| tx2.send(res).unwrap());
+    tx.send((res, during_exit.try_recv().unwrap())).unwrap();
+  });
+
+  let start = Instant::now();
+  lio::exit_timeout(Duration::from_millis(50));
+  assert!(start.elapsed() < Duration::from_secs(2));
+
+  let (res, late) = received.try_recv().expect("callback wasn't called");
+  assert_eq!(res.unwrap_err().raw_os_error(), Some(libc::ECANCELED));
+  assert_eq!(late.unwrap_err().raw_os_error(), Some(libc::ECANCELED));
+
+  unsafe {
+    libc::close(fds[0]);
+    libc::close(fds[1]);
+  }
+}
+
+#[test]
+fn test_exit_restart() {
+  let _lock = common::lock();
+  let null = open_null();
+
+  for _ in 0..2 {
+    lio::init();
+    let (tx, written) = mpsc::channel();
+    lio::write(null, b"again".to_vec(), 0)
+      .when_done(move |(res, _)| tx.send(res.unwrap()).unwrap());
+    lio::exit();
+    assert_eq!(written.try_recv().unwrap(), 5);
+  }
+
+  unsafe { libc::close(null) };
+}
+
+#[test]
+fn test_exit_from_callback() {
+  let _lock = common::lock();
+  lio::init();
+  let null = open_null();
+
+  // Shutting down from a callback would free the driver under it.
+  let res = std::panic::catch_unwind(|| {
+    lio::write(null, b"inline".to_vec(), 0).when_done(|_| lio::exit());
+    let start = Instant::now();
+    while start.elapsed() < Duration::from_secs(2) {
+      lio::tick();
+    }
+  });
+  assert!(res.is_err());
+
+  // The driver is still usable, and shuts down as usual.
+  let (tx, written) = mpsc::channel();
+  lio::write(null, b"again".to_vec(), 0)
+    .when_done(move |(res, _)| tx.send(res.unwrap()).unwrap());
+  lio::exit();
+  assert_eq!(written.try_recv().unwrap(), 5);
+
+  unsafe { libc::close(null) };
+}
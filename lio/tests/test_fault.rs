@@ -0,0 +1,147 @@
+#![cfg(all(feature = "high", feature = "fault_injection"))]
+mod common;
+
+use common::{socketpair, wait};
+use std::{
+  io,
+  sync::{Arc, Mutex},
+  time::{Duration, Instant},
+};
+
+use lio::{Fault, FaultRule, Faults};
+
+/// Sends all of `data`, retrying on `EINTR`/`EAGAIN` and short sends.
+/// Returns how many sends were submitted.
+fn send_all(fd: i32, data: &[u8]) -> usize {
+  let mut sent = 0;
+  let mut attempts = 0;
+  while sent < data.len() {
+    attempts += 1;
+    let (res, _) =
+      wait(lio::send(fd, data[sent..].to_vec(), None).get_receiver());
+    match res {
+      Ok(n) => sent += n as usize,
+      Err(err)
+        if err.kind() == io::ErrorKind::Interrupted
+          || err.kind() == io::ErrorKind::WouldBlock => {}
+      Err(err) => panic!("{err}"),
+    }
+  }
+  attempts
+}
+
+#[test]
+fn test_fault_errors_and_short_sends() {
+  let _driver = common::init();
+  lio::inject_faults(
+    Faults::new()
+      .rule(FaultRule::new(Fault::Error(libc::EINTR)).op("Send").schedule([0]))
+      .rule(FaultRule::new(Fault::Error(libc::EAGAIN)).op("Send").schedule([1]))
+      .rule(FaultRule::new(Fault::Short(4)).op("Send")),
+  );
+
+  let fds = socketpair();
+  // 2 failed sends, then 3 short ones of at most 4 bytes.
+  assert_eq!(send_all(fds[0], b"hello world"), 5);
+
+  let (res, buf) =
+    wait(lio::recv(fds[1], Vec::with_capacity(64), None).get_receiver());
+  assert_eq!(res.unwrap(), 11);
+  assert_eq!(buf, b"hello world");
+
+  // Other operations are unaffected.
+  let (res, _) = wait(lio::write(fds[0], b"abc".to_vec(), -1).get_receiver());
+  assert_eq!(res.unwrap(), 3);
+
+  unsafe {
+    libc::close(fds[0]);
+    libc::close(fds[1]);
+  }
+}
+
+#[test]
+fn test_fault_short_reads() {
+  let _driver = common::init();
+  lio::inject_faults(
+    Faults::new().rule(FaultRule::new(Fault::Short(2)).op("Read")),
+  );
+
+  let fds = socketpair();
+  unsafe { libc::write(fds[0], b"abcdef".as_ptr().cast(), 6) };
+  let (res, buf) =
+    wait(lio::read(fds[1], Vec::with_capacity(6), -1).get_receiver());
+  assert_eq!(res.unwrap(), 2);
+  assert_eq!(&buf[..2], b"ab");
+
+  unsafe {
+    libc::close(fds[0]);
+    libc::close(fds[1]);
+  }
+}
+
+#[test]
+fn test_fault_probability_is_seeded() {
+  let _driver = common::init();
+  let run = || {
+    lio::inject_faults(Faults::new().seed(7).rule(
+      FaultRule::new(Fault::Error(libc::EAGAIN)).op("Fsync").probability(0.5),
+    ));
+    let failed: Vec<bool> = (0..32)
+      .map(|_| {
+        let res = wait(lio::fsync(libc::STDOUT_FILENO).get_receiver());
+        res.is_err_and(|err| err.raw_os_error() == Some(libc::EAGAIN))
+      })
+      .collect();
+    lio::clear_faults();
+    failed
+  };
+
+  let first = run();
+  assert!(first.contains(&true) && first.contains(&false), "{first:?}");
+  assert_eq!(first, run());
+}
+
+fn open_null() -> i32 {
+  unsafe { libc::open(c"/dev/null".as_ptr(), libc::O_WRONLY) }
+}
+
+#[test]
+fn test_fault_delay() {
+  let _driver = common::init();
+  let null = open_null();
+
+  lio::inject_faults(
+    Faults::new().rule(
+      FaultRule::new(Fault::Delay(Duration::from_millis(50))).op("Write"),
+    ),
+  );
+  let start = Instant::now();
+  let (res, _) = wait(lio::write(null, b"late".to_vec(), 0).get_receiver());
+  assert_eq!(res.unwrap(), 4);
+  assert!(start.elapsed() >= Duration::from_millis(50));
+
+  unsafe { libc::close(null) };
+}
+
+#[test]
+fn test_fault_reorder() {
+  let _driver = common::init();
+  let null = open_null();
+
+  lio::inject_faults(
+    Faults::new()
+      .rule(FaultRule::new(Fault::Reorder).op("Write").schedule([0])),
+  );
+  let order = Arc::new(Mutex::new(Vec::new()));
+  for i in 0..2 {
+    let order = order.clone();
+    lio::write(null, b"x".to_vec(), 0)
+      .when_done(move |_| order.lock().unwrap().push(i));
+  }
+  while order.lock().unwrap().len() < 2 {
+    lio::tick();
+  }
+  assert_eq!(*order.lock().unwrap(), [1, 0]);
+
+  unsafe { libc::close(null) };
+}
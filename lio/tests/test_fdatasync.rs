@@ -0,0 +1,101 @@
+#![cfg(feature = "high")]
+mod common;
+
+use common::block_on;
+use lio::fdatasync;
+use std::ffi::CString;
+
+#[test]
+fn test_fdatasync_basic() {
+  let _driver = common::init();
+  block_on(async {
+    let path = CString::new("/tmp/lio_test_fdatasync_basic.txt").unwrap();
+
+    let fd = unsafe {
+      let fd = libc::open(
+        path.as_ptr(),
+        libc::O_CREAT | libc::O_RDWR | libc::O_TRUNC,
+        0o644,
+      );
+      libc::write(fd, b"durable".as_ptr() as *const libc::c_void, 7);
+      fd
+    };
+
+    fdatasync(fd).await.expect("Failed to fdatasync file");
+
+    unsafe {
+      libc::close(fd);
+      libc::unlink(path.as_ptr());
+    }
+  });
+}
+
+#[test]
+fn test_fdatasync_invalid_fd() {
+  let _driver = common::init();
+  block_on(async {
+    let result = fdatasync(-1).await;
+    assert!(result.is_err());
+  });
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_sync_file_range_basic() {
+  let _driver = common::init();
+  block_on(async {
+    let path = CString::new("/tmp/lio_test_sync_file_range_basic.txt").unwrap();
+
+    let fd = unsafe {
+      let fd = libc::open(
+        path.as_ptr(),
+        libc::O_CREAT | libc::O_RDWR | libc::O_TRUNC,
+        0o644,
+      );
+      libc::write(fd, [7u8; 8192].as_ptr() as *const libc::c_void, 8192);
+      fd
+    };
+
+    let flags = libc::SYNC_FILE_RANGE_WAIT_BEFORE
+      | libc::SYNC_FILE_RANGE_WRITE
+      | libc::SYNC_FILE_RANGE_WAIT_AFTER;
+    lio::sync_file_range(fd, 0, 4096, flags)
+      .await
+      .expect("Failed to sync first range");
+    // nbytes of 0 means until the end of the file.
+    lio::sync_file_range(fd, 4096, 0, flags)
+      .await
+      .expect("Failed to sync rest of file");
+
+    unsafe {
+      libc::close(fd);
+      libc::unlink(path.as_ptr());
+    }
+  });
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_sync_file_range_invalid_flags() {
+  let _driver = common::init();
+  block_on(async {
+    let path =
+      CString::new("/tmp/lio_test_sync_file_range_invalid.txt").unwrap();
+
+    let fd = unsafe {
+      libc::open(
+        path.as_ptr(),
+        libc::O_CREAT | libc::O_RDWR | libc::O_TRUNC,
+        0o644,
+      )
+    };
+
+    let result = lio::sync_file_range(fd, 0, 0, u32::MAX).await;
+    assert!(result.is_err());
+
+    unsafe {
+      libc::close(fd);
+      libc::unlink(path.as_ptr());
+    }
+  });
+}
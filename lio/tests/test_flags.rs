@@ -0,0 +1,79 @@
+mod common;
+
+use lio::{MsgFlags, OpenFlags, OperationProgress, op::Operation};
+use std::{
+  ffi::CString,
+  io::Write,
+  net::Shutdown,
+  os::{fd::AsRawFd, unix::net::UnixStream},
+  sync::mpsc,
+  time::{Duration, Instant},
+};
+
+fn wait<O>(op: OperationProgress<O>) -> O::Result
+where
+  O: Operation + Send + 'static,
+  O::Result: Send,
+{
+  let (sender, receiver) = mpsc::channel();
+  op.when_done(move |res| sender.send(res).unwrap());
+  let start = Instant::now();
+  loop {
+    if let Ok(res) = receiver.try_recv() {
+      return res;
+    }
+    assert!(start.elapsed() < Duration::from_secs(5), "op didn't complete");
+    lio::tick();
+  }
+}
+
+#[test]
+fn test_flags_peek() {
+  let _driver = common::init();
+  let (mut writer, reader) = UnixStream::pair().unwrap();
+  let fd = reader.as_raw_fd();
+  writer.write_all(b"peek").unwrap();
+
+  // A peek leaves the data for the next recv.
+  let (res, buf) =
+    wait(lio::recv(fd, Vec::with_capacity(16), Some(MsgFlags::PEEK)));
+  assert_eq!(res.unwrap(), 4);
+  assert_eq!(buf, b"peek");
+  let (res, buf) = wait(lio::recv(fd, Vec::with_capacity(16), None));
+  assert_eq!(res.unwrap(), 4);
+  assert_eq!(buf, b"peek");
+}
+
+#[test]
+fn test_flags_dontwait() {
+  let _driver = common::init();
+  let (_writer, reader) = UnixStream::pair().unwrap();
+
+  let (res, _) = wait(lio::recv(
+    reader.as_raw_fd(),
+    Vec::with_capacity(16),
+    Some(MsgFlags::DONTWAIT),
+  ));
+  assert_eq!(res.unwrap_err().raw_os_error(), Some(libc::EAGAIN));
+}
+
+#[test]
+fn test_flags_shutdown() {
+  let _driver = common::init();
+  let (writer, reader) = UnixStream::pair().unwrap();
+
+  wait(lio::shutdown(writer.as_raw_fd(), Shutdown::Write)).unwrap();
+  let (res, _) =
+    wait(lio::recv(reader.as_raw_fd(), Vec::with_capacity(16), None));
+  assert_eq!(res.unwrap(), 0);
+}
+
+#[test]
+fn test_flags_open() {
+  let _driver = common::init();
+
+  let path = CString::new(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml"));
+  let flags = OpenFlags::RDONLY | OpenFlags::DIRECTORY;
+  let res = wait(lio::openat(libc::AT_FDCWD, path.unwrap(), flags));
+  assert_eq!(res.unwrap_err().raw_os_error(), Some(libc::ENOTDIR));
+}
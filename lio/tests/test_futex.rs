@@ -0,0 +1,118 @@
+#![cfg(all(feature = "high", target_os = "linux"))]
+mod common;
+
+use common::{block_on, recv};
+use lio::{futex_wait, futex_wake};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::mpsc::channel;
+
+#[test]
+fn test_futex_wait_value_mismatch() {
+  let _driver = common::init();
+  static FUTEX: AtomicU32 = AtomicU32::new(1);
+
+  // Futex doesn't contain the expected value, so this returns right away.
+  block_on(unsafe { futex_wait(FUTEX.as_ptr(), 0) })
+    .expect("futex_wait failed");
+}
+
+#[test]
+fn test_futex_wake_no_waiters() {
+  let _driver = common::init();
+  static FUTEX: AtomicU32 = AtomicU32::new(0);
+
+  let woken = block_on(unsafe { futex_wake(FUTEX.as_ptr(), 1) })
+    .expect("futex_wake failed");
+  assert_eq!(woken, 0);
+}
+
+#[test]
+fn test_futex_wait_woken() {
+  let _driver = common::init();
+  static FUTEX: AtomicU32 = AtomicU32::new(0);
+
+  let (tx, rx) = channel();
+  unsafe { futex_wait(FUTEX.as_ptr(), 0) }.when_done(move |res| {
+    tx.send(res).unwrap();
+  });
+
+  // Nobody has woken the waiter yet.
+  for _ in 0..10 {
+    lio::tick();
+  }
+  assert!(rx.try_recv().is_err());
+
+  FUTEX.store(1, Ordering::Release);
+  let woken = block_on(unsafe { futex_wake(FUTEX.as_ptr(), u32::MAX) })
+    .expect("futex_wake failed");
+  assert_eq!(woken, 1);
+
+  recv(&rx).expect("futex_wait failed");
+}
+
+#[test]
+fn test_futex_wake_count() {
+  let _driver = common::init();
+  static FUTEX: AtomicU32 = AtomicU32::new(0);
+
+  let (tx, rx) = channel();
+  for _ in 0..3 {
+    let tx = tx.clone();
+    unsafe { futex_wait(FUTEX.as_ptr(), 0) }.when_done(move |res| {
+      tx.send(res).unwrap();
+    });
+  }
+  // Submits the waits, so they are queued before waking.
+  lio::tick();
+
+  let woken = block_on(unsafe { futex_wake(FUTEX.as_ptr(), 2) })
+    .expect("futex_wake failed");
+  assert_eq!(woken, 2);
+
+  for _ in 0..2 {
+    recv(&rx).unwrap();
+  }
+  for _ in 0..10 {
+    lio::tick();
+  }
+  assert!(rx.try_recv().is_err());
+
+  let woken = block_on(unsafe { futex_wake(FUTEX.as_ptr(), u32::MAX) })
+    .expect("futex_wake failed");
+  assert_eq!(woken, 1);
+  recv(&rx).unwrap();
+}
+
+/// Kernels without futex ops wait on a helper thread, which mustn't block ticking.
+#[cfg(feature = "fault_injection")]
+#[test]
+fn test_futex_wait_woken_fallback() {
+  let _driver = common::init();
+  use lio::{Fault, FaultRule, Faults};
+
+  static FUTEX: AtomicU32 = AtomicU32::new(0);
+
+  lio::inject_faults(
+    Faults::new().rule(FaultRule::new(Fault::Unsupported).op("FutexWait")),
+  );
+  block_on(unsafe { futex_wait(FUTEX.as_ptr(), 1) })
+    .expect("futex_wait failed");
+
+  let (tx, rx) = channel();
+  unsafe { futex_wait(FUTEX.as_ptr(), 0) }.when_done(move |res| {
+    tx.send(res).unwrap();
+  });
+  lio::clear_faults();
+
+  for _ in 0..10 {
+    lio::tick();
+  }
+  assert!(rx.try_recv().is_err());
+
+  // The helper may not be waiting yet, then it sees the new value instead.
+  FUTEX.store(1, Ordering::Release);
+  block_on(unsafe { futex_wake(FUTEX.as_ptr(), u32::MAX) })
+    .expect("futex_wake failed");
+
+  recv(&rx).expect("futex_wait failed");
+}
@@ -0,0 +1,141 @@
+#![cfg(feature = "high")]
+mod common;
+
+use common::block_on;
+use futures_task::noop_waker;
+use lio::{BufResult, OpSet};
+use std::{
+  io::Write,
+  os::{fd::AsRawFd, unix::net::UnixStream},
+  sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+  },
+  task::{Context, Poll, Wake, Waker},
+  time::{Duration, Instant},
+};
+
+struct Flag(AtomicBool);
+
+impl Wake for Flag {
+  fn wake(self: Arc<Self>) {
+    self.0.store(true, Ordering::Release);
+  }
+}
+
+/// Results come out in the order the operations complete.
+#[test]
+fn test_op_set_completion_order() {
+  let _driver = common::init();
+  let pairs: Vec<_> = (0..8).map(|_| UnixStream::pair().unwrap()).collect();
+  let mut set: OpSet<BufResult<i32, Vec<u8>>> = pairs
+    .iter()
+    .map(|(_, reader)| {
+      lio::recv(reader.as_raw_fd(), Vec::with_capacity(8), None)
+    })
+    .collect();
+  assert_eq!(set.len(), 8);
+
+  for (i, (writer, _)) in pairs.iter().enumerate().rev() {
+    (&*writer).write_all(&[i as u8]).unwrap();
+    let (res, buf) = block_on(set.next()).unwrap();
+    assert_eq!(res.unwrap(), 1);
+    assert_eq!(buf, [i as u8]);
+  }
+  assert!(set.is_empty());
+  assert!(block_on(set.next()).is_none());
+}
+
+/// A completing operation wakes the task waiting on the set.
+#[test]
+fn test_op_set_wakes() {
+  let _driver = common::init();
+  let (mut writer, reader) = UnixStream::pair().unwrap();
+  let mut set = OpSet::new();
+  set.push(lio::recv(reader.as_raw_fd(), Vec::with_capacity(8), None));
+
+  let flag = Arc::new(Flag(AtomicBool::new(false)));
+  let waker = Waker::from(flag.clone());
+  let mut cx = Context::from_waker(&waker);
+  assert!(set.poll_next(&mut cx).is_pending());
+
+  writer.write_all(b"wake").unwrap();
+  let start = Instant::now();
+  while !flag.0.load(Ordering::Acquire) {
+    assert!(start.elapsed() < Duration::from_secs(5), "set wasn't woken");
+    lio::tick();
+  }
+
+  let Poll::Ready(Some((res, buf))) = set.poll_next(&mut cx) else {
+    panic!("woken set has no result");
+  };
+  assert_eq!(res.unwrap(), 4);
+  assert_eq!(buf, b"wake");
+}
+
+/// A poll stops after a budget of members, and wakes the task for the rest.
+#[test]
+fn test_op_set_budget() {
+  let _driver = common::init();
+  let pairs: Vec<_> = (0..40).map(|_| UnixStream::pair().unwrap()).collect();
+  let mut set: OpSet<BufResult<i32, Vec<u8>>> = pairs
+    .iter()
+    .map(|(_, reader)| {
+      lio::recv(reader.as_raw_fd(), Vec::with_capacity(8), None)
+    })
+    .collect();
+
+  let flag = Arc::new(Flag(AtomicBool::new(false)));
+  let waker = Waker::from(flag.clone());
+  let mut cx = Context::from_waker(&waker);
+  assert!(set.poll_next(&mut cx).is_pending());
+  assert!(flag.0.swap(false, Ordering::AcqRel), "rest wasn't scheduled");
+
+  // Only the members left over are polled, nothing is left after them.
+  assert!(set.poll_next(&mut cx).is_pending());
+  assert!(!flag.0.load(Ordering::Acquire));
+  assert_eq!(set.len(), 40);
+}
+
+enum Done {
+  Recv(BufResult<i32, Vec<u8>>),
+  Listen(std::io::Result<()>),
+}
+
+/// Operations of different types share a set through an enum.
+#[test]
+fn test_op_set_mixed() {
+  let _driver = common::init();
+  let (writer, reader) = UnixStream::pair().unwrap();
+  let mut set = OpSet::new();
+  set.push_map(
+    lio::recv(reader.as_raw_fd(), Vec::with_capacity(8), None),
+    Done::Recv,
+  );
+  set.push_map(lio::listen(writer.as_raw_fd(), 1), Done::Listen);
+
+  // `listen` on a connected unix socket fails right away.
+  let Done::Listen(res) = block_on(set.next()).unwrap() else {
+    panic!("recv completed before anything was sent");
+  };
+  assert!(res.is_err());
+
+  (&writer).write_all(b"mixed").unwrap();
+  let Done::Recv((res, buf)) = block_on(set.next()).unwrap() else {
+    panic!("expected the recv");
+  };
+  assert_eq!(res.unwrap(), 5);
+  assert_eq!(buf, b"mixed");
+}
+
+/// Operations still in a dropped set are detached.
+#[test]
+fn test_op_set_drop_pending() {
+  let _driver = common::init();
+  let (_writer, reader) = UnixStream::pair().unwrap();
+  let mut set: OpSet<BufResult<i32, Vec<u8>>> = OpSet::new();
+  set.push(lio::recv(reader.as_raw_fd(), Vec::with_capacity(8), None));
+  let waker = noop_waker();
+  assert!(set.poll_next(&mut Context::from_waker(&waker)).is_pending());
+  drop(set);
+}
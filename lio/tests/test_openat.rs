@@ -1,5 +1,5 @@
 #![cfg(feature = "high")]
-use lio::openat;
+use lio::{OpenFlags, openat};
 use std::ffi::CString;
 
 #[test]
@@ -12,7 +12,10 @@ fn test_openat_create_file() {
     let fd = openat(
       libc::AT_FDCWD,
       path.clone(),
-      libc::O_CREAT | libc::O_WRONLY | libc::O_TRUNC | libc::O_RDONLY,
+      OpenFlags::CREAT
+        | OpenFlags::WRONLY
+        | OpenFlags::TRUNC
+        | OpenFlags::RDONLY,
     )
     .await
     .expect("Failed to create file");
@@ -37,14 +40,14 @@ fn test_openat_read_only() {
     let fd_create = openat(
       libc::AT_FDCWD,
       path.clone(),
-      libc::O_CREAT | libc::O_WRONLY | libc::O_TRUNC,
+      OpenFlags::CREAT | OpenFlags::WRONLY | OpenFlags::TRUNC,
     )
     .await
     .expect("Failed to create file");
     unsafe { libc::close(fd_create) };
 
     // Open for reading
-    let fd = openat(libc::AT_FDCWD, path.clone(), libc::O_RDONLY)
+    let fd = openat(libc::AT_FDCWD, path.clone(), OpenFlags::RDONLY)
       .await
       .expect("Failed to open file for reading");
 
@@ -67,7 +70,7 @@ fn test_openat_read_write() {
     let fd = openat(
       libc::AT_FDCWD,
       path.clone(),
-      libc::O_CREAT | libc::O_RDWR | libc::O_TRUNC,
+      OpenFlags::CREAT | OpenFlags::RDWR | OpenFlags::TRUNC,
     )
     .await
     .expect("Failed to open file for read/write");
@@ -89,7 +92,7 @@ fn test_openat_nonexistent_file() {
       CString::new("/tmp/lio_test_nonexistent_file_12345.txt").unwrap();
 
     // Try to open non-existent file without O_CREAT
-    let result = openat(libc::AT_FDCWD, path, libc::O_RDONLY).await;
+    let result = openat(libc::AT_FDCWD, path, OpenFlags::RDONLY).await;
 
     assert!(result.is_err(), "Should fail to open non-existent file");
   });
@@ -110,7 +113,7 @@ fn test_openat_with_directory_fd() {
     let fd = openat(
       dir_fd,
       file_path.clone(),
-      libc::O_CREAT | libc::O_RDWR | libc::O_TRUNC,
+      OpenFlags::CREAT | OpenFlags::RDWR | OpenFlags::TRUNC,
     )
     .await
     .expect("Failed to open file with directory fd");
@@ -140,7 +143,7 @@ fn test_openat_concurrent() {
       let fd = openat(
         libc::AT_FDCWD,
         path.clone(),
-        libc::O_CREAT | libc::O_RDWR | libc::O_TRUNC,
+        OpenFlags::CREAT | OpenFlags::RDWR | OpenFlags::TRUNC,
       )
       .await
       .expect("Failed to open file");
@@ -0,0 +1,148 @@
+#![cfg(all(feature = "high", target_os = "linux"))]
+mod common;
+
+use common::block_on;
+use lio::op::{OpenHow, Resolve};
+use lio::{OpenFlags, openat2};
+use std::ffi::CString;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+
+fn open_dir(path: &str) -> i32 {
+  let _ = fs::remove_dir_all(path);
+  fs::create_dir_all(format!("{path}/sub")).unwrap();
+  let path = CString::new(path).unwrap();
+  unsafe { libc::open(path.as_ptr(), libc::O_RDONLY | libc::O_DIRECTORY) }
+}
+
+#[test]
+fn test_openat2_create_with_mode() {
+  let _driver = common::init();
+  block_on(async {
+    let dir = "/tmp/lio_test_openat2_mode";
+    let dir_fd = open_dir(dir);
+
+    let how = OpenHow::new(OpenFlags::CREAT | OpenFlags::WRONLY).mode(0o600);
+    let fd = openat2(dir_fd, "file.txt", how)
+      .unwrap()
+      .await
+      .expect("Failed to create file");
+    assert!(fd >= 0);
+
+    let mode = fs::metadata(format!("{dir}/file.txt")).unwrap().permissions();
+    assert_eq!(mode.mode() & 0o777, 0o600);
+
+    unsafe {
+      libc::close(fd);
+      libc::close(dir_fd);
+    }
+    fs::remove_dir_all(dir).unwrap();
+  });
+}
+
+#[test]
+fn test_openat2_beneath_allows_subpath() {
+  let _driver = common::init();
+  block_on(async {
+    let dir = "/tmp/lio_test_openat2_beneath_ok";
+    let dir_fd = open_dir(dir);
+    fs::write(format!("{dir}/sub/file.txt"), b"data").unwrap();
+
+    let how = OpenHow::new(OpenFlags::RDONLY).resolve(Resolve::BENEATH);
+    let fd = openat2(dir_fd, "sub/../sub/file.txt", how)
+      .unwrap()
+      .await
+      .expect("Failed to open file beneath directory");
+
+    unsafe {
+      libc::close(fd);
+      libc::close(dir_fd);
+    }
+    fs::remove_dir_all(dir).unwrap();
+  });
+}
+
+#[test]
+fn test_openat2_beneath_rejects_escape() {
+  let _driver = common::init();
+  block_on(async {
+    let dir = "/tmp/lio_test_openat2_beneath_escape";
+    let dir_fd = open_dir(dir);
+
+    let how = OpenHow::new(OpenFlags::RDONLY).resolve(Resolve::BENEATH);
+    let parent = openat2(dir_fd, "../", how).unwrap().await;
+    assert!(parent.is_err());
+
+    let absolute = openat2(dir_fd, "/etc/hostname", how).unwrap().await;
+    assert!(absolute.is_err());
+
+    unsafe { libc::close(dir_fd) };
+    fs::remove_dir_all(dir).unwrap();
+  });
+}
+
+#[test]
+fn test_openat2_no_symlinks() {
+  let _driver = common::init();
+  block_on(async {
+    let dir = "/tmp/lio_test_openat2_no_symlinks";
+    let dir_fd = open_dir(dir);
+    fs::write(format!("{dir}/target.txt"), b"data").unwrap();
+    std::os::unix::fs::symlink("target.txt", format!("{dir}/link.txt"))
+      .unwrap();
+
+    let plain = OpenHow::new(OpenFlags::RDONLY);
+    let fd = openat2(dir_fd, "link.txt", plain).unwrap().await.unwrap();
+    unsafe { libc::close(fd) };
+
+    let how = plain.resolve(Resolve::NO_SYMLINKS);
+    let result = openat2(dir_fd, "link.txt", how).unwrap().await;
+    assert!(result.is_err());
+
+    unsafe { libc::close(dir_fd) };
+    fs::remove_dir_all(dir).unwrap();
+  });
+}
+
+#[test]
+fn test_openat2_in_root() {
+  let _driver = common::init();
+  block_on(async {
+    let dir = "/tmp/lio_test_openat2_in_root";
+    let dir_fd = open_dir(dir);
+    fs::write(format!("{dir}/sub/file.txt"), b"data").unwrap();
+
+    // Absolute paths and ".." are resolved as if `dir` was "/".
+    let how = OpenHow::new(OpenFlags::RDONLY).resolve(Resolve::IN_ROOT);
+    let fd = openat2(dir_fd, "/../../sub/file.txt", how)
+      .unwrap()
+      .await
+      .expect("Failed to open file in root");
+
+    let mut buf = [0u8; 4];
+    let n = unsafe { libc::read(fd, buf.as_mut_ptr().cast(), 4) };
+    assert_eq!(n, 4);
+    assert_eq!(&buf, b"data");
+
+    unsafe {
+      libc::close(fd);
+      libc::close(dir_fd);
+    }
+    fs::remove_dir_all(dir).unwrap();
+  });
+}
+
+#[test]
+fn test_openat2_nul_in_path() {
+  let result = openat2(libc::AT_FDCWD, "a\0b", OpenHow::new(OpenFlags::RDONLY));
+  assert!(result.is_err());
+}
+
+#[test]
+fn test_openat2_resolve_flags() {
+  let flags = Resolve::BENEATH | Resolve::NO_SYMLINKS;
+  assert!(flags.contains(Resolve::BENEATH));
+  assert!(flags.contains(Resolve::NO_SYMLINKS));
+  assert!(!flags.contains(Resolve::IN_ROOT));
+  assert_eq!(flags.bits(), libc::RESOLVE_BENEATH | libc::RESOLVE_NO_SYMLINKS);
+}
@@ -0,0 +1,154 @@
+#![cfg(feature = "high")]
+mod common;
+
+use common::{block_on, socketpair};
+use futures_task::noop_waker;
+use lio::{fs::File, net::Socket};
+use socket2::{Domain, Type};
+use std::{
+  future::Future,
+  net::SocketAddr,
+  os::fd::{AsFd, AsRawFd, FromRawFd, RawFd},
+  task::{Context, Poll},
+  time::{Duration, Instant},
+};
+
+fn is_open(fd: RawFd) -> bool {
+  unsafe { libc::fcntl(fd, libc::F_GETFD) != -1 }
+}
+
+fn wait_closed(fd: RawFd) {
+  let start = Instant::now();
+  while is_open(fd) {
+    assert!(start.elapsed() < Duration::from_secs(1), "fd {fd} not closed");
+    lio::tick();
+  }
+}
+
+#[test]
+fn test_owned_file() {
+  let _driver = common::init();
+  let path = "/tmp/lio_test_owned_file.txt";
+
+  let file = block_on(File::create(path)).unwrap();
+  let (res, _buf) = block_on(file.write_all_at(b"owned file".to_vec(), 0));
+  assert_eq!(res.unwrap(), 10);
+  block_on(file.sync_data()).unwrap();
+  block_on(file.close()).unwrap();
+
+  let file = block_on(File::open(path)).unwrap();
+  let (res, buf) = block_on(file.read_exact_at(Vec::with_capacity(10), 0));
+  res.unwrap();
+  assert_eq!(buf, b"owned file");
+
+  let fd = file.as_raw_fd();
+  assert!(is_open(fd));
+  drop(file);
+  wait_closed(fd);
+
+  std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_owned_socket() {
+  let _driver = common::init();
+  let listener =
+    block_on(Socket::new(Domain::IPV4, Type::STREAM, None)).unwrap();
+  block_on(listener.bind("127.0.0.1:0".parse::<SocketAddr>().unwrap()))
+    .unwrap();
+  block_on(listener.listen(16)).unwrap();
+  let addr = block_on(listener.local_addr()).unwrap();
+
+  let client = block_on(Socket::new(Domain::IPV4, Type::STREAM, None)).unwrap();
+  block_on(client.connect(addr)).unwrap();
+  let (conn, _) = block_on(listener.accept()).unwrap();
+
+  let (res, _) = block_on(client.send_all(b"ping".to_vec()));
+  res.unwrap();
+  let (res, buf) = block_on(conn.recv_exact(Vec::with_capacity(4)));
+  res.unwrap();
+  assert_eq!(buf, b"ping");
+
+  let fds = [listener.as_raw_fd(), client.as_raw_fd(), conn.as_raw_fd()];
+  drop((listener, client, conn));
+  fds.into_iter().for_each(wait_closed);
+}
+
+#[test]
+fn test_owned_borrowed() {
+  let _driver = common::init();
+  let path = "/tmp/lio_test_owned_borrowed.txt";
+
+  let std_file = std::fs::File::create(path).unwrap();
+  let (res, _buf) = block_on(lio::write(&std_file, b"borrowed".to_vec(), 0));
+  assert_eq!(res.unwrap(), 8);
+  block_on(lio::fsync(std_file.as_fd())).unwrap();
+
+  let file = block_on(File::open(path)).unwrap();
+  let (res, buf) = block_on(lio::read(&file, Vec::with_capacity(8), 0));
+  assert_eq!(res.unwrap(), 8);
+  assert_eq!(buf, b"borrowed");
+
+  std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_owned_dropped_future() {
+  let _driver = common::init();
+  let [ours, theirs] = socketpair();
+  let socket = unsafe { Socket::from_raw_fd(ours) };
+
+  // Nothing to receive yet, so the recv is still running when dropped.
+  let mut recv = Box::pin(socket.recv(Vec::with_capacity(4), None));
+  let waker = noop_waker();
+  let poll = recv.as_mut().poll(&mut Context::from_waker(&waker));
+  assert!(matches!(poll, Poll::Pending));
+  drop(recv);
+  drop(socket);
+
+  for _ in 0..10 {
+    lio::tick();
+  }
+  assert!(is_open(ours), "closed under the running recv");
+
+  assert_eq!(unsafe { libc::write(theirs, b"ping".as_ptr().cast(), 4) }, 4);
+  wait_closed(ours);
+  unsafe { libc::close(theirs) };
+}
+
+#[test]
+fn test_owned_try_into_owned() {
+  let _driver = common::init();
+  let [ours, theirs] = socketpair();
+  let socket = unsafe { Socket::from_raw_fd(ours) };
+
+  let mut recv = Box::pin(socket.recv(Vec::with_capacity(4), None));
+  let waker = noop_waker();
+  let poll = recv.as_mut().poll(&mut Context::from_waker(&waker));
+  assert!(matches!(poll, Poll::Pending));
+  drop(recv);
+
+  // The dropped recv still uses the descriptor, so it isn't handed out.
+  let mut socket = socket.try_into_owned().unwrap_err();
+  assert_eq!(socket.as_raw_fd(), ours);
+
+  assert_eq!(unsafe { libc::write(theirs, b"ping".as_ptr().cast(), 4) }, 4);
+  let start = Instant::now();
+  let fd = loop {
+    match socket.try_into_owned() {
+      Ok(fd) => break fd,
+      Err(back) => socket = back,
+    }
+    assert!(start.elapsed() < Duration::from_secs(1), "recv didn't complete");
+    lio::tick();
+  };
+  assert_eq!(fd.as_raw_fd(), ours);
+
+  // Not closed by the socket anymore.
+  for _ in 0..10 {
+    lio::tick();
+  }
+  assert!(is_open(ours));
+  drop(fd);
+  unsafe { libc::close(theirs) };
+}
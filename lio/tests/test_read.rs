@@ -28,7 +28,7 @@ fn test_read_large_buffer() {
     };
 
     // Read it back
-    let buf = vec![0u8; 1024 * 1024];
+    let buf = Vec::with_capacity(1024 * 1024);
     let (bytes_read, result) = read(fd, buf, 0).await;
     let bytes_read = bytes_read.expect("Failed to read large buffer") as usize;
 
@@ -64,7 +64,7 @@ fn test_read_concurrent() {
           fd
         };
 
-        let buf = vec![0u8; 100];
+        let buf = Vec::with_capacity(100);
         let (bytes_read, result) = read(fd, buf, 0).await;
         let bytes_read = bytes_read.expect("Failed to read") as usize;
 
@@ -121,7 +121,7 @@ proptest! {
       };
 
       // Perform the read operation
-      let buf = vec![0u8; buffer_size];
+      let buf = Vec::with_capacity(buffer_size);
       let (bytes_read_result, result_buf) = lio::read(fd, buf, read_offset).await;
 
       let test_result = (|| -> Result<(), TestCaseError> {
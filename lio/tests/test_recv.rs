@@ -1,5 +1,5 @@
 #![cfg(feature = "high")]
-use lio::{accept, bind, connect, listen, recv, send, socket};
+use lio::{MsgFlags, accept, bind, connect, listen, recv, send, socket};
 use proptest::prelude::*;
 use socket2::{Domain, Protocol, Type};
 use std::mem::MaybeUninit;
@@ -38,7 +38,7 @@ fn test_recv_multiple() {
       // Receive until EOF
       let mut all_data = Vec::new();
       loop {
-        let buf = vec![0u8; 1024];
+        let buf = Vec::with_capacity(1024);
         let (bytes_received, received_buf) = recv(client_fd, buf, None).await;
         let bytes_received =
           bytes_received.expect("Failed to receive") as usize;
@@ -119,8 +119,9 @@ fn test_recv_with_flags() {
       let (client_fd, _client_addr) =
         accept(server_sock).await.expect("Failed to accept");
 
-      let buf = vec![0u8; 1024];
-      let (bytes_received, received_buf) = recv(client_fd, buf, Some(0)).await;
+      let buf = Vec::with_capacity(1024);
+      let (bytes_received, received_buf) =
+        recv(client_fd, buf, Some(MsgFlags::NONE)).await;
       let bytes_received =
         bytes_received.expect("Failed to receive with flags");
 
@@ -202,7 +203,7 @@ fn test_recv_on_closed() {
     }
 
     // Try to receive on closed connection
-    let buf = vec![0u8; 1024];
+    let buf = Vec::with_capacity(1024);
     let (bytes_received, _) = recv(server_client_fd, buf, None).await;
     let bytes_received =
       bytes_received.expect("recv should succeed but return 0");
@@ -276,7 +277,7 @@ proptest! {
       assert_eq!(sent as usize, test_data.len(), "Send failed");
 
       // Test recv on server side using lio (the only lio syscall in this test)
-      let recv_buf = vec![0u8; data_size];
+      let recv_buf = Vec::with_capacity(data_size);
       let (recv_result, received_buf) = recv(server_client_fd, recv_buf, None).await;
       let bytes_received = recv_result.expect("Recv failed") as usize;
 
@@ -0,0 +1,87 @@
+#![cfg(feature = "high")]
+mod common;
+
+use common::block_on;
+use lio::{renameat, unlinkat};
+use std::ffi::CString;
+
+#[test]
+fn test_renameat_basic() {
+  let _driver = common::init();
+  block_on(async {
+    let old = CString::new("/tmp/lio_test_renameat_old.txt").unwrap();
+    let new = CString::new("/tmp/lio_test_renameat_new.txt").unwrap();
+
+    unsafe {
+      let fd = libc::open(
+        old.as_ptr(),
+        libc::O_CREAT | libc::O_RDWR | libc::O_TRUNC,
+        0o644,
+      );
+      libc::write(fd, b"renamed".as_ptr() as *const libc::c_void, 7);
+      libc::close(fd);
+      libc::unlink(new.as_ptr());
+    }
+
+    renameat(
+      libc::AT_FDCWD,
+      "/tmp/lio_test_renameat_old.txt",
+      libc::AT_FDCWD,
+      "/tmp/lio_test_renameat_new.txt",
+    )
+    .unwrap()
+    .await
+    .expect("Failed to rename file");
+
+    unsafe {
+      assert_eq!(libc::access(old.as_ptr(), libc::F_OK), -1);
+      assert_eq!(libc::access(new.as_ptr(), libc::F_OK), 0);
+      libc::unlink(new.as_ptr());
+    }
+  });
+}
+
+#[test]
+fn test_renameat_missing_source() {
+  let _driver = common::init();
+  block_on(async {
+    let result = renameat(
+      libc::AT_FDCWD,
+      "/tmp/lio_test_renameat_does_not_exist.txt",
+      libc::AT_FDCWD,
+      "/tmp/lio_test_renameat_target.txt",
+    )
+    .unwrap()
+    .await;
+
+    assert!(result.is_err());
+  });
+}
+
+#[test]
+fn test_renameat_nul_in_path() {
+  let result = renameat(libc::AT_FDCWD, "/tmp/a\0b", libc::AT_FDCWD, "/tmp/c");
+  assert!(result.is_err());
+}
+
+#[test]
+fn test_unlinkat_basic() {
+  let _driver = common::init();
+  block_on(async {
+    let path = CString::new("/tmp/lio_test_unlinkat_basic.txt").unwrap();
+
+    unsafe {
+      let fd = libc::open(path.as_ptr(), libc::O_CREAT | libc::O_RDWR, 0o644);
+      libc::close(fd);
+    }
+
+    unlinkat(libc::AT_FDCWD, "/tmp/lio_test_unlinkat_basic.txt")
+      .unwrap()
+      .await
+      .expect("Failed to unlink file");
+
+    unsafe {
+      assert_eq!(libc::access(path.as_ptr(), libc::F_OK), -1);
+    }
+  });
+}
@@ -1,5 +1,5 @@
 #![cfg(feature = "high")]
-use lio::{accept, bind, connect, listen, recv, send, socket};
+use lio::{MsgFlags, accept, bind, connect, listen, recv, send, socket};
 use proptest::prelude::*;
 use socket2::{Domain, Protocol, Type};
 use std::mem::MaybeUninit;
@@ -227,10 +227,10 @@ fn test_send_with_flags() {
 
     let (server_client_fd, client_sock) = liten::join!(accept_fut, connect_fut);
 
-    // Send with flags (0 is a valid flag value)
+    // Send with explicitly empty flags
     let data = b"Data with flags".to_vec();
     let (bytes_sent, returned_buf) =
-      send(client_sock, data.clone(), Some(0)).await;
+      send(client_sock, data.clone(), Some(MsgFlags::NONE)).await;
     let bytes_sent = bytes_sent.expect("Failed to send with flags");
 
     assert_eq!(bytes_sent as usize, data.len());
@@ -439,7 +439,7 @@ proptest! {
       );
 
       // Receive the data on server side to verify it was sent
-      let recv_buf = vec![0u8; data_size];
+      let recv_buf = Vec::with_capacity(data_size);
       let (bytes_received, received_buf) = recv(server_client_fd, recv_buf, None)
         .await;
       let bytes_received = bytes_received.expect("Recv failed") as usize;
@@ -2,7 +2,7 @@
 use lio::{accept, bind, connect, listen, recv, send, shutdown, socket};
 use socket2::{Domain, Protocol, Type};
 use std::mem::MaybeUninit;
-use std::net::SocketAddr;
+use std::net::{Shutdown, SocketAddr};
 
 #[test]
 fn test_shutdown_write() {
@@ -49,7 +49,7 @@ fn test_shutdown_write() {
       liten::join!(accept_fut, client_fut);
 
     // Shutdown write on client
-    shutdown(client_sock, libc::SHUT_WR)
+    shutdown(client_sock, Shutdown::Write)
       .await
       .expect("Failed to shutdown write");
 
@@ -64,7 +64,7 @@ fn test_shutdown_write() {
     );
 
     // Server should be able to read EOF
-    let buf = vec![0u8; 100];
+    let buf = Vec::with_capacity(100);
     let (bytes_received, _) = recv(server_client_fd, buf, None).await;
     assert_eq!(
       bytes_received.expect("Recv should succeed"),
@@ -125,7 +125,7 @@ fn test_shutdown_read() {
       liten::join!(accept_fut, client_fut);
 
     // Shutdown read on client
-    shutdown(client_sock, libc::SHUT_RD)
+    shutdown(client_sock, Shutdown::Read)
       .await
       .expect("Failed to shutdown read");
 
@@ -135,7 +135,7 @@ fn test_shutdown_read() {
     assert_eq!(bytes_sent.expect("Send should succeed") as usize, data.len());
 
     // Server can receive
-    let buf = vec![0u8; 100];
+    let buf = Vec::with_capacity(100);
     let (bytes_received, received_buf) =
       recv(server_client_fd, buf, None).await;
     let bytes_received = bytes_received.expect("Recv should succeed") as usize;
@@ -195,7 +195,7 @@ fn test_shutdown_both() {
       liten::join!(accept_fut, client_fut);
 
     // Shutdown both directions on client
-    shutdown(client_sock, libc::SHUT_RDWR)
+    shutdown(client_sock, Shutdown::Both)
       .await
       .expect("Failed to shutdown both");
 
@@ -208,7 +208,7 @@ fn test_shutdown_both() {
     );
 
     // Server should receive EOF
-    let buf = vec![0u8; 100];
+    let buf = Vec::with_capacity(100);
     let (bytes_received, _) = recv(server_client_fd, buf, None).await;
     assert_eq!(
       bytes_received.expect("Recv should succeed"),
@@ -229,7 +229,7 @@ fn test_shutdown_both() {
 fn test_shutdown_invalid_fd() {
   liten::block_on(async {
     // Try to shutdown an invalid file descriptor
-    let result = shutdown(-1, libc::SHUT_RDWR).await;
+    let result = shutdown(-1, Shutdown::Both).await;
     assert!(result.is_err(), "Shutdown on invalid fd should fail");
   });
 }
@@ -272,7 +272,7 @@ fn test_shutdown_after_close() {
     }
 
     // Try to shutdown after close (should fail)
-    let result = shutdown(client_sock, libc::SHUT_RDWR).await;
+    let result = shutdown(client_sock, Shutdown::Both).await;
     assert!(result.is_err(), "Shutdown after close should fail");
 
     // Cleanup
@@ -324,12 +324,12 @@ fn test_shutdown_twice() {
     let (server_client_fd, client_sock) = liten::join!(accept_fut, client_fut);
 
     // First shutdown
-    shutdown(client_sock, libc::SHUT_WR)
+    shutdown(client_sock, Shutdown::Write)
       .await
       .expect("First shutdown should succeed");
 
     // Second shutdown on same direction
-    let result = shutdown(client_sock, libc::SHUT_WR).await;
+    let result = shutdown(client_sock, Shutdown::Write).await;
     // Some systems allow this, some don't - just verify it doesn't crash
     let _ = result;
 
@@ -386,7 +386,7 @@ fn test_shutdown_sequential_directions() {
       liten::join!(accept_fut, client_fut);
 
     // Shutdown write first
-    shutdown(client_sock, libc::SHUT_WR)
+    shutdown(client_sock, Shutdown::Write)
       .await
       .expect("Failed to shutdown write");
 
@@ -397,7 +397,7 @@ fn test_shutdown_sequential_directions() {
     };
 
     let client_recv_fut = async {
-      let buf = vec![0u8; 100];
+      let buf = Vec::with_capacity(100);
       recv(client_sock, buf, None).await
     };
 
@@ -410,7 +410,7 @@ fn test_shutdown_sequential_directions() {
     assert_eq!(&received_buf[..bytes_received], b"From server");
 
     // Now shutdown read
-    shutdown(client_sock, libc::SHUT_RD)
+    shutdown(client_sock, Shutdown::Read)
       .await
       .expect("Failed to shutdown read");
 
@@ -468,12 +468,12 @@ fn test_shutdown_before_data_sent() {
       liten::join!(accept_fut, client_fut);
 
     // Shutdown immediately after connection, before any data transfer
-    shutdown(client_sock, libc::SHUT_RDWR)
+    shutdown(client_sock, Shutdown::Both)
       .await
       .expect("Shutdown should succeed on fresh connection");
 
     // Verify server sees EOF
-    let buf = vec![0u8; 100];
+    let buf = Vec::with_capacity(100);
     let (bytes_received, _) = recv(server_client_fd, buf, None).await;
     assert_eq!(bytes_received.expect("Recv should succeed"), 0);
 
@@ -529,12 +529,12 @@ fn test_shutdown_ipv6() {
     let (client_sock, server_client_fd) = liten::join!(client_fut, accept_fut);
 
     // Shutdown write on IPv6 socket
-    shutdown(client_sock, libc::SHUT_WR)
+    shutdown(client_sock, Shutdown::Write)
       .await
       .expect("Failed to shutdown IPv6 socket");
 
     // Verify EOF
-    let buf = vec![0u8; 100];
+    let buf = Vec::with_capacity(100);
     let (bytes_received, _) = recv(server_client_fd, buf, None).await;
     assert_eq!(bytes_received.expect("Recv should succeed"), 0);
 
@@ -596,7 +596,7 @@ fn test_shutdown_concurrent() {
           liten::join!(accept_fut, client_fut);
 
         // Shutdown
-        shutdown(client_sock, libc::SHUT_RDWR)
+        shutdown(client_sock, Shutdown::Both)
           .await
           .expect("Concurrent shutdown failed");
 
@@ -663,12 +663,12 @@ fn test_shutdown_with_pending_data() {
     assert!(bytes_sent.is_ok(), "Send should succeed");
 
     // Shutdown write immediately (data may still be in transit)
-    shutdown(client_sock, libc::SHUT_WR)
+    shutdown(client_sock, Shutdown::Write)
       .await
       .expect("Shutdown should succeed");
 
     // Server should still be able to receive the data
-    let buf = vec![0u8; 100];
+    let buf = Vec::with_capacity(100);
     let (bytes_received, received_buf) =
       recv(server_client_fd, buf, None).await;
     let bytes_received = bytes_received.expect("Recv should succeed") as usize;
@@ -678,7 +678,7 @@ fn test_shutdown_with_pending_data() {
       assert_eq!(&received_buf[..bytes_received], data.as_slice());
 
       // Next read should be EOF
-      let buf2 = vec![0u8; 100];
+      let buf2 = Vec::with_capacity(100);
       let (bytes_received2, _) = recv(server_client_fd, buf2, None).await;
       assert_eq!(bytes_received2.expect("Recv should succeed"), 0);
     }
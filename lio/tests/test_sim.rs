@@ -0,0 +1,182 @@
+#![cfg(all(feature = "high", feature = "sim"))]
+mod common;
+
+use common::wait;
+use std::{
+  net::SocketAddr,
+  sync::{Arc, Mutex},
+  time::Duration,
+};
+
+use lio::{CompletionOrder, OpenFlags, Simulated};
+
+#[test]
+fn test_simulated_files() {
+  let _driver = common::init_with(|| {
+    lio::init_simulated(
+      Simulated::new()
+        .with_dir("/data")
+        .with_file("/etc/motd", b"hi!".to_vec()),
+    )
+  });
+  let fd = wait(
+    lio::openat(
+      libc::AT_FDCWD,
+      c"/data/hello.txt".into(),
+      OpenFlags::CREAT | OpenFlags::RDWR,
+    )
+    .get_receiver(),
+  )
+  .unwrap();
+
+  let (res, _) =
+    wait(lio::write(fd, b"hello world".to_vec(), 0).get_receiver());
+  assert_eq!(res.unwrap(), 11);
+  let (res, buf) = wait(lio::read(fd, Vec::with_capacity(5), 6).get_receiver());
+  assert_eq!(res.unwrap(), 5);
+  assert_eq!(buf, b"world");
+
+  wait(lio::truncate(fd, 5).get_receiver()).unwrap();
+  wait(lio::close(fd).get_receiver()).unwrap();
+
+  wait(
+    lio::renameat(
+      libc::AT_FDCWD,
+      "/data/hello.txt",
+      libc::AT_FDCWD,
+      "/tmp/moved",
+    )
+    .unwrap()
+    .get_receiver(),
+  )
+  .unwrap();
+  let err = wait(
+    lio::openat(libc::AT_FDCWD, c"/data/hello.txt".into(), OpenFlags::RDONLY)
+      .get_receiver(),
+  )
+  .unwrap_err();
+  assert_eq!(err.raw_os_error(), Some(libc::ENOENT));
+
+  // Relative to a directory fd.
+  let dir = wait(
+    lio::openat(libc::AT_FDCWD, c"/tmp".into(), OpenFlags::DIRECTORY)
+      .get_receiver(),
+  )
+  .unwrap();
+  let fd =
+    wait(lio::openat(dir, c"moved".into(), OpenFlags::RDONLY).get_receiver())
+      .unwrap();
+  let (res, buf) =
+    wait(lio::read(fd, Vec::with_capacity(16), 0).get_receiver());
+  assert_eq!(res.unwrap(), 5);
+  assert_eq!(&buf[..5], b"hello");
+
+  // Files given up front.
+  let fd = wait(
+    lio::openat(libc::AT_FDCWD, c"/etc/motd".into(), OpenFlags::RDONLY)
+      .get_receiver(),
+  )
+  .unwrap();
+  let (res, buf) =
+    wait(lio::read(fd, Vec::with_capacity(16), 0).get_receiver());
+  assert_eq!(res.unwrap(), 3);
+  assert_eq!(buf, b"hi!");
+}
+
+#[test]
+fn test_simulated_loopback() {
+  let _driver = common::init_with(|| lio::init_simulated(Simulated::new()));
+  let addr: SocketAddr = "127.0.0.1:8080".parse().unwrap();
+  let tcp = || {
+    wait(
+      lio::socket(socket2::Domain::IPV4, socket2::Type::STREAM, None)
+        .get_receiver(),
+    )
+    .unwrap()
+  };
+
+  let listener = tcp();
+  wait(lio::bind(listener, addr).get_receiver()).unwrap();
+  wait(lio::listen(listener, 16).get_receiver()).unwrap();
+
+  // Accept waits for a connection.
+  let accepted = lio::accept(listener).get_receiver();
+  for _ in 0..3 {
+    lio::tick();
+  }
+  assert!(accepted.try_recv().is_err());
+
+  let client = tcp();
+  wait(lio::connect(client, addr).get_receiver()).unwrap();
+  let (server, peer) = wait(accepted).unwrap();
+  let peer = peer.as_inet().unwrap();
+  assert_eq!(peer.ip(), addr.ip());
+  assert_ne!(peer.port(), addr.port());
+  let local = wait(lio::local_addr(client).get_receiver()).unwrap();
+  assert_eq!(local.as_inet(), Some(peer));
+  let remote = wait(lio::peer_addr(client).get_receiver()).unwrap();
+  assert_eq!(remote.as_inet(), Some(addr));
+
+  let received = lio::recv(server, Vec::with_capacity(32), None).get_receiver();
+  let (res, _) = wait(lio::send(client, b"ping".to_vec(), None).get_receiver());
+  assert_eq!(res.unwrap(), 4);
+  let (res, buf) = wait(received);
+  assert_eq!(res.unwrap(), 4);
+  assert_eq!(buf, b"ping");
+
+  // Closing one end is EOF for the other.
+  wait(lio::close(client).get_receiver()).unwrap();
+  let (res, _) =
+    wait(lio::recv(server, Vec::with_capacity(8), None).get_receiver());
+  assert_eq!(res.unwrap(), 0);
+  let (res, _) = wait(lio::send(server, b"gone".to_vec(), None).get_receiver());
+  assert_eq!(res.unwrap_err().raw_os_error(), Some(libc::EPIPE));
+
+  let other = tcp();
+  let err = wait(
+    lio::connect(other, "127.0.0.1:9999".parse::<SocketAddr>().unwrap())
+      .get_receiver(),
+  )
+  .unwrap_err();
+  assert_eq!(err.raw_os_error(), Some(libc::ECONNREFUSED));
+  let err = wait(lio::peer_addr(other).get_receiver()).unwrap_err();
+  assert_eq!(err.raw_os_error(), Some(libc::ENOTCONN));
+
+  // Port 0 picks an ephemeral port.
+  let ephemeral = tcp();
+  wait(
+    lio::bind(ephemeral, "127.0.0.1:0".parse::<SocketAddr>().unwrap())
+      .get_receiver(),
+  )
+  .unwrap();
+  let local = wait(lio::local_addr(ephemeral).get_receiver()).unwrap();
+  assert_ne!(local.as_inet().unwrap().port(), 0);
+}
+
+#[test]
+fn test_simulated_completion_order() {
+  let _driver = common::init_with(|| {
+    lio::init_simulated(
+      Simulated::new().completion_order(CompletionOrder::Reversed),
+    )
+  });
+  let order = Arc::new(Mutex::new(Vec::new()));
+  for i in 0..4 {
+    let order = order.clone();
+    lio::fsync(libc::AT_FDCWD)
+      .when_done(move |_| order.lock().unwrap().push(i));
+  }
+  lio::tick();
+  assert_eq!(*order.lock().unwrap(), [3, 2, 1, 0]);
+}
+
+#[test]
+fn test_simulated_virtual_time() {
+  let _driver = common::init_with(|| lio::init_simulated(Simulated::new()));
+  // Returns right away, even though an hour of virtual time passes.
+  let long = lio::timeout(Duration::from_secs(3600)).get_receiver();
+  let short = lio::timeout(Duration::from_secs(1)).get_receiver();
+  wait(short).unwrap();
+  assert!(long.try_recv().is_err());
+  wait(long).unwrap();
+}
@@ -0,0 +1,57 @@
+#![cfg(feature = "high")]
+mod common;
+
+use common::block_on;
+use lio::net::Socket;
+use socket2::{Domain, Type};
+use std::net::SocketAddr;
+
+fn tcp(domain: Domain) -> Socket {
+  block_on(Socket::new(domain, Type::STREAM, None)).unwrap()
+}
+
+#[test]
+fn test_sockname_ipv4() {
+  let _driver = common::init();
+  connected(Domain::IPV4, "127.0.0.1");
+}
+
+#[test]
+fn test_sockname_ipv6() {
+  let _driver = common::init();
+  connected(Domain::IPV6, "[::1]");
+}
+
+fn connected(domain: Domain, ip: &str) {
+  let listener = tcp(domain);
+  let any: SocketAddr = format!("{ip}:0").parse().unwrap();
+  block_on(listener.bind(any)).unwrap();
+  block_on(listener.listen(16)).unwrap();
+
+  let addr = block_on(listener.local_addr()).unwrap().as_inet().unwrap();
+  assert_eq!(addr.ip(), any.ip());
+  assert_ne!(addr.port(), 0);
+
+  let client = tcp(domain);
+  block_on(client.connect(addr)).unwrap();
+  let (conn, peer) = block_on(listener.accept()).unwrap();
+
+  assert_eq!(block_on(client.peer_addr()).unwrap().as_inet(), Some(addr));
+  assert_eq!(block_on(client.local_addr()).unwrap(), peer);
+  assert_eq!(block_on(conn.peer_addr()).unwrap(), peer);
+  assert_eq!(block_on(conn.local_addr()).unwrap().as_inet(), Some(addr));
+}
+
+#[test]
+fn test_sockname_unconnected() {
+  let _driver = common::init();
+  let socket = tcp(Domain::IPV4);
+  let local = block_on(socket.local_addr()).unwrap();
+  assert_eq!(local.as_inet(), Some("0.0.0.0:0".parse().unwrap()));
+
+  let err = block_on(socket.peer_addr()).unwrap_err();
+  assert_eq!(err.raw_os_error(), Some(libc::ENOTCONN));
+
+  let err = block_on(lio::local_addr(-1)).unwrap_err();
+  assert_eq!(err.raw_os_error(), Some(libc::EBADF));
+}
@@ -0,0 +1,140 @@
+#![cfg(feature = "high")]
+mod common;
+
+use common::block_on;
+use lio::{
+  net::Socket,
+  sockopt::{
+    KeepAlive, Linger, Raw, RecvBufferSize, ReuseAddr, ReusePort,
+    SendBufferSize, TcpKeepCount, TcpKeepIdle, TcpKeepInterval, TcpNoDelay,
+  },
+};
+use socket2::{Domain, Type};
+use std::{
+  os::fd::{AsRawFd, RawFd},
+  time::Duration,
+};
+
+fn tcp() -> Socket {
+  block_on(Socket::new(Domain::IPV4, Type::STREAM, None)).unwrap()
+}
+
+fn raw_int(fd: RawFd, level: i32, name: i32) -> libc::c_int {
+  let mut value: libc::c_int = 0;
+  let mut len = std::mem::size_of::<libc::c_int>() as libc::socklen_t;
+  let res = unsafe {
+    libc::getsockopt(
+      fd,
+      level,
+      name,
+      (&mut value as *mut libc::c_int).cast(),
+      &mut len,
+    )
+  };
+  assert_eq!(res, 0);
+  value
+}
+
+#[test]
+fn test_sockopt_bools() {
+  let _driver = common::init();
+  let socket = tcp();
+  let fd = socket.as_raw_fd();
+
+  assert!(!block_on(socket.getsockopt(ReuseAddr)).unwrap());
+  block_on(socket.setsockopt(ReuseAddr, true)).unwrap();
+  assert!(block_on(socket.getsockopt(ReuseAddr)).unwrap());
+  assert_eq!(raw_int(fd, libc::SOL_SOCKET, libc::SO_REUSEADDR), 1);
+
+  block_on(socket.setsockopt(ReusePort, true)).unwrap();
+  assert!(block_on(socket.getsockopt(ReusePort)).unwrap());
+
+  block_on(socket.setsockopt(KeepAlive, true)).unwrap();
+  assert!(block_on(socket.getsockopt(KeepAlive)).unwrap());
+  block_on(socket.setsockopt(KeepAlive, false)).unwrap();
+  assert!(!block_on(socket.getsockopt(KeepAlive)).unwrap());
+
+  block_on(socket.setsockopt(TcpNoDelay, true)).unwrap();
+  assert!(block_on(socket.getsockopt(TcpNoDelay)).unwrap());
+  assert_eq!(raw_int(fd, libc::IPPROTO_TCP, libc::TCP_NODELAY), 1);
+}
+
+#[test]
+fn test_sockopt_keepalive() {
+  let _driver = common::init();
+  let socket = tcp();
+  block_on(socket.setsockopt(TcpKeepIdle, Duration::from_secs(30))).unwrap();
+  assert_eq!(
+    block_on(socket.getsockopt(TcpKeepIdle)).unwrap(),
+    Duration::from_secs(30)
+  );
+
+  block_on(socket.setsockopt(TcpKeepInterval, Duration::from_secs(5))).unwrap();
+  assert_eq!(
+    block_on(socket.getsockopt(TcpKeepInterval)).unwrap(),
+    Duration::from_secs(5)
+  );
+
+  block_on(socket.setsockopt(TcpKeepCount, 3)).unwrap();
+  assert_eq!(block_on(socket.getsockopt(TcpKeepCount)).unwrap(), 3);
+}
+
+#[test]
+fn test_sockopt_buffers() {
+  let _driver = common::init();
+  let socket = tcp();
+  block_on(socket.setsockopt(RecvBufferSize, 64 * 1024)).unwrap();
+  assert!(block_on(socket.getsockopt(RecvBufferSize)).unwrap() >= 64 * 1024);
+
+  block_on(socket.setsockopt(SendBufferSize, 64 * 1024)).unwrap();
+  assert!(block_on(socket.getsockopt(SendBufferSize)).unwrap() >= 64 * 1024);
+}
+
+#[test]
+fn test_sockopt_linger() {
+  let _driver = common::init();
+  let socket = tcp();
+  assert_eq!(block_on(socket.getsockopt(Linger)).unwrap(), None);
+
+  block_on(socket.setsockopt(Linger, Some(Duration::from_secs(7)))).unwrap();
+  assert_eq!(
+    block_on(socket.getsockopt(Linger)).unwrap(),
+    Some(Duration::from_secs(7))
+  );
+
+  block_on(socket.setsockopt(Linger, None)).unwrap();
+  assert_eq!(block_on(socket.getsockopt(Linger)).unwrap(), None);
+}
+
+#[test]
+fn test_sockopt_raw() {
+  let _driver = common::init();
+  let socket = tcp();
+  let lowat =
+    unsafe { Raw::<libc::c_int>::new(libc::SOL_SOCKET, libc::SO_RCVLOWAT) };
+  block_on(socket.setsockopt(lowat, 16)).unwrap();
+  assert_eq!(block_on(socket.getsockopt(lowat)).unwrap(), 16);
+}
+
+#[test]
+fn test_sockopt_errors() {
+  let _driver = common::init();
+  let (read, write) = {
+    let mut fds = [0; 2];
+    assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+    (fds[0], fds[1])
+  };
+
+  let err = block_on(lio::setsockopt(read, ReuseAddr, true)).unwrap_err();
+  assert_eq!(err.raw_os_error(), Some(libc::ENOTSOCK));
+  let err = block_on(lio::getsockopt(read, TcpNoDelay)).unwrap_err();
+  assert_eq!(err.raw_os_error(), Some(libc::ENOTSOCK));
+
+  let err = block_on(lio::getsockopt(-1, ReuseAddr)).unwrap_err();
+  assert_eq!(err.raw_os_error(), Some(libc::EBADF));
+
+  unsafe {
+    libc::close(read);
+    libc::close(write);
+  }
+}
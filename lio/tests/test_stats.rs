@@ -0,0 +1,112 @@
+#![cfg(feature = "high")]
+mod common;
+
+use common::wait;
+
+fn open_null() -> i32 {
+  unsafe { libc::open(c"/dev/null".as_ptr(), libc::O_WRONLY) }
+}
+
+/// Submitted, completed and failed writes so far. Counters are process wide,
+/// so tests compare them with what the earlier ones left.
+fn writes() -> [u64; 3] {
+  lio::stats()
+    .op("Write")
+    .map_or([0; 3], |op| [op.submitted, op.completed, op.failed])
+}
+
+#[test]
+fn test_stats_counts() {
+  let _driver = common::init();
+  let before = writes();
+
+  let null = open_null();
+  let (res, _) = wait(lio::write(null, b"counted".to_vec(), 0).get_receiver());
+  assert_eq!(res.unwrap(), 7);
+  let (res, _) = wait(lio::write(-1, b"failed".to_vec(), 0).get_receiver());
+  assert!(res.is_err());
+  unsafe { libc::close(null) };
+
+  let [submitted, completed, failed] = writes();
+  assert_eq!(submitted - before[0], 2);
+  assert_eq!(completed - before[1], 2);
+  assert_eq!(failed - before[2], 1);
+
+  let stats = lio::stats();
+  let write = stats.op("Write").unwrap();
+  assert_eq!(write.outstanding(), 0);
+  assert!(write.max_latency > std::time::Duration::ZERO);
+  assert!(write.mean_latency().unwrap() <= write.max_latency);
+  assert!(stats.op("Read").is_none());
+  assert_eq!(stats.in_flight, 0);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_stats_ring() {
+  let _driver = common::init();
+
+  let ring = lio::stats().ring.expect("io_uring should report queue stats");
+  assert!(ring.sq_capacity > 0);
+  assert!(ring.cq_capacity >= ring.sq_capacity);
+  assert_eq!(ring.cq_overflow, 0);
+}
+
+#[cfg(feature = "histogram")]
+#[test]
+fn test_stats_histogram() {
+  let _driver = common::init();
+  let latencies = || {
+    lio::stats()
+      .op("Write")
+      .map_or(0, |op| op.latency.buckets().map(|(_, count)| count).sum::<u64>())
+  };
+  let before = latencies();
+
+  let null = open_null();
+  let (res, _) = wait(lio::write(null, b"counted".to_vec(), 0).get_receiver());
+  assert_eq!(res.unwrap(), 7);
+  unsafe { libc::close(null) };
+
+  assert_eq!(latencies() - before, 1);
+}
+
+#[test]
+fn test_stats_buffer_types() {
+  let _driver = common::init();
+  let before = writes();
+
+  // Other buffer types have counters of their own, which `op` adds up.
+  let null = open_null();
+  let (res, _) = wait(lio::write(null, b"vec".to_vec(), 0).get_receiver());
+  assert_eq!(res.unwrap(), 3);
+  let boxed: Box<[u8]> = Box::from(&b"boxed"[..]);
+  let (res, _) = wait(lio::write(null, boxed, 0).get_receiver());
+  assert_eq!(res.unwrap(), 5);
+  unsafe { libc::close(null) };
+
+  let stats = lio::stats();
+  let writes_by_type: Vec<_> =
+    stats.ops.iter().filter(|op| op.name == "Write").collect();
+  assert_eq!(writes_by_type.len(), 2);
+  assert_ne!(writes_by_type[0].type_name, writes_by_type[1].type_name);
+  assert_eq!(writes()[0] - before[0], 2);
+}
+
+#[test]
+fn test_stats_outlive_driver() {
+  let _lock = common::lock();
+  lio::init();
+  let before = writes();
+
+  let null = open_null();
+  let (res, _) = wait(lio::write(null, b"counted".to_vec(), 0).get_receiver());
+  assert_eq!(res.unwrap(), 7);
+  unsafe { libc::close(null) };
+
+  lio::exit();
+
+  let stats = lio::stats();
+  assert_eq!(writes()[1] - before[1], 1);
+  assert!(stats.ring.is_none());
+}
@@ -0,0 +1,146 @@
+#![cfg(all(feature = "high", feature = "tracing"))]
+mod common;
+
+use common::wait;
+use std::{
+  collections::HashMap,
+  fmt,
+  sync::{Arc, Mutex},
+};
+
+use tracing::{
+  Subscriber,
+  field::{Field, Visit},
+  span,
+};
+use tracing_subscriber::{
+  Layer, layer::Context, prelude::*, registry::LookupSpan,
+};
+
+/// Fields recorded on `lio_op` spans, and the messages of events inside them.
+#[derive(Default, Clone)]
+struct Recorded {
+  spans: Arc<Mutex<HashMap<u64, HashMap<String, String>>>>,
+  events: Arc<Mutex<Vec<(u64, String)>>>,
+}
+
+struct Fields<'a>(&'a mut HashMap<String, String>);
+
+impl Visit for Fields<'_> {
+  fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+    self.0.insert(field.name().into(), format!("{value:?}"));
+  }
+
+  fn record_str(&mut self, field: &Field, value: &str) {
+    self.0.insert(field.name().into(), value.into());
+  }
+}
+
+impl<S> Layer<S> for Recorded
+where
+  S: Subscriber + for<'a> LookupSpan<'a>,
+{
+  fn on_new_span(
+    &self,
+    attrs: &span::Attributes<'_>,
+    id: &span::Id,
+    _: Context<'_, S>,
+  ) {
+    if attrs.metadata().name() == "lio_op" {
+      let mut fields = HashMap::new();
+      attrs.record(&mut Fields(&mut fields));
+      self.spans.lock().unwrap().insert(id.into_u64(), fields);
+    }
+  }
+
+  fn on_record(
+    &self,
+    id: &span::Id,
+    values: &span::Record<'_>,
+    _: Context<'_, S>,
+  ) {
+    if let Some(fields) = self.spans.lock().unwrap().get_mut(&id.into_u64()) {
+      values.record(&mut Fields(fields));
+    }
+  }
+
+  fn on_event(&self, event: &tracing::Event<'_>, ctx: Context<'_, S>) {
+    let Some(span) = ctx.event_span(event) else { return };
+    let mut fields = HashMap::new();
+    event.record(&mut Fields(&mut fields));
+    if let Some(message) = fields.remove("message") {
+      self.events.lock().unwrap().push((span.id().into_u64(), message));
+    }
+  }
+}
+
+impl Recorded {
+  fn span_with(
+    &self,
+    key: &str,
+    value: &str,
+  ) -> (u64, HashMap<String, String>) {
+    let spans = self.spans.lock().unwrap();
+    let (id, fields) = spans
+      .iter()
+      .find(|(_, fields)| fields.get(key).map(String::as_str) == Some(value))
+      .unwrap_or_else(|| panic!("no lio_op span with {key} = {value}"));
+    (*id, fields.clone())
+  }
+
+  fn events_in(&self, span: u64) -> Vec<String> {
+    let events = self.events.lock().unwrap();
+    events
+      .iter()
+      .filter(|(id, _)| *id == span)
+      .map(|(_, m)| m.clone())
+      .collect()
+  }
+}
+
+/// Runs `f` on a driver of its own, recording the spans of its operations.
+fn record(f: impl FnOnce()) -> Recorded {
+  let recorded = Recorded::default();
+  let subscriber = tracing_subscriber::registry().with(recorded.clone());
+  tracing::subscriber::with_default(subscriber, || {
+    let _driver = common::init();
+    f();
+  });
+  recorded
+}
+
+#[test]
+fn test_tracing_completed() {
+  let null = unsafe { libc::open(c"/dev/null".as_ptr(), libc::O_WRONLY) };
+  let recorded = record(|| {
+    let (res, _) = wait(lio::write(null, b"hello".to_vec(), 0).get_receiver());
+    assert_eq!(res.unwrap(), 5);
+  });
+  unsafe { libc::close(null) };
+
+  let (span, fields) = recorded.span_with("fd", &null.to_string());
+  assert_eq!(fields["op"], "Write");
+  assert_eq!(fields["len"], "5");
+  assert_eq!(fields["result"], "5");
+  assert!(fields.contains_key("id"));
+  assert!(fields.contains_key("duration_us"));
+  #[cfg(target_os = "linux")]
+  assert_eq!(fields["opcode"], "23");
+
+  let events = recorded.events_in(span);
+  assert!(events.contains(&"submitting".to_string()), "{events:?}");
+  assert!(events.contains(&"completed".to_string()), "{events:?}");
+}
+
+#[test]
+fn test_tracing_failed() {
+  let recorded = record(|| {
+    let (res, _) = wait(lio::write(-1, b"bad".to_vec(), 0).get_receiver());
+    assert!(res.is_err());
+  });
+
+  let (span, fields) = recorded.span_with("fd", "-1");
+  assert_eq!(fields["errno"], libc::EBADF.to_string());
+  assert!(!fields.contains_key("result"));
+  assert!(recorded.events_in(span).contains(&"failed".to_string()));
+}
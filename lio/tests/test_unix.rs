@@ -0,0 +1,83 @@
+#![cfg(feature = "high")]
+mod common;
+
+use common::block_on;
+use lio::{SocketAddress, UnixAddress};
+use socket2::{Domain, Type};
+use std::{io, os::fd::RawFd};
+
+fn unix_socket() -> RawFd {
+  block_on(lio::socket(Domain::UNIX, Type::STREAM, None)).unwrap()
+}
+
+fn close(fds: &[RawFd]) {
+  for &fd in fds {
+    block_on(lio::close(fd)).unwrap();
+  }
+}
+
+#[test]
+fn test_unix_pathname() {
+  let _driver = common::init();
+  let path = std::env::temp_dir()
+    .join(format!("lio_test_unix_{}.sock", std::process::id()));
+  let _ = std::fs::remove_file(&path);
+  let addr = UnixAddress::from_pathname(&path).unwrap();
+  assert_eq!(addr.as_pathname(), Some(path.as_path()));
+
+  let listener = unix_socket();
+  block_on(lio::bind(listener, addr.clone())).unwrap();
+  block_on(lio::listen(listener, 16)).unwrap();
+  let local = block_on(lio::local_addr(listener)).unwrap();
+  assert_eq!(local.as_unix(), Some(&addr));
+
+  let client = unix_socket();
+  block_on(lio::connect(client, addr)).unwrap();
+  let (conn, peer) = block_on(lio::accept(listener)).unwrap();
+  assert_eq!(peer.family(), libc::AF_UNIX);
+  assert!(peer.as_unix().unwrap().is_unnamed());
+
+  let (res, _) = block_on(lio::send_all(client, b"ping".to_vec()));
+  res.unwrap();
+  let (res, buf) = block_on(lio::recv_exact(conn, Vec::with_capacity(4)));
+  res.unwrap();
+  assert_eq!(buf, b"ping");
+
+  close(&[listener, client, conn]);
+  std::fs::remove_file(&path).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_unix_abstract_name() {
+  let _driver = common::init();
+  let name = format!("lio_test_unix_{}", std::process::id());
+  let addr = UnixAddress::from_abstract_name(&name).unwrap();
+  assert_eq!(addr.as_abstract_name(), Some(name.as_bytes()));
+  assert_eq!(addr.as_pathname(), None);
+
+  let listener = unix_socket();
+  block_on(lio::bind(listener, addr.clone())).unwrap();
+  block_on(lio::listen(listener, 16)).unwrap();
+
+  let client = unix_socket();
+  block_on(lio::connect(client, SocketAddress::from(addr))).unwrap();
+  let (conn, _) = block_on(lio::accept(listener)).unwrap();
+
+  close(&[listener, client, conn]);
+}
+
+#[test]
+fn test_unix_invalid() {
+  let _driver = common::init();
+  let err = UnixAddress::from_pathname("a".repeat(200)).unwrap_err();
+  assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+  let err = UnixAddress::from_pathname("a\0b").unwrap_err();
+  assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+  let client = unix_socket();
+  let missing = UnixAddress::from_pathname("/nonexistent/lio.sock").unwrap();
+  let err = block_on(lio::connect(client, missing)).unwrap_err();
+  assert_eq!(err.raw_os_error(), Some(libc::ENOENT));
+  close(&[client]);
+}
@@ -0,0 +1,102 @@
+#![cfg(feature = "high")]
+mod common;
+
+use common::block_on;
+use lio::waitid;
+use std::os::unix::process::ExitStatusExt;
+use std::process::Command;
+
+/// Spawns a child, which the test then reaps with `waitid`.
+fn spawn(command: &mut Command) -> u32 {
+  command.spawn().expect("Failed to spawn child").id()
+}
+
+#[test]
+fn test_waitid_success() {
+  let _driver = common::init();
+  let pid = spawn(&mut Command::new("true"));
+
+  let status = block_on(waitid(pid)).expect("Failed to wait for child");
+  assert!(status.success());
+}
+
+#[test]
+fn test_waitid_exit_code() {
+  let _driver = common::init();
+  let pid = spawn(Command::new("sh").args(["-c", "exit 3"]));
+
+  let status = block_on(waitid(pid)).expect("Failed to wait for child");
+  assert_eq!(status.code(), Some(3));
+}
+
+#[test]
+fn test_waitid_killed_by_signal() {
+  let _driver = common::init();
+  let pid = spawn(Command::new("sleep").arg("10"));
+
+  let progress = waitid(pid);
+  unsafe { libc::kill(pid as libc::pid_t, libc::SIGKILL) };
+
+  let status = block_on(progress).expect("Failed to wait for child");
+  assert_eq!(status.code(), None);
+  assert_eq!(status.signal(), Some(libc::SIGKILL));
+}
+
+#[test]
+fn test_waitid_waits_for_exit() {
+  let _driver = common::init();
+  let start = std::time::Instant::now();
+  let pid = spawn(Command::new("sleep").arg("0.2"));
+
+  let status = block_on(waitid(pid)).expect("Failed to wait for child");
+  assert!(status.success());
+  assert!(start.elapsed() >= std::time::Duration::from_millis(200));
+}
+
+#[test]
+fn test_waitid_not_a_child() {
+  let _driver = common::init();
+  // pid 1 is never a child of the test process.
+  let result = block_on(waitid(1));
+  assert!(result.is_err());
+}
+
+#[test]
+fn test_waitid_concurrent() {
+  let _driver = common::init();
+  let pids: Vec<_> = (0..5)
+    .map(|code| spawn(Command::new("sh").args(["-c", &format!("exit {code}")])))
+    .collect();
+
+  let progresses: Vec<_> = pids.iter().map(|&pid| waitid(pid)).collect();
+
+  for (code, progress) in progresses.into_iter().enumerate() {
+    let status = block_on(progress).expect("Failed to wait for child");
+    assert_eq!(status.code(), Some(code as i32));
+  }
+}
+
+/// Kernels without `IORING_OP_WAITID` poll a pidfd instead.
+#[cfg(feature = "fault_injection")]
+#[test]
+fn test_waitid_pidfd_fallback() {
+  let _driver = common::init();
+  use lio::{Fault, FaultRule, Faults};
+
+  lio::inject_faults(
+    Faults::new().rule(FaultRule::new(Fault::Unsupported).op("WaitId")),
+  );
+  let running = spawn(Command::new("sh").args(["-c", "sleep 0.1; exit 4"]));
+  let running = waitid(running);
+  let exited = spawn(&mut Command::new("true"));
+  std::thread::sleep(std::time::Duration::from_millis(50));
+  let exited = waitid(exited);
+  let not_a_child = waitid(1);
+  lio::clear_faults();
+
+  let status = block_on(running).expect("Failed to wait for child");
+  assert_eq!(status.code(), Some(4));
+  let status = block_on(exited).expect("Failed to wait for child");
+  assert!(status.success());
+  assert!(block_on(not_a_child).is_err());
+}